@@ -1,17 +1,52 @@
 extern crate criterion;
 extern crate querable;
 
-use std::collections::HashMap;
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    collections::HashMap,
+    sync::atomic::{AtomicUsize, Ordering},
+};
 
-use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
 
 use querable::{
     default::{DefaultTokenizer, SlashTokenizer},
     error::Error,
     kind::QueryKind,
-    types::Queryable,
+    step::Step,
+    types::{Queryable, VALUES_SEGMENT},
 };
 
+// system allocator that counts allocations, so the miss path could be
+// measured by allocations as well as by time
+struct CountingAlloc;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAlloc = CountingAlloc;
+
+// number of allocations done by a single `f` call
+fn allocations<F, R>(f: F) -> usize
+where
+    F: FnOnce() -> R,
+{
+    let before = ALLOCATIONS.load(Ordering::Relaxed);
+    black_box(f());
+    ALLOCATIONS.load(Ordering::Relaxed) - before
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum Number {
     Integer(i64),
@@ -99,6 +134,7 @@ value_conv!(
 // array!["test", 1, 2 "test"]
 macro_rules! array {
     [] => (Value::Array(Vec::<Value>::new()));
+    [$val:expr; $n:expr] => (Value::Array(vec![Value::from($val); $n]));
     [$($val:expr),*] => (Value::Array(<[_]>::into_vec(Box::new([$(Value::from($val)),*]))));
 }
 
@@ -134,6 +170,8 @@ macro_rules! dict {
 }
 
 impl Queryable for Value {
+    type Output = Value;
+
     #[inline]
     fn query_kind(&self) -> Option<QueryKind> {
         match self {
@@ -148,25 +186,78 @@ impl Queryable for Value {
             Value::Dictionary(d) => d
                 .get(path)
                 .cloned()
-                .ok_or_else(|| Error::KeyNotExist(String::from(path))),
+                .ok_or_else(|| Error::key_not_exist(String::from(path))),
             Value::Array(_) => Err(Error::TypeError(
                 String::from(path),
                 QueryKind::Array,
                 QueryKind::Dictionary,
             )),
-            _ => Err(Error::UnknownType(String::from(path))),
+            _ => Err(Error::unknown_type(String::from(path))),
         }
     }
 
     fn query_array(&self, idx: usize) -> Result<Self, Error> {
         match self {
-            Value::Array(d) => d.get(idx).cloned().ok_or(Error::IndexNotExist(idx)),
+            Value::Array(d) => d.get(idx).cloned().ok_or(Error::IndexNotExist {
+                index: idx,
+                len: d.len(),
+            }),
             Value::Dictionary(_) => Err(Error::TypeError(
                 format!("[{}]", idx),
                 QueryKind::Dictionary,
                 QueryKind::Array,
             )),
-            _ => Err(Error::UnknownType(format!("[{}]", idx))),
+            _ => Err(Error::unknown_type(format!("[{}]", idx))),
+        }
+    }
+
+    fn into_query_dict(self, path: &str) -> Result<Self, Error> {
+        match self {
+            Value::Dictionary(mut d) => d
+                .remove(path)
+                .ok_or_else(|| Error::key_not_exist(String::from(path))),
+            other => other.query_dict(path),
+        }
+    }
+
+    fn into_query_array(self, idx: usize) -> Result<Self, Error> {
+        match self {
+            Value::Array(mut d) if idx < d.len() => Ok(d.swap_remove(idx)),
+            other => other.query_array(idx),
+        }
+    }
+
+    fn query_values(&self) -> Result<Self, Error> {
+        match self {
+            Value::Dictionary(d) => {
+                let mut entries = d.iter().collect::<Vec<_>>();
+                entries.sort_by_key(|&(k, _)| k);
+
+                Ok(Value::Array(
+                    entries.into_iter().map(|(_, v)| v.clone()).collect(),
+                ))
+            }
+            Value::Array(_) => Err(Error::TypeError(
+                String::from(VALUES_SEGMENT),
+                QueryKind::Array,
+                QueryKind::Dictionary,
+            )),
+            _ => Err(Error::unknown_type(VALUES_SEGMENT)),
+        }
+    }
+
+    fn children(&self) -> Vec<(Step<'_>, &Self)> {
+        match self {
+            Value::Dictionary(d) => d
+                .iter()
+                .map(|(k, v)| (Step::Key(k.as_str().into()), v))
+                .collect(),
+            Value::Array(d) => d
+                .iter()
+                .enumerate()
+                .map(|(idx, v)| (Step::Index(idx), v))
+                .collect(),
+            _ => Vec::new(),
         }
     }
 }
@@ -281,5 +372,88 @@ pub fn querable_lookup(c: &mut Criterion) {
     }
 }
 
-criterion_group!(benches, querable_lookup);
+pub fn querable_into_query(c: &mut Criterion) {
+    let data = array![
+        array![array![array![array![array![array![array![array![
+            array![array![1]]
+        ]]]]]]]],
+        dict! {
+            "child" => dict! {
+                "child" => dict! {
+                    "child" => dict! {
+                        "child" => dict! {
+                            "child" => dict! {
+                                "child" => array![1; 1024],
+                                "sibling" => array!["sibling"; 1024],
+                            },
+                            "sibling" => array!["sibling"; 1024],
+                        },
+                    },
+                },
+            },
+        }
+    ];
+
+    let queries = vec![
+        "[0].[0].[0].[0].[0].[0].[0]",
+        "[1].child.child.child.child.child",
+        "[1].child.child.child.child.child.child.[0]",
+    ];
+
+    for query in queries {
+        c.bench_with_input(
+            BenchmarkId::new("query_clone", format!("{}-{}", "sample_2", query)),
+            &query,
+            |b, &q| b.iter(|| assert!(data.query::<DefaultTokenizer>(q).is_ok())),
+        );
+
+        c.bench_with_input(
+            BenchmarkId::new("into_query_move", format!("{}-{}", "sample_2", query)),
+            &query,
+            |b, &q| {
+                b.iter_batched(
+                    || data.clone(),
+                    |d| assert!(d.into_query::<DefaultTokenizer>(q).is_ok()),
+                    BatchSize::LargeInput,
+                )
+            },
+        );
+    }
+}
+
+// lookups that miss, the allocations of a single lookup (cloned nodes along
+// the way included) are printed under the bench name
+pub fn querable_lookup_miss(c: &mut Criterion) {
+    let data = array![dict! {
+        "id" => 12,
+        "child" => dict! { "id" => 20 },
+    }];
+
+    let queries = vec!["[0].missing", "[0].child.missing", "[0].id.@values", "[1]"];
+
+    for query in queries {
+        let name = "lookup_miss_default_tokenizer";
+        let parameter = format!("{}-{}", "sample_3", query);
+
+        println!(
+            "{}/{}: {} allocations",
+            name,
+            parameter,
+            allocations(|| querable::lookup::<_, _, DefaultTokenizer>(&data, query))
+        );
+
+        c.bench_with_input(BenchmarkId::new(name, parameter), &query, |b, &q| {
+            b.iter(|| {
+                assert!(querable::lookup::<_, _, DefaultTokenizer>(&data, black_box(q)).is_err())
+            })
+        });
+    }
+}
+
+criterion_group!(
+    benches,
+    querable_lookup,
+    querable_into_query,
+    querable_lookup_miss
+);
 criterion_main!(benches);
@@ -0,0 +1,154 @@
+extern crate criterion;
+extern crate querable;
+
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+
+use querable::{
+    default::{DefaultTokenizer, SlashTokenizer},
+    error::KeyError,
+    types::Tokenizer,
+};
+
+// system allocator that counts allocations, so both tokenizer designs
+// could be compared by allocations as well as by time
+struct CountingAlloc;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAlloc = CountingAlloc;
+
+// number of allocations done by a single `f` call
+fn allocations<F, R>(f: F) -> usize
+where
+    F: FnOnce() -> R,
+{
+    let before = ALLOCATIONS.load(Ordering::Relaxed);
+    black_box(f());
+    ALLOCATIONS.load(Ordering::Relaxed) - before
+}
+
+// walk the whole query by repeatedly calling `dict_parse` on the rest
+fn tokenize_all<T: Tokenizer>(query: &str) -> Result<usize, KeyError> {
+    let mut count = 0;
+    let mut rest = Some(query);
+
+    while let Some(current) = rest {
+        let (key, next) = T::dict_parse(current)?;
+        count += key.map_or(0, |_| 1);
+        rest = next;
+    }
+
+    Ok(count)
+}
+
+// walk the whole query through the `segments` iterator
+fn tokenize_segments<T: Tokenizer>(query: &str) -> Result<usize, KeyError> {
+    T::segments(query).try_fold(0, |count, key| key.map(|_| count + 1))
+}
+
+pub fn tokenizer_parse(c: &mut Criterion) {
+    let queries = vec![
+        "[0]",
+        "[0].id",
+        "[0].child.id",
+        "[0].child.child.child.child.child.child",
+        "[1].[0].[0].[0].[0].[0].[0]",
+        "[2].child.child.child.child.child.child.child.child.child.child.child",
+    ];
+
+    for query in queries {
+        c.bench_with_input(
+            BenchmarkId::new("dict_parse_default_tokenizer", query),
+            &query,
+            |b, &q| b.iter(|| assert!(tokenize_all::<DefaultTokenizer>(black_box(q)).is_ok())),
+        );
+    }
+
+    let queries = vec![
+        "/0",
+        "/0/id",
+        "/0/child/id",
+        "/0/child/child/child/child/child/child",
+        "/1/0/0/0/0/0/0",
+        "/2/child/child/child/child/child/child/child/child/child/child/child",
+    ];
+
+    for query in queries {
+        c.bench_with_input(
+            BenchmarkId::new("dict_parse_slash_tokenizer", query),
+            &query,
+            |b, &q| b.iter(|| assert!(tokenize_all::<SlashTokenizer>(black_box(q)).is_ok())),
+        );
+    }
+}
+
+type Walk = fn(&str) -> Result<usize, KeyError>;
+
+// `State` tuple loop against the `segments` iterator over the same queries,
+// benches are named `tokenize_{state,segments}_<tokenizer>/<sample>-<query>`
+// and the allocations of a single walk are printed under the same name
+pub fn tokenizer_design(c: &mut Criterion) {
+    let samples: [(&str, Walk, Walk, [&str; 2]); 2] = [
+        (
+            "default_tokenizer",
+            tokenize_all::<DefaultTokenizer>,
+            tokenize_segments::<DefaultTokenizer>,
+            [
+                "[0].child.child.child.child.child.child",
+                "[2].child.child.child.child.child.child.child.child.child.child.child",
+            ],
+        ),
+        (
+            "slash_tokenizer",
+            tokenize_all::<SlashTokenizer>,
+            tokenize_segments::<SlashTokenizer>,
+            [
+                "/0/child/child/child/child/child/child",
+                "/2/child/child/child/child/child/child/child/child/child/child/child",
+            ],
+        ),
+    ];
+
+    for (tokenizer, state, segments, queries) in samples.iter() {
+        for query in queries.iter() {
+            for (design, walk) in [("state", state), ("segments", segments)].iter() {
+                let name = format!("tokenize_{}_{}", design, tokenizer);
+                let parameter = format!("{}-{}", "sample_1", query);
+
+                // both designs should see the same segments
+                assert_eq!(walk(query), state(query));
+
+                println!(
+                    "{}/{}: {} allocations",
+                    name,
+                    parameter,
+                    allocations(|| walk(query))
+                );
+
+                c.bench_with_input(BenchmarkId::new(name, parameter), query, |b, &q| {
+                    b.iter(|| assert!(walk(black_box(q)).is_ok()))
+                });
+            }
+        }
+    }
+}
+
+criterion_group!(benches, tokenizer_parse, tokenizer_design);
+criterion_main!(benches);
@@ -0,0 +1,13 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+
+use querable::{default::DefaultTokenizer, types::Tokenizer};
+
+fuzz_target!(|key: &str| {
+    if DefaultTokenizer::index_parse(key).is_ok() {
+        // only a single pair of brackets wrapping the index are allowed
+        assert!(key.starts_with('[') && key.ends_with(']'));
+        assert_eq!(key.matches('[').count(), 1);
+        assert_eq!(key.matches(']').count(), 1);
+    }
+});
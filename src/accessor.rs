@@ -0,0 +1,318 @@
+//!
+//! Accessor traits for document types.
+//!
+//! Most of the [Queryable](crate::types::Queryable) implementations only
+//! differ on how to reach the inner dictionary or array of `Self`.
+//! Implementing [AsDict](AsDict) and [AsArray](AsArray), then calling
+//! [queryable_impl](crate::queryable_impl) derives the rest of it.
+//!
+use std::{
+    collections::{BTreeMap, HashMap},
+    hash::BuildHasher,
+};
+
+use crate::{error::Error, kind::QueryKind, step::Step, types::VALUES_SEGMENT};
+
+///
+/// Dictionary of `V`, keyed by `String`.
+///
+pub type Map<V> = HashMap<String, V>;
+
+///
+/// Access `Self` as a dictionary, `None` in case `Self` isn't one.
+///
+pub trait AsDict: Sized {
+    fn as_dict(&self) -> Option<&Map<Self>>;
+}
+
+///
+/// Access `Self` as an array, `None` in case `Self` isn't one.
+///
+pub trait AsArray: Sized {
+    fn as_array(&self) -> Option<&[Self]>;
+}
+
+///
+/// Map keyed by `String`, the dictionary side of the
+/// [Queryable](crate::types::Queryable) implementation of map types.
+///
+/// `keys` follows the natural order of the map, e.g. sorted for
+/// `BTreeMap` and insertion order for `IndexMap` (behind the `indexmap`
+/// feature). `HashMap` has no order of its own, so its keys are sorted.
+///
+/// ```rust
+/// use std::collections::BTreeMap;
+/// use querable::accessor::MapLike;
+///
+/// let mut map = BTreeMap::new();
+/// map.insert(String::from("b"), 2);
+/// map.insert(String::from("a"), 1);
+///
+/// assert_eq!(MapLike::keys(&map), vec!["a", "b"]);
+/// assert_eq!(MapLike::get(&map, "b"), Some(&2));
+/// ```
+///
+pub trait MapLike {
+    type Value;
+
+    fn get(&self, key: &str) -> Option<&Self::Value>;
+
+    fn keys(&self) -> Vec<&str>;
+
+    fn len(&self) -> usize;
+
+    #[inline]
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<V, S> MapLike for HashMap<String, V, S>
+where
+    S: BuildHasher,
+{
+    type Value = V;
+
+    #[inline]
+    fn get(&self, key: &str) -> Option<&V> {
+        HashMap::get(self, key)
+    }
+
+    fn keys(&self) -> Vec<&str> {
+        let mut keys = HashMap::keys(self).map(String::as_str).collect::<Vec<_>>();
+        keys.sort_unstable();
+        keys
+    }
+
+    #[inline]
+    fn len(&self) -> usize {
+        HashMap::len(self)
+    }
+}
+
+impl<V> MapLike for BTreeMap<String, V> {
+    type Value = V;
+
+    #[inline]
+    fn get(&self, key: &str) -> Option<&V> {
+        BTreeMap::get(self, key)
+    }
+
+    #[inline]
+    fn keys(&self) -> Vec<&str> {
+        BTreeMap::keys(self).map(String::as_str).collect()
+    }
+
+    #[inline]
+    fn len(&self) -> usize {
+        BTreeMap::len(self)
+    }
+}
+
+#[cfg(feature = "indexmap")]
+impl<V, S> MapLike for indexmap::IndexMap<String, V, S>
+where
+    S: BuildHasher,
+{
+    type Value = V;
+
+    #[inline]
+    fn get(&self, key: &str) -> Option<&V> {
+        indexmap::IndexMap::get(self, key)
+    }
+
+    #[inline]
+    fn keys(&self) -> Vec<&str> {
+        indexmap::IndexMap::keys(self).map(String::as_str).collect()
+    }
+
+    #[inline]
+    fn len(&self) -> usize {
+        indexmap::IndexMap::len(self)
+    }
+}
+
+#[doc(hidden)]
+pub fn query_kind<V>(v: &V) -> Option<QueryKind>
+where
+    V: AsDict + AsArray,
+{
+    if v.as_dict().is_some() {
+        Some(QueryKind::Dictionary)
+    } else if v.as_array().is_some() {
+        Some(QueryKind::Array)
+    } else {
+        None
+    }
+}
+
+#[doc(hidden)]
+pub fn query_dict<V>(v: &V, path: &str) -> Result<V, Error>
+where
+    V: AsDict + AsArray + Clone,
+{
+    match (v.as_dict(), v.as_array()) {
+        (Some(d), _) => d
+            .get(path)
+            .cloned()
+            .ok_or_else(|| Error::key_not_exist(String::from(path))),
+        (None, Some(_)) => Err(Error::TypeError(
+            String::from(path),
+            QueryKind::Dictionary,
+            QueryKind::Array,
+        )),
+        (None, None) => Err(Error::unknown_type(String::from(path))),
+    }
+}
+
+#[doc(hidden)]
+pub fn query_array<V>(v: &V, idx: usize) -> Result<V, Error>
+where
+    V: AsDict + AsArray + Clone,
+{
+    match (v.as_array(), v.as_dict()) {
+        (Some(d), _) => d.get(idx).cloned().ok_or(Error::IndexNotExist {
+            index: idx,
+            len: d.len(),
+        }),
+        (None, Some(_)) => Err(Error::TypeError(
+            format!("[{}]", idx),
+            QueryKind::Array,
+            QueryKind::Dictionary,
+        )),
+        (None, None) => Err(Error::unknown_type(format!("[{}]", idx))),
+    }
+}
+
+#[doc(hidden)]
+pub fn query_values<V>(v: &V) -> Result<V, Error>
+where
+    V: AsDict + AsArray + Clone + From<Vec<V>>,
+{
+    match (v.as_dict(), v.as_array()) {
+        (Some(d), _) => {
+            let mut entries = d.iter().collect::<Vec<_>>();
+            entries.sort_by_key(|&(k, _)| k);
+
+            Ok(V::from(
+                entries.into_iter().map(|(_, v)| v.clone()).collect(),
+            ))
+        }
+        (None, Some(_)) => Err(Error::TypeError(
+            String::from(VALUES_SEGMENT),
+            QueryKind::Dictionary,
+            QueryKind::Array,
+        )),
+        (None, None) => Err(Error::unknown_type(VALUES_SEGMENT)),
+    }
+}
+
+#[doc(hidden)]
+pub fn children<V>(v: &V) -> Vec<(Step<'_>, &V)>
+where
+    V: AsDict + AsArray,
+{
+    match (v.as_dict(), v.as_array()) {
+        (Some(d), _) => d
+            .iter()
+            .map(|(k, v)| (Step::Key(k.as_str().into()), v))
+            .collect(),
+        (None, Some(d)) => d
+            .iter()
+            .enumerate()
+            .map(|(idx, v)| (Step::Index(idx), v))
+            .collect(),
+        (None, None) => Vec::new(),
+    }
+}
+
+///
+/// Implement [Queryable](crate::types::Queryable) for a type through its
+/// [AsDict](AsDict) and [AsArray](AsArray) accessors.
+///
+/// The type needs to be `Clone`, and `From<Vec<Self>>` so
+/// [VALUES_SEGMENT](VALUES_SEGMENT) could be resolved into an array.
+///
+/// ```rust
+/// use querable::{
+///     accessor::{AsArray, AsDict, Map},
+///     default::DefaultTokenizer,
+///     lookup, queryable_impl,
+/// };
+///
+/// #[derive(Debug, Clone, PartialEq)]
+/// enum Doc {
+///     Leaf(i64),
+///     List(Vec<Doc>),
+///     Object(Map<Doc>),
+/// }
+///
+/// impl AsDict for Doc {
+///     fn as_dict(&self) -> Option<&Map<Doc>> {
+///         match self {
+///             Doc::Object(d) => Some(d),
+///             _ => None,
+///         }
+///     }
+/// }
+///
+/// impl AsArray for Doc {
+///     fn as_array(&self) -> Option<&[Doc]> {
+///         match self {
+///             Doc::List(d) => Some(d),
+///             _ => None,
+///         }
+///     }
+/// }
+///
+/// impl From<Vec<Doc>> for Doc {
+///     fn from(d: Vec<Doc>) -> Self {
+///         Doc::List(d)
+///     }
+/// }
+///
+/// queryable_impl!(Doc);
+///
+/// let doc = Doc::List(vec![Doc::Leaf(1)]);
+///
+/// assert_eq!(lookup::<_, _, DefaultTokenizer>(&doc, "[0]"), Ok(Doc::Leaf(1)));
+/// ```
+///
+#[macro_export]
+macro_rules! queryable_impl {
+    ($t:ty) => {
+        impl $crate::types::Queryable for $t {
+            type Output = $t;
+
+            #[inline]
+            fn query_kind(&self) -> Option<$crate::kind::QueryKind> {
+                $crate::accessor::query_kind(self)
+            }
+
+            #[inline]
+            fn query_self(&self) -> Result<$t, $crate::error::Error> {
+                Ok(self.clone())
+            }
+
+            #[inline]
+            fn query_dict(&self, path: &str) -> Result<$t, $crate::error::Error> {
+                $crate::accessor::query_dict(self, path)
+            }
+
+            #[inline]
+            fn query_array(&self, idx: usize) -> Result<$t, $crate::error::Error> {
+                $crate::accessor::query_array(self, idx)
+            }
+
+            #[inline]
+            fn query_values(&self) -> Result<$t, $crate::error::Error> {
+                $crate::accessor::query_values(self)
+            }
+
+            #[inline]
+            fn children(&self) -> Vec<($crate::step::Step<'_>, &$t)> {
+                $crate::accessor::children(self)
+            }
+        }
+    };
+}
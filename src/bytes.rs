@@ -0,0 +1,90 @@
+//!
+//! Binary data as a [Queryable](Queryable) array.
+//!
+//! [ByteArray](ByteArray) resolves `[n]` straight into the `n`-th byte,
+//! e.g. for binary-ish data that would otherwise be modeled as an array of
+//! small integers in the document type.
+//!
+use crate::{
+    error::Error,
+    kind::QueryKind,
+    step::Step,
+    types::{Queryable, VALUES_SEGMENT},
+};
+
+///
+/// Array of bytes, querying it resolves into a single `u8`.
+///
+/// ```rust
+/// use querable::{bytes::ByteArray, default::DefaultTokenizer, types::Queryable};
+///
+/// let data = ByteArray(vec![0xca, 0xfe]);
+///
+/// assert_eq!(data.query::<DefaultTokenizer>("[1]"), Ok(0xfe));
+/// ```
+///
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ByteArray(pub Vec<u8>);
+
+impl From<Vec<u8>> for ByteArray {
+    #[inline]
+    fn from(bytes: Vec<u8>) -> Self {
+        ByteArray(bytes)
+    }
+}
+
+impl From<&[u8]> for ByteArray {
+    #[inline]
+    fn from(bytes: &[u8]) -> Self {
+        ByteArray(bytes.to_vec())
+    }
+}
+
+impl Queryable for ByteArray {
+    type Output = u8;
+
+    #[inline]
+    fn query_kind(&self) -> Option<QueryKind> {
+        Some(QueryKind::Array)
+    }
+
+    #[inline]
+    fn from_default_str(text: &str) -> Option<u8> {
+        u8::from_default_str(text)
+    }
+
+    #[inline]
+    fn query_dict(&self, path: &str) -> Result<u8, Error> {
+        Err(Error::TypeError(
+            String::from(path),
+            QueryKind::Dictionary,
+            QueryKind::Array,
+        ))
+    }
+
+    #[inline]
+    fn query_array(&self, idx: usize) -> Result<u8, Error> {
+        self.0.get(idx).copied().ok_or(Error::IndexNotExist {
+            index: idx,
+            len: self.0.len(),
+        })
+    }
+
+    #[inline]
+    fn query_values(&self) -> Result<u8, Error> {
+        Err(Error::TypeError(
+            String::from(VALUES_SEGMENT),
+            QueryKind::Dictionary,
+            QueryKind::Array,
+        ))
+    }
+
+    #[inline]
+    fn children(&self) -> Vec<(Step<'_>, &u8)> {
+        self.0
+            .iter()
+            .enumerate()
+            .map(|(idx, byte)| (Step::Index(idx), byte))
+            .collect()
+    }
+}
@@ -0,0 +1,96 @@
+//!
+//! Thread-safe cache of resolved queries.
+//!
+//! Serving many lookups over the same immutable document could skip the
+//! traversal for paths that already being resolved. [CachedQueryable](CachedQueryable)
+//! keeps the resolved values, and could be shared between threads
+//! (e.g. behind an `Arc`) as long as the document is `Send + Sync`.
+//!
+use std::{
+    any::TypeId,
+    collections::HashMap,
+    sync::{PoisonError, RwLock},
+};
+
+use crate::{
+    error::Error,
+    types::{Queryable, Tokenizer},
+};
+
+///
+/// Document `V` with a cache of the values resolved from it.
+///
+/// Values are cached per path and per [Tokenizer](Tokenizer), since the
+/// same path might mean different things for different tokenizers. Only
+/// successful lookups are being cached.
+///
+#[derive(Debug)]
+pub struct CachedQueryable<V> {
+    document: V,
+    cache: RwLock<HashMap<(TypeId, String), V>>,
+}
+
+impl<V> CachedQueryable<V>
+where
+    V: Queryable<Output = V> + Clone,
+{
+    #[inline]
+    pub fn new(document: V) -> Self {
+        CachedQueryable {
+            document,
+            cache: RwLock::new(HashMap::new()),
+        }
+    }
+
+    #[inline]
+    pub fn document(&self) -> &V {
+        &self.document
+    }
+
+    ///
+    /// Resolve `path` through [Queryable::query](Queryable::query), the
+    /// resolved value is cached on a miss.
+    ///
+    pub fn get<T>(&self, path: &str) -> Result<V, Error>
+    where
+        T: Tokenizer + 'static,
+    {
+        let key = (TypeId::of::<T>(), String::from(path));
+
+        // the cache only holds plain values, a poisoned lock is still consistent
+        if let Some(found) = self
+            .cache
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(&key)
+        {
+            return Ok(found.clone());
+        }
+
+        let found = self.document.query::<T>(path)?;
+
+        self.cache
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .entry(key)
+            .or_insert_with(|| found.clone());
+
+        Ok(found)
+    }
+
+    ///
+    /// Number of cached values.
+    ///
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.cache
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
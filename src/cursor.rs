@@ -0,0 +1,154 @@
+//!
+//! Step-by-step navigation over a [Queryable](Queryable).
+//!
+//! [Cursor](Cursor) keeps the node it points at together with every node
+//! above it, so it could go down one segment at a time and back up again
+//! without resolving the path from the root, e.g. for interactive path
+//! editors.
+//!
+use crate::{
+    error::Error,
+    kind::QueryKind,
+    step::Step,
+    types::{not_traversable, Queryable, Tokenizer},
+};
+
+///
+/// Borrowing cursor over `V`, nodes are never being cloned.
+///
+/// Every move returns the cursor back, so moves could be chained with
+/// `and_then`.
+///
+#[derive(Debug, Clone)]
+pub struct Cursor<'a, V> {
+    current: &'a V,
+    // nodes above `current`, the root first
+    parents: Vec<&'a V>,
+    // steps from the root into `current`
+    steps: Vec<Step<'a>>,
+}
+
+impl<'a, V> Cursor<'a, V>
+where
+    V: Queryable<Output = V>,
+{
+    #[inline]
+    pub fn new(root: &'a V) -> Self {
+        Cursor {
+            current: root,
+            parents: Vec::new(),
+            steps: Vec::new(),
+        }
+    }
+
+    #[inline]
+    pub fn current(&self) -> &'a V {
+        self.current
+    }
+
+    ///
+    /// Number of steps from the root, `0` when pointing at the root.
+    ///
+    #[inline]
+    pub fn depth(&self) -> usize {
+        self.steps.len()
+    }
+
+    ///
+    /// Path from the root into the current node rendered by
+    /// [Tokenizer](Tokenizer) `T`, empty when pointing at the root.
+    ///
+    #[inline]
+    pub fn path<T>(&self) -> String
+    where
+        T: Tokenizer,
+    {
+        T::render(&self.steps)
+    }
+
+    ///
+    /// Move into the child `key` of the current dictionary.
+    ///
+    pub fn down_key(&mut self, key: &str) -> Result<&mut Self, Error> {
+        let (step, child) = key_child(self.current, key)?;
+        Ok(self.down(step, child))
+    }
+
+    ///
+    /// Move into the element `idx` of the current array.
+    ///
+    pub fn down_index(&mut self, idx: usize) -> Result<&mut Self, Error> {
+        let (step, child) = index_child(self.current, idx)?;
+        Ok(self.down(step, child))
+    }
+
+    ///
+    /// Move back into the parent node, [Error::NoParent](Error::NoParent)
+    /// when already pointing at the root.
+    ///
+    pub fn up(&mut self) -> Result<&mut Self, Error> {
+        let parent = self.parents.pop().ok_or(Error::NoParent)?;
+
+        self.current = parent;
+        self.steps.pop();
+
+        Ok(self)
+    }
+
+    fn down(&mut self, step: Step<'a>, child: &'a V) -> &mut Self {
+        self.parents.push(self.current);
+        self.current = child;
+        self.steps.push(step);
+
+        self
+    }
+}
+
+// child of `node` whose step is `matching`, along with that step
+pub(crate) fn find_child<'a, V, F>(node: &'a V, matching: F) -> Option<(Step<'a>, &'a V)>
+where
+    V: Queryable<Output = V>,
+    F: Fn(&Step<'a>) -> bool,
+{
+    node.children().into_iter().find(|(step, _)| matching(step))
+}
+
+// child `key` of dictionary `node`, along with the step into it
+pub(crate) fn key_child<'a, V>(node: &'a V, key: &str) -> Result<(Step<'a>, &'a V), Error>
+where
+    V: Queryable<Output = V>,
+{
+    match node.query_kind() {
+        Some(QueryKind::Dictionary) => {
+            find_child(node, |step| matches!(step, Step::Key(k) if k == key))
+                .ok_or_else(|| Error::key_not_exist(String::from(key)))
+        }
+        Some(QueryKind::Array) => Err(Error::TypeError(
+            String::from(key),
+            QueryKind::Dictionary,
+            QueryKind::Array,
+        )),
+        None => Err(not_traversable(node, String::from(key))),
+    }
+}
+
+// element `idx` of array `node`, along with the step into it
+pub(crate) fn index_child<V>(node: &V, idx: usize) -> Result<(Step<'_>, &V), Error>
+where
+    V: Queryable<Output = V>,
+{
+    match node.query_kind() {
+        Some(QueryKind::Array) => {
+            find_child(node, |step| *step == Step::Index(idx)).ok_or(Error::IndexNotExist {
+                index: idx,
+                len: node.children().len(),
+            })
+        }
+        Some(QueryKind::Dictionary) => Err(Error::TypeError(
+            format!("[{}]", idx),
+            QueryKind::Array,
+            QueryKind::Dictionary,
+        )),
+        None => Err(not_traversable(node, format!("[{}]", idx))),
+    }
+}
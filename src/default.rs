@@ -1,14 +1,277 @@
 use crate::{
     error::{IndexError, KeyError},
+    kind::QueryKind,
+    step::{Segment, Step},
     types::{State, Tokenizer},
 };
+use std::{
+    borrow::Cow,
+    fmt,
+    marker::PhantomData,
+    num::{IntErrorKind, ParseIntError},
+};
+
+// parse `[n]`, optionally trimming whitespaces around `n`
+#[inline]
+fn bracket_index_parse(key: &str, trim: bool) -> Result<usize, IndexError> {
+    let index = key
+        .strip_prefix('[')
+        .and_then(|k| k.strip_suffix(']'))
+        .map(|k| if trim { k.trim() } else { k });
+
+    match index {
+        // e.g `[٢]`, digits other than ASCII aren't an index
+        Some(index) if !index.is_ascii() => Err(IndexError::ParseError(String::from(key))),
+        Some(index) if !index.is_empty() && !index.contains(&['[', ']'][..]) => {
+            if is_fractional(index) {
+                Err(IndexError::NotAnInteger(String::from(index)))
+            } else {
+                digits_parse(index, key)
+            }
+        }
+        _ => Err(IndexError::ParseError(String::from(key))),
+    }
+}
+
+// parse `index` made of ASCII digits only, `key` is the whole step.
+// `usize::from_str` takes a leading `+`, yet `+1` isn't an index
+#[inline]
+fn digits_parse(index: &str, key: &str) -> Result<usize, IndexError> {
+    match index.parse::<usize>() {
+        Err(e) => Err(int_error(index, e)),
+        Ok(_) if !index.bytes().all(|b| b.is_ascii_digit()) => {
+            Err(IndexError::ParseError(String::from(key)))
+        }
+        Ok(index) => Ok(index),
+    }
+}
+
+// position of the closing `"]` of a quoted key, `quoted` is what follows
+// the opening `["`. `\` escapes the next character, e.g `\"` or `\\`
+fn quoted_end(quoted: &str) -> Option<usize> {
+    let mut chars = quoted.char_indices();
+
+    while let Some((idx, c)) = chars.next() {
+        match c {
+            '\\' => {
+                chars.next();
+            }
+            '"' if quoted[idx + 1..].starts_with(']') => return Some(idx),
+            _ => (),
+        }
+    }
+
+    None
+}
+
+// split a quoted key step, e.g `["a.b"]`, from the rest of `key`.
+// `None` when `key` doesn't start with a quoted key.
+#[inline]
+fn quoted_split(key: &str) -> Option<Result<State<'_>, KeyError>> {
+    let end = quoted_end(key.strip_prefix("[\"")?)? + 4;
+    let (current, rest) = key.split_at(end);
+
+    match rest.strip_prefix('.') {
+        Some(next) if next.starts_with('.') => Some(Err(KeyError::EmptySegment {
+            query: String::from(key),
+            position: end + 1,
+        })),
+        Some(next) => Some(Ok((Some(current), Some(next)))),
+        None if rest.is_empty() => Some(Ok((Some(current), None))),
+        None => Some(Err(KeyError::ParseError(String::from(key)))),
+    }
+}
+
+// split a fallback off `path` on the first `:-` that isn't part of
+// a quoted key step, e.g `["a:-b"]:-c` falls back to `c`
+fn quoted_default_split(path: &str) -> (&str, Option<&str>) {
+    let mut from = 0;
+
+    while let Some(idx) = path[from..].find(":-").map(|idx| from + idx) {
+        match path[from..idx].find("[\"") {
+            Some(open) => match quoted_end(&path[from + open + 2..]) {
+                Some(close) => from += open + close + 4,
+                // unterminated quote, the rest is a key
+                None => break,
+            },
+            None => return (&path[..idx], Some(&path[idx + 2..])),
+        }
+    }
+
+    (path, None)
+}
+
+// key of a quoted key step, e.g `a.b` of `["a.b"]`, might be empty.
+// escapes are resolved, e.g `a"b` of `["a\"b"]`
+fn quoted_key(key: &str) -> Option<Cow<'_, str>> {
+    let quoted = key.strip_prefix("[\"")?;
+    let end = quoted_end(quoted).filter(|end| end + 2 == quoted.len())?;
+    let quoted = &quoted[..end];
+
+    if !quoted.contains('\\') {
+        return Some(Cow::Borrowed(quoted));
+    }
+
+    let mut key = String::with_capacity(quoted.len());
+    let mut chars = quoted.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => key.extend(chars.next()),
+            c => key.push(c),
+        }
+    }
+
+    Some(Cow::Owned(key))
+}
+
+// whether `key` has to be quoted to be read back as the same key
+#[inline]
+fn needs_quote(key: &str) -> bool {
+    key.is_empty()
+        || key.contains(":-")
+        || key.contains(|c: char| matches!(c, '.' | '[' | ']' | '"' | '*') || c.is_whitespace())
+}
+
+// write `key` as a quoted key step, e.g `["a.b"]`
+fn write_quoted<W>(out: &mut W, key: &str) -> fmt::Result
+where
+    W: fmt::Write + ?Sized,
+{
+    out.write_str("[\"")?;
+
+    for c in key.chars() {
+        if matches!(c, '"' | '\\') {
+            out.write_char('\\')?;
+        }
+        out.write_char(c)?;
+    }
+
+    out.write_str("\"]")
+}
+
+// index that doesn't fit into usize gets its own error, since it's a valid number.
+// the limit depends on the target, e.g `[4294967296]` overflows on 32-bit targets.
+#[inline]
+fn int_error(index: &str, e: ParseIntError) -> IndexError {
+    match e.kind() {
+        IntErrorKind::PosOverflow => IndexError::Overflow(String::from(index)),
+        _ => IndexError::IntError {
+            input: String::from(index),
+            source: e,
+        },
+    }
+}
+
+// classify a step of the bracket tokenizers, e.g `[0]`, `[-1]`, `[*]` or
+// a quoted key `["a.b"]`, indexes being parsed by `index_parse`
+fn bracket_step_parse(
+    key: &str,
+    index_parse: fn(&str) -> Result<usize, IndexError>,
+) -> Result<Step<'_>, IndexError> {
+    if let Some(key) = quoted_key(key) {
+        return Ok(Step::Key(key));
+    }
+
+    match key {
+        "*" | "[*]" => Ok(Step::Wildcard),
+        "[last]" => Ok(Step::FromEnd(1)),
+        _ if key.starts_with("[-") && key.ends_with(']') => {
+            from_end_parse(key, &key[2..key.len() - 1])
+        }
+        _ if key.starts_with('[') => index_parse(key).map(Step::Index),
+        _ => Ok(Step::Key(key.into())),
+    }
+}
+
+// parse `n` of a from-the-end index step, e.g `1` of `[-1]`. `key` is the whole step
+#[inline]
+fn from_end_parse<'a>(key: &str, n: &str) -> Result<Step<'a>, IndexError> {
+    match n.parse::<usize>() {
+        Ok(0) => Err(IndexError::ParseError(String::from(key))),
+        Ok(_) if n.starts_with('+') => Err(IndexError::ParseError(String::from(key))),
+        Ok(n) => Ok(Step::FromEnd(n)),
+        Err(e) => Err(int_error(key, e)),
+    }
+}
+
+// whether `index` looks like a float or scientific notation number, e.g `1.0` or `1e3`
+#[inline]
+fn is_fractional(index: &str) -> bool {
+    index.starts_with(|c: char| c.is_ascii_digit())
+        && index.contains(&['.', 'e', 'E'][..])
+        && index.parse::<f64>().is_ok()
+}
+
+// join two partial paths by `sep`, stray `sep` at the seam are dropped.
+// `prefixed` paths start every step with `sep`, e.g `/a/b`.
+fn join_with(base: &str, relative: &str, sep: char, prefixed: bool) -> String {
+    let base = base.trim_end_matches(sep);
+    let relative = relative.trim_start_matches(sep);
+
+    match (base.is_empty(), relative.is_empty()) {
+        (_, true) => String::from(base),
+        (true, false) if !prefixed => String::from(relative),
+        _ => {
+            let mut out = String::with_capacity(base.len() + relative.len() + 1);
+            out.push_str(base);
+            out.push(sep);
+            out.push_str(relative);
+            out
+        }
+    }
+}
+
+// split `key` where every step is prefixed by one of the ASCII `sep`, e.g `/1/2`
+fn prefixed_parse<'a>(key: &'a str, sep: &[char]) -> Result<State<'a>, KeyError> {
+    if key.is_empty() {
+        Err(KeyError::EmptyKey)
+    } else if !key.starts_with(sep) {
+        // key should always prefixed with a separator
+        Err(KeyError::ParseError(String::from(key)))
+    } else {
+        let size = key.len();
+        // /1/2
+        // 1/2
+        match key[1..size].find(sep) {
+            // since path is empty (case "//")
+            Some(0) => Err(KeyError::EmptySegment {
+                query: String::from(key),
+                position: 1,
+            }),
+            // if there is a separator, then there will be next
+            Some(idx) => {
+                let pivot = idx + 1;
+                let current = &key[1..pivot];
+                // check whether current have a whitespace or not
+                // key shouldn't have a whitespace
+                match current.find(char::is_whitespace) {
+                    Some(_) => Err(KeyError::ParseError(String::from(current))),
+                    // `//` right after the current step, same as above
+                    _ if key[pivot + 1..].starts_with(sep) => Err(KeyError::EmptySegment {
+                        query: String::from(key),
+                        position: pivot + 1,
+                    }),
+                    _ => Ok((Some(current), Some(&key[pivot..size]))),
+                }
+            }
+            _ => Ok((Some(&key[1..size]), None)),
+        }
+    }
+}
 
 ///
 /// [DefaultTokenizer](DefaultTokenizer) have a format query likes :
 /// ```
 /// // [0].test.[1]
 /// // test.test.[1]
+/// // test.["a.b"].[1]
 /// ```
+///
+/// Keys could be quoted as `["..."]` so they might contain '.' or be empty,
+/// the quoted key ends at the first `"]` that isn't escaped, `\"` and `\\`
+/// being a literal '"' and '\'. Keys are written back quoted when needed.
+///
 pub struct DefaultTokenizer;
 
 impl Tokenizer for DefaultTokenizer {
@@ -30,13 +293,33 @@ impl Tokenizer for DefaultTokenizer {
     /// assert!(DefaultTokenizer::index_parse("[x]").is_err());
     /// ```
     ///
+    /// - index should be a whole number.
+    ///
+    /// ```rust
+    /// use querable::{types::Tokenizer, default::DefaultTokenizer, error::{IndexError}};
+    ///
+    /// assert_eq!(DefaultTokenizer::index_parse("[1.0]"), Err(IndexError::NotAnInteger(String::from("1.0"))));
+    /// ```
+    ///
+    /// - nested or unbalanced brackets are not allowed.
+    ///
+    /// ```rust
+    /// use querable::{types::Tokenizer, default::DefaultTokenizer, error::{IndexError}};
+    ///
+    /// assert_eq!(DefaultTokenizer::index_parse("[[1]]"), Err(IndexError::ParseError(String::from("[[1]]"))));
+    /// ```
+    ///
+    /// - only ASCII digits are allowed.
+    ///
+    /// ```rust
+    /// use querable::{types::Tokenizer, default::DefaultTokenizer, error::{IndexError}};
+    ///
+    /// assert_eq!(DefaultTokenizer::index_parse("[٢]"), Err(IndexError::ParseError(String::from("[٢]"))));
+    /// ```
+    ///
+    #[inline]
     fn index_parse(key: &str) -> Result<usize, IndexError> {
-        if key.starts_with('[') && key.ends_with(']') && key.len() > 2 {
-            let index = &key[1..key.len() - 1];
-            index.parse::<usize>().map_err(IndexError::IntError)
-        } else {
-            Err(IndexError::ParseError(String::from(key)))
-        }
+        bracket_index_parse(key, false)
     }
 
     /// Parse key/path index.
@@ -48,32 +331,243 @@ impl Tokenizer for DefaultTokenizer {
     ///
     /// assert_eq!(DefaultTokenizer::dict_parse("   .test"), Err(KeyError::ParseError(String::from("   "))));
     /// assert_eq!(DefaultTokenizer::dict_parse(""), Err(KeyError::EmptyKey));
+    /// assert_eq!(DefaultTokenizer::dict_parse("a..b"), Err(KeyError::EmptySegment { query: String::from("a..b"), position: 2 }));
+    /// ```
+    ///
+    /// - a query can't start with '.', the root is an empty query.
+    ///
+    /// ```rust
+    /// use querable::{types::Tokenizer, default::DefaultTokenizer, error::{KeyError}};
+    ///
+    /// assert_eq!(DefaultTokenizer::dict_parse(".b"), Err(KeyError::ParseError(String::from(".b"))));
+    /// ```
+    ///
+    /// - key that contains '.' (or an empty key) could be quoted, e.g `["a.b"]` or `[""]`.
+    ///
+    /// ```rust
+    /// use querable::{types::Tokenizer, default::DefaultTokenizer};
+    ///
+    /// assert_eq!(DefaultTokenizer::dict_parse(r#"["a.b"].c"#), Ok((Some(r#"["a.b"]"#), Some("c"))));
+    /// assert_eq!(DefaultTokenizer::dict_parse(r#"[""]"#), Ok((Some(r#"[""]"#), None)));
     /// ```
     ///
     #[inline]
-    fn dict_parse(key: &str) -> Result<State, KeyError> {
+    fn dict_parse(key: &str) -> Result<State<'_>, KeyError> {
+        if let Some(state) = quoted_split(key) {
+            return state;
+        }
+
         if key.is_empty() {
             Err(KeyError::EmptyKey)
         } else {
             let size = key.len();
 
             match key.find('.') {
-                Some(0) => Err(KeyError::EmptyKey),
+                Some(0) => Err(KeyError::ParseError(String::from(key))),
                 Some(idx) => {
                     let current = &key[0..idx];
+                    let pivot = idx + 1;
 
                     match current.find(char::is_whitespace) {
                         Some(_) => Err(KeyError::ParseError(String::from(current))),
-                        _ => {
-                            let pivot = idx + 1;
-                            Ok((Some(current), Some(&key[pivot..size])))
-                        }
+                        // `..`, the next step is empty. rejected here so that the
+                        // next step never starts with '.'
+                        _ if key[pivot..].starts_with('.') => Err(KeyError::EmptySegment {
+                            query: String::from(key),
+                            position: pivot,
+                        }),
+                        _ => Ok((Some(current), Some(&key[pivot..size]))),
                     }
                 }
                 _ => Ok((Some(&key[0..size]), None)),
             }
         }
     }
+
+    /// Join paths by `.`.
+    ///
+    /// ```rust
+    /// use querable::{types::Tokenizer, default::DefaultTokenizer};
+    ///
+    /// assert_eq!(DefaultTokenizer::join_paths("a.b", "c.d"), "a.b.c.d");
+    /// assert_eq!(DefaultTokenizer::join_paths("a.b.", ".c.d"), "a.b.c.d");
+    /// assert_eq!(DefaultTokenizer::join_paths("", "c.d"), "c.d");
+    /// ```
+    ///
+    #[inline]
+    fn join_paths(base: &str, relative: &str) -> String {
+        join_with(base, relative, '.', false)
+    }
+
+    /// Splits a fallback off on the first `:-` outside of a quoted key.
+    ///
+    /// ```rust
+    /// use querable::{types::Tokenizer, default::DefaultTokenizer};
+    ///
+    /// assert_eq!(DefaultTokenizer::default_split(r#"["a:-b"]"#), (r#"["a:-b"]"#, None));
+    /// assert_eq!(DefaultTokenizer::default_split(r#"["a:-b"].c:-1"#), (r#"["a:-b"].c"#, Some("1")));
+    /// ```
+    ///
+    #[inline]
+    fn default_split(path: &str) -> (&str, Option<&str>) {
+        quoted_default_split(path)
+    }
+
+    /// Classify a path step.
+    ///
+    /// - step that starts with '[' are an index and should be a valid index.
+    ///
+    /// ```rust
+    /// use querable::{types::Tokenizer, default::DefaultTokenizer, step::Step};
+    ///
+    /// assert_eq!(DefaultTokenizer::step_parse("[0]"), Ok(Step::Index(0)));
+    /// assert_eq!(DefaultTokenizer::step_parse("id"), Ok(Step::Key("id".into())));
+    /// assert_eq!(DefaultTokenizer::step_parse("[*]"), Ok(Step::Wildcard));
+    /// assert_eq!(DefaultTokenizer::step_parse("[-1]"), Ok(Step::FromEnd(1)));
+    /// assert_eq!(DefaultTokenizer::step_parse("[last]"), Ok(Step::FromEnd(1)));
+    /// assert_eq!(DefaultTokenizer::step_parse(r#"["a.b"]"#), Ok(Step::Key("a.b".into())));
+    /// assert!(DefaultTokenizer::step_parse("[x]").is_err());
+    /// ```
+    ///
+    #[inline]
+    fn step_parse(key: &str) -> Result<Step<'_>, IndexError> {
+        bracket_step_parse(key, Self::index_parse)
+    }
+
+    /// A `[`-prefixed segment is an index, even an unterminated one, except
+    /// for a quoted key (`["`), anything else is a key.
+    ///
+    /// ```rust
+    /// use querable::{types::Tokenizer, default::DefaultTokenizer, kind::QueryKind};
+    ///
+    /// assert_eq!(DefaultTokenizer::peek_kind("[1"), Some(QueryKind::Array));
+    /// assert_eq!(DefaultTokenizer::peek_kind("ab"), Some(QueryKind::Dictionary));
+    /// ```
+    ///
+    fn peek_kind(remaining: &str) -> Option<QueryKind> {
+        let segment = match Self::dict_parse(remaining) {
+            Ok((Some(segment), _)) => segment,
+            _ => remaining,
+        };
+
+        if segment.is_empty() {
+            None
+        } else if segment.starts_with('[') && !segment.starts_with("[\"") {
+            Some(QueryKind::Array)
+        } else {
+            Some(QueryKind::Dictionary)
+        }
+    }
+
+    #[inline]
+    fn write_step<W>(out: &mut W, step: &Step<'_>) -> fmt::Result
+    where
+        W: fmt::Write + ?Sized,
+    {
+        match step {
+            Step::Index(idx) => write!(out, "[{}]", idx),
+            Step::Key(key) if needs_quote(key) => write_quoted(out, key),
+            Step::Key(key) => out.write_str(key),
+            Step::FromEnd(n) => write!(out, "[-{}]", n),
+            Step::Wildcard => out.write_char('*'),
+        }
+    }
+
+    /// Write steps as a path.
+    ///
+    /// ```rust
+    /// use querable::{types::Tokenizer, default::DefaultTokenizer, step::Step};
+    ///
+    /// assert_eq!(DefaultTokenizer::render(&[Step::Index(0), Step::Key("id".into())]), "[0].id");
+    /// ```
+    ///
+    fn write_path<W>(out: &mut W, steps: &[Step<'_>]) -> fmt::Result
+    where
+        W: fmt::Write + ?Sized,
+    {
+        for (idx, step) in steps.iter().enumerate() {
+            if idx > 0 {
+                out.write_char('.')?;
+            }
+            Self::write_step(out, step)?;
+        }
+
+        Ok(())
+    }
+}
+
+///
+/// [LenientTokenizer](LenientTokenizer) have the same format query as
+/// [DefaultTokenizer](DefaultTokenizer), except whitespaces are allowed
+/// around the array index :
+/// ```
+/// // [ 0 ].test.[1]
+/// ```
+pub struct LenientTokenizer;
+
+impl Tokenizer for LenientTokenizer {
+    /// Parse index array, trimming whitespaces inside the brackets.
+    ///
+    /// ```rust
+    /// use querable::{types::Tokenizer, default::LenientTokenizer};
+    ///
+    /// assert_eq!(LenientTokenizer::index_parse("[ 1 ]"), Ok(1));
+    /// assert!(LenientTokenizer::index_parse("[ ]").is_err());
+    /// ```
+    ///
+    #[inline]
+    fn index_parse(key: &str) -> Result<usize, IndexError> {
+        bracket_index_parse(key, true)
+    }
+
+    #[inline]
+    fn dict_parse(key: &str) -> Result<State<'_>, KeyError> {
+        match key.find('.') {
+            _ if key.starts_with("[\"") => DefaultTokenizer::dict_parse(key),
+            // whitespaces are allowed inside index brackets
+            Some(idx) if key.starts_with('[') && key[..idx].ends_with(']') => {
+                match &key[idx + 1..] {
+                    next if next.starts_with('.') => Err(KeyError::EmptySegment {
+                        query: String::from(key),
+                        position: idx + 1,
+                    }),
+                    next => Ok((Some(&key[..idx]), Some(next))),
+                }
+            }
+            _ => DefaultTokenizer::dict_parse(key),
+        }
+    }
+
+    #[inline]
+    fn join_paths(base: &str, relative: &str) -> String {
+        DefaultTokenizer::join_paths(base, relative)
+    }
+
+    #[inline]
+    fn default_split(path: &str) -> (&str, Option<&str>) {
+        DefaultTokenizer::default_split(path)
+    }
+
+    #[inline]
+    fn step_parse(key: &str) -> Result<Step<'_>, IndexError> {
+        bracket_step_parse(key, Self::index_parse)
+    }
+
+    #[inline]
+    fn write_step<W>(out: &mut W, step: &Step<'_>) -> fmt::Result
+    where
+        W: fmt::Write + ?Sized,
+    {
+        DefaultTokenizer::write_step(out, step)
+    }
+
+    #[inline]
+    fn write_path<W>(out: &mut W, steps: &[Step<'_>]) -> fmt::Result
+    where
+        W: fmt::Write + ?Sized,
+    {
+        DefaultTokenizer::write_path(out, steps)
+    }
 }
 
 ///
@@ -87,7 +581,79 @@ pub struct SlashTokenizer;
 impl Tokenizer for SlashTokenizer {
     #[inline]
     fn index_parse(key: &str) -> Result<usize, IndexError> {
-        key.parse::<usize>().map_err(IndexError::IntError)
+        digits_parse(key, key)
+    }
+
+    /// Both empty path and a bare `/` are the root.
+    ///
+    /// ```rust
+    /// use querable::{types::Tokenizer, default::SlashTokenizer};
+    ///
+    /// assert!(SlashTokenizer::is_root("/"));
+    /// assert!(!SlashTokenizer::is_root("/a"));
+    /// ```
+    ///
+    #[inline]
+    fn is_root(path: &str) -> bool {
+        matches!(path.trim(), "" | "/")
+    }
+
+    /// Classify a path step, `-n` is the n-th element from the end.
+    ///
+    /// ```rust
+    /// use querable::{types::Tokenizer, default::SlashTokenizer, step::Step};
+    ///
+    /// assert_eq!(SlashTokenizer::step_parse("-1"), Ok(Step::FromEnd(1)));
+    /// assert_eq!(SlashTokenizer::step_parse("1"), Ok(Step::Index(1)));
+    /// ```
+    ///
+    fn step_parse(key: &str) -> Result<Step<'_>, IndexError> {
+        match key.strip_prefix('-') {
+            Some(n) if !n.is_empty() && n.bytes().all(|b| b.is_ascii_digit()) => {
+                from_end_parse(key, n)
+            }
+            _ if key == "*" => Ok(Step::Wildcard),
+            _ => Ok(Self::index_parse(key)
+                .map(Step::Index)
+                .unwrap_or_else(|_| Step::Key(key.into()))),
+        }
+    }
+
+    /// Classify all-digit segments (and `-n` from the end) as an index.
+    ///
+    /// This is only a heuristic, a numeric dictionary key (e.g. `/ports/80`)
+    /// looks the same as an index, which one it is depends on the document.
+    ///
+    /// ```rust
+    /// use querable::{types::Tokenizer, default::SlashTokenizer, step::Segment};
+    ///
+    /// assert_eq!(SlashTokenizer::classify("01"), Segment::Index);
+    /// assert_eq!(SlashTokenizer::classify("+1"), Segment::Key);
+    /// ```
+    ///
+    fn classify(segment: &str) -> Segment {
+        match Self::step_parse(segment) {
+            Ok(Step::FromEnd(_)) => Segment::Index,
+            _ if !segment.is_empty() && segment.bytes().all(|b| b.is_ascii_digit()) => {
+                Segment::Index
+            }
+            _ => Segment::Key,
+        }
+    }
+
+    /// Join paths by `/`, the result is always prefixed by `/`.
+    ///
+    /// ```rust
+    /// use querable::{types::Tokenizer, default::SlashTokenizer};
+    ///
+    /// assert_eq!(SlashTokenizer::join_paths("/a/b", "/c/d"), "/a/b/c/d");
+    /// assert_eq!(SlashTokenizer::join_paths("/a/b/", "c/d"), "/a/b/c/d");
+    /// assert_eq!(SlashTokenizer::join_paths("/", "/c"), "/c");
+    /// ```
+    ///
+    #[inline]
+    fn join_paths(base: &str, relative: &str) -> String {
+        join_with(base, relative, '/', true)
     }
 
     /// Parse dict key/path query.
@@ -105,7 +671,8 @@ impl Tokenizer for SlashTokenizer {
     ///
     /// ```rust
     /// use querable::{types::Tokenizer, default::SlashTokenizer, error::{KeyError, IndexError}};
-    /// assert_eq!(SlashTokenizer::dict_parse("//"), Err(KeyError::EmptyKey));
+    /// assert_eq!(SlashTokenizer::dict_parse("//"), Err(KeyError::EmptySegment { query: String::from("//"), position: 1 }));
+    /// assert_eq!(SlashTokenizer::dict_parse("/a//b"), Err(KeyError::EmptySegment { query: String::from("/a//b"), position: 3 }));
     /// ```
     ///
     /// - not an empty string
@@ -114,32 +681,651 @@ impl Tokenizer for SlashTokenizer {
     /// use querable::{types::Tokenizer, default::SlashTokenizer, error::{KeyError, IndexError}};
     /// assert_eq!(SlashTokenizer::dict_parse(""), Err(KeyError::EmptyKey))
     /// ```
-    fn dict_parse(key: &str) -> Result<State, KeyError> {
-        if key.is_empty() {
-            Err(KeyError::EmptyKey)
-        } else if !key.starts_with('/') {
-            // key should always prefixed with slash
-            Err(KeyError::ParseError(String::from(key)))
-        } else {
-            let size = key.len();
-            // /1/2
-            // 1/2
-            match key[1..size].find('/') {
-                // since path is empty (case "//")
-                Some(0) => Err(KeyError::EmptyKey),
-                // if there is '/', then there will be next
-                Some(idx) => {
-                    let pivot = idx + 1;
-                    let current = &key[1..pivot];
-                    // check whether current have a whitespace or not
-                    // key shouldn't have a whitespace
-                    match current.find(char::is_whitespace) {
-                        Some(_) => Err(KeyError::ParseError(String::from(current))),
-                        _ => Ok((Some(current), Some(&key[pivot..size]))),
-                    }
-                }
-                _ => Ok((Some(&key[1..size]), None)),
-            }
+    #[inline]
+    fn dict_parse(key: &str) -> Result<State<'_>, KeyError> {
+        prefixed_parse(key, &['/'])
+    }
+
+    #[inline]
+    fn write_step<W>(out: &mut W, step: &Step<'_>) -> fmt::Result
+    where
+        W: fmt::Write + ?Sized,
+    {
+        match step {
+            Step::Index(idx) => write!(out, "{}", idx),
+            Step::Key(key) => out.write_str(key),
+            Step::FromEnd(n) => write!(out, "-{}", n),
+            Step::Wildcard => out.write_char('*'),
+        }
+    }
+
+    /// Write steps as a path.
+    ///
+    /// ```rust
+    /// use querable::{types::Tokenizer, default::SlashTokenizer, step::Step};
+    ///
+    /// assert_eq!(SlashTokenizer::render(&[Step::Index(0), Step::Key("id".into())]), "/0/id");
+    /// ```
+    ///
+    fn write_path<W>(out: &mut W, steps: &[Step<'_>]) -> fmt::Result
+    where
+        W: fmt::Write + ?Sized,
+    {
+        for step in steps {
+            out.write_char('/')?;
+            Self::write_step(out, step)?;
         }
+
+        Ok(())
+    }
+}
+
+///
+/// [StrictSlashTokenizer](StrictSlashTokenizer) have the same format query as
+/// [SlashTokenizer](SlashTokenizer), except a step could be marked to be
+/// either an index or a key regardless of the node it's being resolved on :
+/// ```
+/// // /#0/$1/name
+/// ```
+///
+/// - `#n` is an array index, e.g. `#0` on a dictionary is a type error.
+/// - `$key` is a dictionary key, e.g. `$0` on an array isn't an index.
+///
+/// Unmarked steps are being resolved the same way as [SlashTokenizer](SlashTokenizer).
+/// Keys that starts with '#' or '$' need to be marked, e.g. `$#0` for key `#0`,
+/// since dictionaries are still looked up by the raw step.
+///
+pub struct StrictSlashTokenizer;
+
+impl Tokenizer for StrictSlashTokenizer {
+    /// Parse array index, `#` marker is optional.
+    ///
+    /// ```rust
+    /// use querable::{types::Tokenizer, default::StrictSlashTokenizer};
+    ///
+    /// assert_eq!(StrictSlashTokenizer::index_parse("#0"), Ok(0));
+    /// assert_eq!(StrictSlashTokenizer::index_parse("0"), Ok(0));
+    /// assert!(StrictSlashTokenizer::index_parse("$0").is_err());
+    /// ```
+    ///
+    #[inline]
+    fn index_parse(key: &str) -> Result<usize, IndexError> {
+        SlashTokenizer::index_parse(key.strip_prefix('#').unwrap_or(key))
+    }
+
+    #[inline]
+    fn is_root(path: &str) -> bool {
+        SlashTokenizer::is_root(path)
+    }
+
+    /// Classify a path step, marked steps are always either
+    /// [Step::Index](Step::Index) or [Step::Key](Step::Key).
+    ///
+    /// ```rust
+    /// use querable::{types::Tokenizer, default::StrictSlashTokenizer, step::Step};
+    ///
+    /// assert_eq!(StrictSlashTokenizer::step_parse("#1"), Ok(Step::Index(1)));
+    /// assert_eq!(StrictSlashTokenizer::step_parse("$1"), Ok(Step::Key("1".into())));
+    /// assert_eq!(StrictSlashTokenizer::step_parse("1"), Ok(Step::Index(1)));
+    /// assert!(StrictSlashTokenizer::step_parse("#x").is_err());
+    /// ```
+    ///
+    fn step_parse(key: &str) -> Result<Step<'_>, IndexError> {
+        if let Some(key) = key.strip_prefix('$') {
+            return Ok(Step::Key(key.into()));
+        }
+
+        if key.starts_with('#') {
+            return Self::index_parse(key).map(Step::Index);
+        }
+
+        SlashTokenizer::step_parse(key)
+    }
+
+    #[inline]
+    fn join_paths(base: &str, relative: &str) -> String {
+        SlashTokenizer::join_paths(base, relative)
+    }
+
+    #[inline]
+    fn dict_parse(key: &str) -> Result<State<'_>, KeyError> {
+        SlashTokenizer::dict_parse(key)
+    }
+
+    /// Write a step, indexes and keys that could be mistaken for
+    /// something else are always marked.
+    ///
+    /// ```rust
+    /// use querable::{types::Tokenizer, default::StrictSlashTokenizer, step::Step};
+    ///
+    /// let steps = [Step::Index(0), Step::Key("0".into()), Step::Key("id".into())];
+    ///
+    /// assert_eq!(StrictSlashTokenizer::render(&steps), "/#0/$0/id");
+    /// ```
+    ///
+    fn write_step<W>(out: &mut W, step: &Step<'_>) -> fmt::Result
+    where
+        W: fmt::Write + ?Sized,
+    {
+        match step {
+            Step::Index(idx) => write!(out, "#{}", idx),
+            Step::Key(key) => match Self::step_parse(key) {
+                Ok(Step::Key(parsed)) if parsed == *key => out.write_str(key),
+                _ => write!(out, "${}", key),
+            },
+            step => SlashTokenizer::write_step(out, step),
+        }
+    }
+
+    #[inline]
+    fn write_path<W>(out: &mut W, steps: &[Step<'_>]) -> fmt::Result
+    where
+        W: fmt::Write + ?Sized,
+    {
+        for step in steps {
+            out.write_char('/')?;
+            Self::write_step(out, step)?;
+        }
+
+        Ok(())
+    }
+}
+
+///
+/// [BackslashTolerantTokenizer](BackslashTolerantTokenizer) have the same
+/// format query as [SlashTokenizer](SlashTokenizer), except `\` is accepted
+/// as a separator as well, so Windows-style paths resolve the same way :
+/// ```
+/// // \0\test
+/// // /0\test
+/// ```
+///
+/// Keys can't contain `\` in this format, use [SlashTokenizer](SlashTokenizer)
+/// for those. Paths are always rendered with `/`.
+///
+pub struct BackslashTolerantTokenizer;
+
+impl Tokenizer for BackslashTolerantTokenizer {
+    #[inline]
+    fn index_parse(key: &str) -> Result<usize, IndexError> {
+        SlashTokenizer::index_parse(key)
+    }
+
+    #[inline]
+    fn is_root(path: &str) -> bool {
+        matches!(path.trim(), "" | "/" | "\\")
+    }
+
+    #[inline]
+    fn step_parse(key: &str) -> Result<Step<'_>, IndexError> {
+        SlashTokenizer::step_parse(key)
+    }
+
+    /// Join paths by `/`, stray `\` at the seam are dropped as well.
+    ///
+    /// ```rust
+    /// use querable::{types::Tokenizer, default::BackslashTolerantTokenizer};
+    ///
+    /// assert_eq!(BackslashTolerantTokenizer::join_paths("\\a\\", "\\b"), "\\a/b");
+    /// ```
+    ///
+    #[inline]
+    fn join_paths(base: &str, relative: &str) -> String {
+        SlashTokenizer::join_paths(
+            base.trim_end_matches(&['/', '\\'][..]),
+            relative.trim_start_matches(&['/', '\\'][..]),
+        )
+    }
+
+    /// Parse dict key/path query, steps are prefixed by either `/` or `\`.
+    ///
+    /// ```rust
+    /// use querable::{types::Tokenizer, default::BackslashTolerantTokenizer};
+    ///
+    /// assert_eq!(BackslashTolerantTokenizer::dict_parse("\\a/b"), Ok((Some("a"), Some("/b"))));
+    /// assert_eq!(BackslashTolerantTokenizer::dict_parse("/a\\b"), Ok((Some("a"), Some("\\b"))));
+    /// ```
+    ///
+    #[inline]
+    fn dict_parse(key: &str) -> Result<State<'_>, KeyError> {
+        prefixed_parse(key, &['/', '\\'])
+    }
+
+    #[inline]
+    fn write_step<W>(out: &mut W, step: &Step<'_>) -> fmt::Result
+    where
+        W: fmt::Write + ?Sized,
+    {
+        SlashTokenizer::write_step(out, step)
+    }
+
+    #[inline]
+    fn write_path<W>(out: &mut W, steps: &[Step<'_>]) -> fmt::Result
+    where
+        W: fmt::Write + ?Sized,
+    {
+        SlashTokenizer::write_path(out, steps)
+    }
+}
+
+///
+/// [JsonPointerTokenizer](JsonPointerTokenizer) have a format query likes
+/// [RFC 6901](https://tools.ietf.org/html/rfc6901) :
+/// ```
+/// // /0/1/2/3
+/// // /test/a~1b/1
+/// ```
+///
+/// Unlike [SlashTokenizer](SlashTokenizer), a step might be empty or contain
+/// whitespaces, `~1` and `~0` escapes are being resolved into '/' and '~'
+/// when a step are being classified by [Tokenizer::step_parse](Tokenizer::step_parse).
+///
+pub struct JsonPointerTokenizer;
+
+impl Tokenizer for JsonPointerTokenizer {
+    /// Parse array index.
+    ///
+    /// - index shouldn't have a leading zero.
+    ///
+    /// ```rust
+    /// use querable::{types::Tokenizer, default::JsonPointerTokenizer, error::{IndexError}};
+    ///
+    /// assert_eq!(JsonPointerTokenizer::index_parse("10"), Ok(10));
+    /// assert_eq!(JsonPointerTokenizer::index_parse("01"), Err(IndexError::ParseError(String::from("01"))));
+    /// ```
+    ///
+    #[inline]
+    fn index_parse(key: &str) -> Result<usize, IndexError> {
+        if key.len() > 1 && key.starts_with('0') {
+            Err(IndexError::ParseError(String::from(key)))
+        } else {
+            digits_parse(key, key)
+        }
+    }
+
+    /// Parse reference tokens of a pointer.
+    ///
+    /// Every token is prefixed by '/' and might contain any other character,
+    /// including whitespaces, or be empty, e.g. `/a//b` refers to key `""`
+    /// under `a`. Escapes are left to [Tokenizer::step_parse](Tokenizer::step_parse).
+    ///
+    /// ```rust
+    /// use querable::{types::Tokenizer, default::JsonPointerTokenizer, error::KeyError};
+    ///
+    /// assert_eq!(JsonPointerTokenizer::dict_parse("/a b/c"), Ok((Some("a b"), Some("/c"))));
+    /// assert_eq!(JsonPointerTokenizer::dict_parse("//b"), Ok((Some(""), Some("/b"))));
+    /// assert_eq!(JsonPointerTokenizer::dict_parse("/"), Ok((Some(""), None)));
+    /// assert_eq!(JsonPointerTokenizer::dict_parse("a"), Err(KeyError::ParseError(String::from("a"))));
+    /// ```
+    ///
+    fn dict_parse(key: &str) -> Result<State<'_>, KeyError> {
+        let rest = match key.strip_prefix('/') {
+            Some(rest) => rest,
+            None if key.is_empty() => return Err(KeyError::EmptyKey),
+            None => return Err(KeyError::ParseError(String::from(key))),
+        };
+
+        match rest.find('/') {
+            Some(idx) => Ok((Some(&rest[..idx]), Some(&rest[idx..]))),
+            None => Ok((Some(rest), None)),
+        }
+    }
+
+    /// Never splits a fallback off, since any character is allowed in a key.
+    ///
+    /// ```rust
+    /// use querable::{types::Tokenizer, default::JsonPointerTokenizer};
+    ///
+    /// assert_eq!(JsonPointerTokenizer::default_split("/a:-b"), ("/a:-b", None));
+    /// ```
+    ///
+    #[inline]
+    fn default_split(path: &str) -> (&str, Option<&str>) {
+        (path, None)
+    }
+
+    /// Join paths by `/`.
+    ///
+    /// Unlike [SlashTokenizer](SlashTokenizer), a trailing `/` refers to an
+    /// empty key, so only a missing `/` in front of `relative` is added.
+    ///
+    /// ```rust
+    /// use querable::{types::Tokenizer, default::JsonPointerTokenizer, query::Query, step::Step};
+    ///
+    /// assert_eq!(JsonPointerTokenizer::join_paths("/a", "b"), "/a/b");
+    ///
+    /// let path = JsonPointerTokenizer::join_paths("/a/", "/b");
+    /// assert_eq!(path, "/a//b");
+    ///
+    /// let steps = [Step::Key("a".into()), Step::Key("".into()), Step::Key("b".into())];
+    /// assert_eq!(Query::parse::<JsonPointerTokenizer>(&path).map(|q| q.steps().to_vec()), Ok(steps.to_vec()));
+    /// assert_eq!(JsonPointerTokenizer::render(&steps), path);
+    ///
+    /// # #[cfg(feature = "value")]
+    /// # {
+    /// use querable::{dict, lookup, value::Value};
+    ///
+    /// let data = dict! { "a" => dict! { "" => dict! { "b" => 1 } } };
+    /// assert_eq!(lookup::<_, _, JsonPointerTokenizer>(&data, &path), Ok(Value::integer(1)));
+    /// # }
+    /// ```
+    ///
+    fn join_paths(base: &str, relative: &str) -> String {
+        match relative {
+            "" => String::from(base),
+            _ if relative.starts_with('/') => format!("{}{}", base, relative),
+            _ => format!("{}/{}", base, relative),
+        }
+    }
+
+    /// Classify a path step, resolving `~1` and `~0` escapes for keys.
+    ///
+    /// ```rust
+    /// use querable::{types::Tokenizer, default::JsonPointerTokenizer, step::Step};
+    ///
+    /// assert_eq!(JsonPointerTokenizer::step_parse("0"), Ok(Step::Index(0)));
+    /// assert_eq!(JsonPointerTokenizer::step_parse("a~1b~0"), Ok(Step::Key("a/b~".into())));
+    /// ```
+    ///
+    fn step_parse(key: &str) -> Result<Step<'_>, IndexError> {
+        match Self::index_parse(key) {
+            Ok(idx) => Ok(Step::Index(idx)),
+            Err(_) if key.contains('~') => Ok(Step::Key(Cow::Owned(
+                key.replace("~1", "/").replace("~0", "~"),
+            ))),
+            Err(_) => Ok(Step::Key(key.into())),
+        }
+    }
+
+    /// Write a step, escaping '~' and '/' in keys.
+    ///
+    /// ```rust
+    /// use querable::{types::Tokenizer, default::JsonPointerTokenizer, step::Step};
+    ///
+    /// assert_eq!(JsonPointerTokenizer::render(&[Step::Key("a/b~".into())]), "/a~1b~0");
+    /// ```
+    ///
+    fn write_step<W>(out: &mut W, step: &Step<'_>) -> fmt::Result
+    where
+        W: fmt::Write + ?Sized,
+    {
+        match step {
+            Step::Index(idx) => write!(out, "{}", idx),
+            Step::Key(key) => key.chars().try_for_each(|c| match c {
+                '~' => out.write_str("~0"),
+                '/' => out.write_str("~1"),
+                c => out.write_char(c),
+            }),
+            Step::FromEnd(n) => write!(out, "-{}", n),
+            Step::Wildcard => out.write_char('*'),
+        }
+    }
+
+    #[inline]
+    fn write_path<W>(out: &mut W, steps: &[Step<'_>]) -> fmt::Result
+    where
+        W: fmt::Write + ?Sized,
+    {
+        for step in steps {
+            out.write_char('/')?;
+            Self::write_step(out, step)?;
+        }
+
+        Ok(())
+    }
+}
+
+///
+/// [DelimTokenizer](DelimTokenizer) splits query by a compile-time separator `SEP` :
+/// ```
+/// // DelimTokenizer<':'> : 0:test:1
+/// // DelimTokenizer<'|'> : test|test|1
+/// ```
+///
+/// Array index are plain integers like [SlashTokenizer](SlashTokenizer),
+/// but unlike it, the query isn't prefixed by the separator, so
+/// `DelimTokenizer<'/'>` parses `0/test` rather than `/0/test`.
+///
+pub struct DelimTokenizer<const SEP: char>;
+
+impl<const SEP: char> Tokenizer for DelimTokenizer<SEP> {
+    #[inline]
+    fn index_parse(key: &str) -> Result<usize, IndexError> {
+        digits_parse(key, key)
+    }
+
+    /// Parse key/path index, split by `SEP`.
+    ///
+    /// ```rust
+    /// use querable::{types::Tokenizer, default::DelimTokenizer, error::KeyError};
+    ///
+    /// assert_eq!(DelimTokenizer::<':'>::dict_parse("a:b:c"), Ok((Some("a"), Some("b:c"))));
+    /// assert_eq!(DelimTokenizer::<':'>::dict_parse(":b"), Err(KeyError::EmptyKey));
+    /// ```
+    ///
+    fn dict_parse(key: &str) -> Result<State<'_>, KeyError> {
+        if key.is_empty() {
+            return Err(KeyError::EmptyKey);
+        }
+
+        match key.find(SEP) {
+            Some(0) => Err(KeyError::EmptyKey),
+            Some(idx) => {
+                let current = &key[..idx];
+
+                match current.find(char::is_whitespace) {
+                    Some(_) => Err(KeyError::ParseError(String::from(current))),
+                    _ => Ok((Some(current), Some(&key[idx + SEP.len_utf8()..]))),
+                }
+            }
+            None => Ok((Some(key), None)),
+        }
+    }
+
+    #[inline]
+    fn join_paths(base: &str, relative: &str) -> String {
+        join_with(base, relative, SEP, false)
+    }
+
+    /// Splits a fallback off on the first `:-`, except when `SEP` is ':'
+    /// since `a:-1` is then the last element of `a`.
+    ///
+    /// ```rust
+    /// use querable::{types::Tokenizer, default::DelimTokenizer};
+    ///
+    /// assert_eq!(DelimTokenizer::<':'>::default_split("a:-1"), ("a:-1", None));
+    /// assert_eq!(DelimTokenizer::<'|'>::default_split("a|b:-1"), ("a|b", Some("1")));
+    /// ```
+    ///
+    #[inline]
+    fn default_split(path: &str) -> (&str, Option<&str>) {
+        match SEP {
+            ':' => (path, None),
+            _ => DefaultTokenizer::default_split(path),
+        }
+    }
+
+    #[inline]
+    fn write_step<W>(out: &mut W, step: &Step<'_>) -> fmt::Result
+    where
+        W: fmt::Write + ?Sized,
+    {
+        SlashTokenizer::write_step(out, step)
+    }
+
+    fn write_path<W>(out: &mut W, steps: &[Step<'_>]) -> fmt::Result
+    where
+        W: fmt::Write + ?Sized,
+    {
+        for (idx, step) in steps.iter().enumerate() {
+            if idx > 0 {
+                out.write_char(SEP)?;
+            }
+
+            Self::write_step(out, step)?;
+        }
+
+        Ok(())
+    }
+}
+
+///
+/// [StrictNumericTokenizer](StrictNumericTokenizer) have the same format query as
+/// `DelimTokenizer<'.'>`, array index are plain integers :
+/// ```
+/// // list.0.name
+/// ```
+///
+/// Unlike it, a segment that is an integer is always an array index, so it
+/// can't be used as a dictionary key, e.g. `config.0` on a dictionary `config`
+/// is [Error::NumericKeyOnDictionary](crate::error::Error::NumericKeyOnDictionary)
+/// even when it has a `"0"` key.
+///
+pub struct StrictNumericTokenizer;
+
+impl Tokenizer for StrictNumericTokenizer {
+    #[inline]
+    fn index_parse(key: &str) -> Result<usize, IndexError> {
+        DelimTokenizer::<'.'>::index_parse(key)
+    }
+
+    #[inline]
+    fn dict_parse(key: &str) -> Result<State<'_>, KeyError> {
+        DelimTokenizer::<'.'>::dict_parse(key)
+    }
+
+    #[inline]
+    fn join_paths(base: &str, relative: &str) -> String {
+        DelimTokenizer::<'.'>::join_paths(base, relative)
+    }
+
+    #[inline]
+    fn numeric_keys() -> bool {
+        false
+    }
+
+    #[inline]
+    fn write_step<W>(out: &mut W, step: &Step<'_>) -> fmt::Result
+    where
+        W: fmt::Write + ?Sized,
+    {
+        DelimTokenizer::<'.'>::write_step(out, step)
+    }
+
+    #[inline]
+    fn write_path<W>(out: &mut W, steps: &[Step<'_>]) -> fmt::Result
+    where
+        W: fmt::Write + ?Sized,
+    {
+        DelimTokenizer::<'.'>::write_path(out, steps)
+    }
+}
+
+///
+/// [OneBasedTokenizer](OneBasedTokenizer) wraps another tokenizer so array
+/// index in a query starts from 1, e.g `[1]` is the first element :
+/// ```
+/// // OneBasedTokenizer<DefaultTokenizer> : [1].test.[1]
+/// // OneBasedTokenizer<SlashTokenizer> : /1/test/1
+/// ```
+///
+/// [Queryable::query_array](crate::types::Queryable::query_array) still
+/// receives 0-based index.
+///
+pub struct OneBasedTokenizer<T>(PhantomData<T>);
+
+// shift 1-based index into 0-based index
+#[inline]
+fn one_based(key: &str, idx: usize) -> Result<usize, IndexError> {
+    idx.checked_sub(1)
+        .ok_or_else(|| IndexError::Underflow(String::from(key)))
+}
+
+// shift 0-based index step back into 1-based index step
+#[inline]
+fn one_based_step<'a>(step: &'a Step<'_>) -> Step<'a> {
+    match step {
+        Step::Index(idx) => Step::Index(idx + 1),
+        Step::Key(key) => Step::Key(Cow::Borrowed(key)),
+        Step::FromEnd(n) => Step::FromEnd(*n),
+        Step::Wildcard => Step::Wildcard,
+    }
+}
+
+impl<T> Tokenizer for OneBasedTokenizer<T>
+where
+    T: Tokenizer,
+{
+    /// Parse 1-based index array.
+    ///
+    /// ```rust
+    /// use querable::{types::Tokenizer, default::{DefaultTokenizer, OneBasedTokenizer}, error::IndexError};
+    ///
+    /// assert_eq!(OneBasedTokenizer::<DefaultTokenizer>::index_parse("[1]"), Ok(0));
+    /// assert_eq!(OneBasedTokenizer::<DefaultTokenizer>::index_parse("[0]"), Err(IndexError::Underflow(String::from("[0]"))));
+    /// ```
+    ///
+    #[inline]
+    fn index_parse(key: &str) -> Result<usize, IndexError> {
+        T::index_parse(key).and_then(|idx| one_based(key, idx))
+    }
+
+    #[inline]
+    fn dict_parse(key: &str) -> Result<State<'_>, KeyError> {
+        T::dict_parse(key)
+    }
+
+    #[inline]
+    fn default_split(path: &str) -> (&str, Option<&str>) {
+        T::default_split(path)
+    }
+
+    #[inline]
+    fn is_root(path: &str) -> bool {
+        T::is_root(path)
+    }
+
+    #[inline]
+    fn numeric_keys() -> bool {
+        T::numeric_keys()
+    }
+
+    #[inline]
+    fn classify(segment: &str) -> Segment {
+        T::classify(segment)
+    }
+
+    #[inline]
+    fn peek_kind(remaining: &str) -> Option<QueryKind> {
+        T::peek_kind(remaining)
+    }
+
+    #[inline]
+    fn join_paths(base: &str, relative: &str) -> String {
+        T::join_paths(base, relative)
+    }
+
+    #[inline]
+    fn step_parse(key: &str) -> Result<Step<'_>, IndexError> {
+        match T::step_parse(key)? {
+            Step::Index(idx) => one_based(key, idx).map(Step::Index),
+            step => Ok(step),
+        }
+    }
+
+    #[inline]
+    fn write_step<W>(out: &mut W, step: &Step<'_>) -> fmt::Result
+    where
+        W: fmt::Write + ?Sized,
+    {
+        T::write_step(out, &one_based_step(step))
+    }
+
+    fn write_path<W>(out: &mut W, steps: &[Step<'_>]) -> fmt::Result
+    where
+        W: fmt::Write + ?Sized,
+    {
+        let steps = steps.iter().map(one_based_step).collect::<Vec<_>>();
+        T::write_path(out, &steps)
     }
 }
@@ -0,0 +1,51 @@
+//!
+//! Difference between two [Queryable](crate::types::Queryable) documents.
+//!
+//! Documents are being compared by their flattened leaves, see
+//! [Queryable::flatten](crate::types::Queryable::flatten), so each
+//! [Change](Change) is keyed by the rendered path of the leaf.
+//!
+use std::collections::BTreeMap;
+
+///
+/// Change of a single leaf, from the old document into the new one.
+///
+#[derive(Debug, Clone, PartialEq)]
+pub enum Change<V> {
+    // leaf only exists in the new document
+    Added(V),
+    // leaf only exists in the old document
+    Removed(V),
+    // old, new
+    Modified(V, V),
+}
+
+// changes between flattened leaves of `old` and `new`, ordered by path
+pub(crate) fn diff_leaves<V>(
+    old: Vec<(String, &V)>,
+    new: Vec<(String, &V)>,
+) -> Vec<(String, Change<V>)>
+where
+    V: PartialEq + Clone,
+{
+    let mut new = new.into_iter().collect::<BTreeMap<_, _>>();
+    let mut changes = BTreeMap::new();
+
+    for (path, old) in old {
+        match new.remove(&path) {
+            Some(new) if new != old => {
+                changes.insert(path, Change::Modified(old.clone(), new.clone()));
+            }
+            Some(_) => {}
+            None => {
+                changes.insert(path, Change::Removed(old.clone()));
+            }
+        }
+    }
+
+    for (path, new) in new {
+        changes.insert(path, Change::Added(new.clone()));
+    }
+
+    changes.into_iter().collect()
+}
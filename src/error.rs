@@ -1,36 +1,160 @@
 use crate::kind::QueryKind;
-use std::{convert, num::ParseIntError};
+use std::{borrow::Cow, convert, error, fmt, num::ParseIntError, str::Utf8Error};
+
+#[cfg(feature = "std")]
+use std::io;
 
 #[derive(Debug, PartialEq)]
 pub enum Error {
-    // is an error for dictionary key not exists
-    KeyNotExist(String),
-    // is an error for array index not exists or out of bound
-    IndexNotExist(usize),
+    // is an error for dictionary key not exists, see `Error::key_not_exist`
+    KeyNotExist(Cow<'static, str>),
+    // is an error for array index not exists or out of bound, `len` being the array length
+    IndexNotExist {
+        index: usize,
+        len: usize,
+    },
     EmptyPath(QueryKind),
-    UnknownType(String),
+    UnknownType(Cow<'static, str>),
+    // is an error for path that still has segments left when it reaches a
+    // node that can't be traversed, e.g. a scalar
+    NotTraversable {
+        path: String,
+        kind_hint: &'static str,
+    },
     IndexError(IndexError),
     KeyError(KeyError),
     // path, expected, found
     TypeError(String, QueryKind, QueryKind),
+    // path, expected; same as TypeError but a scalar (leaf) is being found
+    ScalarTypeError(String, QueryKind),
+    // is an error for query scheme prefix that has no tokenizer
+    UnknownScheme(String),
+    // is an error for path segment that isn't an index while traversing an array
+    ExpectedIndex {
+        segment: String,
+    },
+    // is an error for path that has no parent segment
+    NoParent,
+    // is an error for wildcard in a path that resolves into a single value
+    UnexpectedWildcard,
+    // is an error for segment that parses as an array index while traversing a
+    // dictionary, for tokenizers without numeric keys
+    NumericKeyOnDictionary(String),
+    // is an error for query given as bytes that aren't valid UTF-8
+    InvalidQueryEncoding(Utf8Error),
+    // is an error for cursor being used after the document it was created
+    // from has been mutated
+    Stale,
 }
 
 #[derive(Debug, PartialEq)]
 pub enum IndexError {
-    IntError(ParseIntError),
+    // is an error for index that can't be parsed as an integer, `input` being the offending text
+    IntError {
+        input: String,
+        source: ParseIntError,
+    },
     ParseError(String),
     // TODO: @zerosign, maybe use StdError ?
     CustomError(String),
+    // is an error for index that being written as float or scientific notation
+    NotAnInteger(String),
+    // is an error for index that is larger than usize::MAX
+    Overflow(String),
+    // is an error for index that is lower than the first index, e.g `[0]` in 1-based index
+    Underflow(String),
 }
 
 #[derive(Debug, PartialEq)]
 pub enum KeyError {
     ParseError(String),
     EmptyKey,
+    // is an error for an empty segment between consecutive separators,
+    // `position` is the byte offset of the empty segment in `query`
+    EmptySegment { query: String, position: usize },
     // TODO: @zerosign, maybe use StdError ?
     CustomError(String),
 }
 
+impl Error {
+    ///
+    /// [Error::KeyNotExist](Error::KeyNotExist) of `key`, a `&'static str`
+    /// key is being borrowed rather than copied into a `String`.
+    ///
+    #[inline]
+    pub fn key_not_exist<K>(key: K) -> Self
+    where
+        K: Into<Cow<'static, str>>,
+    {
+        Error::KeyNotExist(key.into())
+    }
+
+    ///
+    /// [Error::UnknownType](Error::UnknownType) at `path`, a
+    /// `&'static str` path is being borrowed rather than copied into a `String`.
+    ///
+    #[inline]
+    pub fn unknown_type<P>(path: P) -> Self
+    where
+        P: Into<Cow<'static, str>>,
+    {
+        Error::UnknownType(path.into())
+    }
+
+    ///
+    /// Whether the error comes from the query itself (malformed or can't be
+    /// resolved regardless of the data), rather than from the data.
+    ///
+    pub fn is_query_error(&self) -> bool {
+        match self {
+            Error::IndexError(_)
+            | Error::KeyError(_)
+            | Error::EmptyPath(_)
+            | Error::UnknownScheme(_)
+            | Error::ExpectedIndex { .. }
+            | Error::NoParent
+            | Error::UnexpectedWildcard
+            | Error::InvalidQueryEncoding(_) => true,
+            Error::KeyNotExist(_)
+            | Error::IndexNotExist { .. }
+            | Error::UnknownType(_)
+            | Error::NotTraversable { .. }
+            | Error::TypeError(..)
+            | Error::ScalarTypeError(..)
+            | Error::NumericKeyOnDictionary(_)
+            | Error::Stale => false,
+        }
+    }
+
+    ///
+    /// Whether the error comes from the data not matching the query,
+    /// e.g. missing key or index, or mismatched type.
+    ///
+    /// A stale cursor ([Error::Stale](Error::Stale)) is neither a query
+    /// nor a data error, it's a misuse of the cursor by the caller.
+    ///
+    pub fn is_data_error(&self) -> bool {
+        match self {
+            Error::KeyNotExist(_)
+            | Error::IndexNotExist { .. }
+            | Error::UnknownType(_)
+            | Error::NotTraversable { .. }
+            | Error::TypeError(..)
+            | Error::ScalarTypeError(..)
+            | Error::NumericKeyOnDictionary(_) => true,
+            Error::IndexError(_)
+            | Error::KeyError(_)
+            | Error::EmptyPath(_)
+            | Error::UnknownScheme(_)
+            | Error::ExpectedIndex { .. }
+            | Error::NoParent
+            | Error::UnexpectedWildcard
+            | Error::InvalidQueryEncoding(_)
+            | Error::Stale => false,
+        }
+    }
+}
+
 impl convert::From<KeyError> for Error {
     #[inline]
     fn from(e: KeyError) -> Self {
@@ -44,3 +168,122 @@ impl convert::From<IndexError> for Error {
         Error::IndexError(e)
     }
 }
+
+impl fmt::Display for IndexError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IndexError::IntError { input, source } => {
+                write!(f, "invalid array index {:?}: {}", input, source)
+            }
+            IndexError::ParseError(key) => write!(f, "invalid array index syntax: {:?}", key),
+            IndexError::CustomError(reason) => write!(f, "{}", reason),
+            IndexError::NotAnInteger(index) => {
+                write!(f, "array index must be a whole number, got {:?}", index)
+            }
+            IndexError::Overflow(index) => write!(
+                f,
+                "array index {:?} is too large, maximum is {}",
+                index,
+                usize::MAX
+            ),
+            IndexError::Underflow(index) => write!(f, "array index {:?} must be >= 1", index),
+        }
+    }
+}
+
+impl error::Error for IndexError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            IndexError::IntError { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for KeyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            KeyError::ParseError(key) => write!(f, "invalid key syntax: {:?}", key),
+            KeyError::EmptyKey => write!(f, "empty key"),
+            KeyError::EmptySegment { query, position } => {
+                write!(f, "empty segment at {} of {:?}", position, query)
+            }
+            KeyError::CustomError(reason) => write!(f, "{}", reason),
+        }
+    }
+}
+
+impl error::Error for KeyError {}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::KeyNotExist(key) => write!(f, "key {:?} doesn't exist", key),
+            Error::IndexNotExist { index, len } => {
+                write!(f, "index {} doesn't exist, length is {}", index, len)
+            }
+            Error::EmptyPath(kind) => write!(f, "empty path while querying {}", kind),
+            Error::UnknownType(path) => write!(f, "unknown type at {:?}", path),
+            Error::NotTraversable { path, kind_hint } => {
+                write!(f, "can't resolve {:?} on a {}", path, kind_hint)
+            }
+            Error::IndexError(e) => write!(f, "{}", e),
+            Error::KeyError(e) => write!(f, "{}", e),
+            Error::TypeError(path, expected, found) => {
+                write!(f, "expected {} at {:?}, found {}", expected, path, found)
+            }
+            Error::ScalarTypeError(path, expected) => {
+                write!(f, "expected {} at {:?}, found scalar", expected, path)
+            }
+            Error::UnknownScheme(scheme) => write!(f, "unknown query scheme {:?}", scheme),
+            Error::ExpectedIndex { segment } => {
+                write!(f, "expected an array index, got {:?}", segment)
+            }
+            Error::NoParent => write!(f, "path has no parent"),
+            Error::UnexpectedWildcard => {
+                write!(f, "wildcard can't be resolved into a single value")
+            }
+            Error::NumericKeyOnDictionary(segment) => {
+                write!(f, "numeric segment {:?} can't be a dictionary key", segment)
+            }
+            Error::InvalidQueryEncoding(e) => write!(f, "query isn't valid UTF-8: {}", e),
+            Error::Stale => write!(f, "cursor is stale, the document has been mutated"),
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Error::IndexError(e) => Some(e),
+            Error::KeyError(e) => Some(e),
+            Error::InvalidQueryEncoding(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+///
+/// Bridge into `std::io`, missing key or index is
+/// [io::ErrorKind::NotFound](io::ErrorKind::NotFound), mismatched type is
+/// [io::ErrorKind::InvalidInput](io::ErrorKind::InvalidInput), stale cursor
+/// is [io::ErrorKind::Other](io::ErrorKind::Other) and malformed query is
+/// [io::ErrorKind::InvalidData](io::ErrorKind::InvalidData).
+///
+#[cfg(feature = "std")]
+impl convert::From<Error> for io::Error {
+    fn from(e: Error) -> Self {
+        let kind = match e {
+            Error::KeyNotExist(_) | Error::IndexNotExist { .. } => io::ErrorKind::NotFound,
+            Error::TypeError(..)
+            | Error::ScalarTypeError(..)
+            | Error::UnknownType(_)
+            | Error::NotTraversable { .. }
+            | Error::NumericKeyOnDictionary(_) => io::ErrorKind::InvalidInput,
+            Error::Stale => io::ErrorKind::Other,
+            _ => io::ErrorKind::InvalidData,
+        };
+
+        io::Error::new(kind, e)
+    }
+}
@@ -0,0 +1,86 @@
+//!
+//! Dictionary backed by a lookup function.
+//!
+//! [FnDict](FnDict) resolves keys by calling a function instead of
+//! looking into a concrete map, e.g. for environment variables or a
+//! database-backed getter, so they could be queried without being
+//! materialized first.
+//!
+use crate::{
+    error::Error,
+    kind::QueryKind,
+    step::Step,
+    types::{Queryable, VALUES_SEGMENT},
+};
+
+///
+/// [Queryable](Queryable) dictionary where `query_dict(key)` is `f(key)`,
+/// `None` being [Error::KeyNotExist](Error::KeyNotExist).
+///
+/// - It's always [QueryKind::Dictionary](QueryKind::Dictionary), an index
+///   is [Error::TypeError](Error::TypeError).
+/// - Keys can't be listed, so it has no
+///   [children](Queryable::children) and
+///   [VALUES_SEGMENT](VALUES_SEGMENT) doesn't exist.
+///
+/// ```rust
+/// use querable::{default::DefaultTokenizer, fn_dict::FnDict, lookup};
+///
+/// let squares = FnDict::new(|key: &str| key.parse::<u8>().ok().and_then(|n| n.checked_mul(n)));
+///
+/// assert_eq!(lookup::<_, _, DefaultTokenizer>(&squares, "12"), Ok(144));
+/// ```
+///
+#[derive(Clone, Copy)]
+pub struct FnDict<F> {
+    f: F,
+}
+
+impl<F> FnDict<F> {
+    #[inline]
+    pub fn new(f: F) -> Self {
+        FnDict { f }
+    }
+}
+
+impl<F, V> Queryable for FnDict<F>
+where
+    F: Fn(&str) -> Option<V>,
+    V: Queryable<Output = V>,
+{
+    type Output = V;
+
+    #[inline]
+    fn query_kind(&self) -> Option<QueryKind> {
+        Some(QueryKind::Dictionary)
+    }
+
+    #[inline]
+    fn from_default_str(text: &str) -> Option<V> {
+        V::from_default_str(text)
+    }
+
+    #[inline]
+    fn query_dict(&self, path: &str) -> Result<V, Error> {
+        (self.f)(path).ok_or_else(|| Error::key_not_exist(String::from(path)))
+    }
+
+    #[inline]
+    fn query_array(&self, idx: usize) -> Result<V, Error> {
+        Err(Error::TypeError(
+            format!("[{}]", idx),
+            QueryKind::Array,
+            QueryKind::Dictionary,
+        ))
+    }
+
+    #[inline]
+    fn query_values(&self) -> Result<V, Error> {
+        Err(Error::key_not_exist(VALUES_SEGMENT))
+    }
+
+    #[inline]
+    fn children(&self) -> Vec<(Step<'_>, &V)> {
+        Vec::new()
+    }
+}
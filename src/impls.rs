@@ -0,0 +1,622 @@
+//!
+//! [Queryable](Queryable) implementation for std types.
+//!
+use crate::{
+    accessor::MapLike,
+    error::Error,
+    kind::QueryKind,
+    step::Step,
+    types::{Queryable, VALUES_SEGMENT},
+};
+use std::{
+    collections::{BTreeMap, HashMap},
+    hash::BuildHasher,
+    rc::Rc,
+    sync::Arc,
+};
+
+///
+/// Fixed size array are always [QueryKind::Array](QueryKind::Array),
+/// querying it resolves into its element.
+///
+impl<T, const N: usize> Queryable for [T; N]
+where
+    T: Queryable<Output = T> + Clone,
+{
+    type Output = T;
+
+    #[inline]
+    fn query_kind(&self) -> Option<QueryKind> {
+        Some(QueryKind::Array)
+    }
+
+    #[inline]
+    fn from_default_str(text: &str) -> Option<T> {
+        T::from_default_str(text)
+    }
+
+    #[inline]
+    fn query_dict(&self, path: &str) -> Result<T, Error> {
+        Err(Error::TypeError(
+            String::from(path),
+            QueryKind::Dictionary,
+            QueryKind::Array,
+        ))
+    }
+
+    #[inline]
+    fn query_array(&self, idx: usize) -> Result<T, Error> {
+        self.get(idx)
+            .cloned()
+            .ok_or(Error::IndexNotExist { index: idx, len: N })
+    }
+
+    #[inline]
+    fn into_query_array(self, idx: usize) -> Result<T, Error> {
+        IntoIterator::into_iter(self)
+            .nth(idx)
+            .ok_or(Error::IndexNotExist { index: idx, len: N })
+    }
+
+    #[inline]
+    fn query_values(&self) -> Result<T, Error> {
+        Err(Error::TypeError(
+            String::from(VALUES_SEGMENT),
+            QueryKind::Dictionary,
+            QueryKind::Array,
+        ))
+    }
+
+    #[inline]
+    fn children(&self) -> Vec<(Step<'_>, &T)> {
+        self.iter()
+            .enumerate()
+            .map(|(idx, child)| (Step::Index(idx), child))
+            .collect()
+    }
+}
+
+///
+/// Bytes are scalars (leaf), e.g. the elements of
+/// [ByteArray](crate::bytes::ByteArray).
+///
+impl Queryable for u8 {
+    type Output = u8;
+
+    #[inline]
+    fn query_kind(&self) -> Option<QueryKind> {
+        None
+    }
+
+    #[inline]
+    fn query_self(&self) -> Result<u8, Error> {
+        Ok(*self)
+    }
+
+    #[inline]
+    fn from_default_str(text: &str) -> Option<u8> {
+        text.parse().ok()
+    }
+
+    #[inline]
+    fn query_dict(&self, path: &str) -> Result<u8, Error> {
+        Err(Error::unknown_type(String::from(path)))
+    }
+
+    #[inline]
+    fn query_array(&self, idx: usize) -> Result<u8, Error> {
+        Err(Error::unknown_type(format!("[{}]", idx)))
+    }
+
+    #[inline]
+    fn query_values(&self) -> Result<u8, Error> {
+        Err(Error::unknown_type(VALUES_SEGMENT))
+    }
+
+    #[inline]
+    fn children(&self) -> Vec<(Step<'_>, &u8)> {
+        Vec::new()
+    }
+}
+
+//
+// Homogeneous tuples are [QueryKind::Array](QueryKind::Array) of their
+// positional fields, the same as fixed size arrays. Tuples of mixed types
+// would need a common `Output` for all of their fields.
+//
+macro_rules! tuple_impl {
+    ($($len:expr => ($($idx:tt $t:ident),+);)+) => {
+        $(
+            impl<T> Queryable for ($($t,)+)
+            where
+                T: Queryable<Output = T> + Clone,
+            {
+                type Output = T;
+
+                #[inline]
+                fn query_kind(&self) -> Option<QueryKind> {
+                    Some(QueryKind::Array)
+                }
+
+                #[inline]
+                fn from_default_str(text: &str) -> Option<T> {
+                    T::from_default_str(text)
+                }
+
+                #[inline]
+                fn query_dict(&self, path: &str) -> Result<T, Error> {
+                    Err(Error::TypeError(
+                        String::from(path),
+                        QueryKind::Dictionary,
+                        QueryKind::Array,
+                    ))
+                }
+
+                #[inline]
+                fn query_array(&self, idx: usize) -> Result<T, Error> {
+                    match idx {
+                        $($idx => Ok(self.$idx.clone()),)+
+                        _ => Err(Error::IndexNotExist { index: idx, len: $len }),
+                    }
+                }
+
+                #[inline]
+                fn into_query_array(self, idx: usize) -> Result<T, Error> {
+                    match idx {
+                        $($idx => Ok(self.$idx),)+
+                        _ => Err(Error::IndexNotExist { index: idx, len: $len }),
+                    }
+                }
+
+                #[inline]
+                fn query_values(&self) -> Result<T, Error> {
+                    Err(Error::TypeError(
+                        String::from(VALUES_SEGMENT),
+                        QueryKind::Dictionary,
+                        QueryKind::Array,
+                    ))
+                }
+
+                #[inline]
+                fn children(&self) -> Vec<(Step<'_>, &T)> {
+                    vec![$((Step::Index($idx), &self.$idx)),+]
+                }
+            }
+        )+
+    };
+}
+
+tuple_impl! {
+    1 => (0 T);
+    2 => (0 T, 1 T);
+    3 => (0 T, 1 T, 2 T);
+    4 => (0 T, 1 T, 2 T, 3 T);
+    5 => (0 T, 1 T, 2 T, 3 T, 4 T);
+    6 => (0 T, 1 T, 2 T, 3 T, 4 T, 5 T);
+    7 => (0 T, 1 T, 2 T, 3 T, 4 T, 5 T, 6 T);
+    8 => (0 T, 1 T, 2 T, 3 T, 4 T, 5 T, 6 T, 7 T);
+    9 => (0 T, 1 T, 2 T, 3 T, 4 T, 5 T, 6 T, 7 T, 8 T);
+    10 => (0 T, 1 T, 2 T, 3 T, 4 T, 5 T, 6 T, 7 T, 8 T, 9 T);
+    11 => (0 T, 1 T, 2 T, 3 T, 4 T, 5 T, 6 T, 7 T, 8 T, 9 T, 10 T);
+    12 => (0 T, 1 T, 2 T, 3 T, 4 T, 5 T, 6 T, 7 T, 8 T, 9 T, 10 T, 11 T);
+}
+
+//
+// Smart pointers are transparent, querying it resolves into the
+// [Output](Queryable::Output) of the value it points to.
+//
+macro_rules! pointer_impl {
+    ($($ptr:ident),*) => {
+        $(
+            impl<T> Queryable for $ptr<T>
+            where
+                T: Queryable,
+            {
+                type Output = T::Output;
+
+                #[inline]
+                fn query_kind(&self) -> Option<QueryKind> {
+                    (**self).query_kind()
+                }
+
+                #[inline]
+                fn query_self(&self) -> Result<T::Output, Error> {
+                    (**self).query_self()
+                }
+
+                #[inline]
+                fn from_default_str(text: &str) -> Option<T::Output> {
+                    T::from_default_str(text)
+                }
+
+                #[inline]
+                fn index_scalar(&self, idx: usize) -> Option<Result<T::Output, Error>> {
+                    (**self).index_scalar(idx)
+                }
+
+                #[inline]
+                fn scalar_kind(&self) -> &'static str {
+                    (**self).scalar_kind()
+                }
+
+                #[inline]
+                fn query_dict(&self, path: &str) -> Result<T::Output, Error> {
+                    (**self).query_dict(path)
+                }
+
+                #[inline]
+                fn query_array(&self, idx: usize) -> Result<T::Output, Error> {
+                    (**self).query_array(idx)
+                }
+
+                #[inline]
+                fn query_values(&self) -> Result<T::Output, Error> {
+                    (**self).query_values()
+                }
+
+                #[inline]
+                fn query_keys(&self) -> Option<Result<T::Output, Error>> {
+                    (**self).query_keys()
+                }
+
+                #[inline]
+                fn children(&self) -> Vec<(Step<'_>, &T::Output)> {
+                    (**self).children()
+                }
+            }
+        )*
+    };
+}
+
+pointer_impl!(Box, Rc, Arc);
+
+//
+// Maps keyed by `String` are [QueryKind::Dictionary](QueryKind::Dictionary)
+// of their values, resolved through [MapLike](MapLike) so `@keys`, `@values`
+// and children follow the order of the map. A blanket impl over every
+// `MapLike` would overlap the impls above, hence one impl per map type.
+//
+macro_rules! map_impl {
+    ($(impl<$($g:ident $(: $bound:path)?),*> for $map:ty;)+) => {
+        $(
+            impl<V, $($g),*> Queryable for $map
+            where
+                V: Queryable<Output = V> + Clone + From<Vec<V>> + From<String>,
+                $($($g: $bound,)?)*
+            {
+                type Output = V;
+
+                #[inline]
+                fn query_kind(&self) -> Option<QueryKind> {
+                    Some(QueryKind::Dictionary)
+                }
+
+                #[inline]
+                fn from_default_str(text: &str) -> Option<V> {
+                    V::from_default_str(text)
+                }
+
+                #[inline]
+                fn query_dict(&self, path: &str) -> Result<V, Error> {
+                    MapLike::get(self, path)
+                        .cloned()
+                        .ok_or_else(|| Error::key_not_exist(String::from(path)))
+                }
+
+                #[inline]
+                fn query_array(&self, idx: usize) -> Result<V, Error> {
+                    Err(Error::TypeError(
+                        format!("[{}]", idx),
+                        QueryKind::Array,
+                        QueryKind::Dictionary,
+                    ))
+                }
+
+                fn query_values(&self) -> Result<V, Error> {
+                    Ok(V::from(
+                        self.children()
+                            .into_iter()
+                            .map(|(_, child)| child.clone())
+                            .collect::<Vec<_>>(),
+                    ))
+                }
+
+                fn query_keys(&self) -> Option<Result<V, Error>> {
+                    let keys = MapLike::keys(self)
+                        .into_iter()
+                        .map(|key| V::from(String::from(key)))
+                        .collect::<Vec<_>>();
+
+                    Some(Ok(V::from(keys)))
+                }
+
+                fn children(&self) -> Vec<(Step<'_>, &V)> {
+                    MapLike::keys(self)
+                        .into_iter()
+                        .filter_map(|key| MapLike::get(self, key).map(|child| (Step::Key(key.into()), child)))
+                        .collect()
+                }
+            }
+        )+
+    };
+}
+
+map_impl! {
+    impl<S: BuildHasher> for HashMap<String, V, S>;
+    impl<> for BTreeMap<String, V>;
+}
+
+#[cfg(feature = "indexmap")]
+map_impl! {
+    impl<S: BuildHasher> for indexmap::IndexMap<String, V, S>;
+}
+
+///
+/// JSON objects are [QueryKind::Dictionary](QueryKind::Dictionary) and
+/// arrays are [QueryKind::Array](QueryKind::Array), anything else is a
+/// scalar that reports its JSON type (`null`, `bool`, `number` or
+/// `string`) through [Queryable::scalar_kind](Queryable::scalar_kind).
+///
+#[cfg(feature = "serde_json")]
+impl Queryable for serde_json::Value {
+    type Output = serde_json::Value;
+
+    #[inline]
+    fn query_kind(&self) -> Option<QueryKind> {
+        match self {
+            serde_json::Value::Object(_) => Some(QueryKind::Dictionary),
+            serde_json::Value::Array(_) => Some(QueryKind::Array),
+            _ => None,
+        }
+    }
+
+    #[inline]
+    fn query_self(&self) -> Result<Self, Error> {
+        Ok(self.clone())
+    }
+
+    ///
+    /// Fallbacks are JSON scalars (e.g. `null`, `true` or `1.5`), or
+    /// strings otherwise.
+    ///
+    fn from_default_str(text: &str) -> Option<Self> {
+        match serde_json::from_str::<serde_json::Value>(text) {
+            Ok(value) if value.is_null() || value.is_boolean() || value.is_number() => Some(value),
+            _ => Some(serde_json::Value::String(String::from(text))),
+        }
+    }
+
+    #[inline]
+    fn scalar_kind(&self) -> &'static str {
+        match self {
+            serde_json::Value::Null => "null",
+            serde_json::Value::Bool(_) => "bool",
+            serde_json::Value::Number(_) => "number",
+            serde_json::Value::String(_) => "string",
+            serde_json::Value::Array(_) => "array",
+            serde_json::Value::Object(_) => "object",
+        }
+    }
+
+    fn query_dict(&self, path: &str) -> Result<Self, Error> {
+        match self {
+            serde_json::Value::Object(d) => d
+                .get(path)
+                .cloned()
+                .ok_or_else(|| Error::key_not_exist(String::from(path))),
+            serde_json::Value::Array(_) => Err(Error::TypeError(
+                String::from(path),
+                QueryKind::Dictionary,
+                QueryKind::Array,
+            )),
+            _ => Err(Error::unknown_type(String::from(path))),
+        }
+    }
+
+    fn query_array(&self, idx: usize) -> Result<Self, Error> {
+        match self {
+            serde_json::Value::Array(d) => d.get(idx).cloned().ok_or(Error::IndexNotExist {
+                index: idx,
+                len: d.len(),
+            }),
+            serde_json::Value::Object(_) => Err(Error::TypeError(
+                format!("[{}]", idx),
+                QueryKind::Array,
+                QueryKind::Dictionary,
+            )),
+            _ => Err(Error::unknown_type(format!("[{}]", idx))),
+        }
+    }
+
+    fn into_query_dict(self, path: &str) -> Result<Self, Error> {
+        match self {
+            serde_json::Value::Object(mut d) => d
+                .remove(path)
+                .ok_or_else(|| Error::key_not_exist(String::from(path))),
+            other => other.query_dict(path),
+        }
+    }
+
+    fn into_query_array(self, idx: usize) -> Result<Self, Error> {
+        match self {
+            serde_json::Value::Array(mut d) if idx < d.len() => Ok(d.swap_remove(idx)),
+            other => other.query_array(idx),
+        }
+    }
+
+    fn query_values(&self) -> Result<Self, Error> {
+        match self {
+            serde_json::Value::Object(d) => {
+                Ok(serde_json::Value::Array(d.values().cloned().collect()))
+            }
+            serde_json::Value::Array(_) => Err(Error::TypeError(
+                String::from(VALUES_SEGMENT),
+                QueryKind::Dictionary,
+                QueryKind::Array,
+            )),
+            _ => Err(Error::unknown_type(VALUES_SEGMENT)),
+        }
+    }
+
+    fn query_keys(&self) -> Option<Result<Self, Error>> {
+        match self {
+            serde_json::Value::Object(d) => Some(Ok(serde_json::Value::Array(
+                d.keys().cloned().map(serde_json::Value::String).collect(),
+            ))),
+            _ => None,
+        }
+    }
+
+    fn children(&self) -> Vec<(Step<'_>, &Self)> {
+        match self {
+            serde_json::Value::Object(d) => d
+                .iter()
+                .map(|(k, v)| (Step::Key(k.as_str().into()), v))
+                .collect(),
+            serde_json::Value::Array(d) => d
+                .iter()
+                .enumerate()
+                .map(|(idx, v)| (Step::Index(idx), v))
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+}
+
+///
+/// Bare JSON object, always [QueryKind::Dictionary](QueryKind::Dictionary),
+/// same as the object of [serde_json::Value](serde_json::Value).
+///
+#[cfg(feature = "serde_json")]
+impl Queryable for serde_json::Map<String, serde_json::Value> {
+    type Output = serde_json::Value;
+
+    #[inline]
+    fn query_kind(&self) -> Option<QueryKind> {
+        Some(QueryKind::Dictionary)
+    }
+
+    #[inline]
+    fn query_self(&self) -> Result<serde_json::Value, Error> {
+        Ok(serde_json::Value::Object(self.clone()))
+    }
+
+    #[inline]
+    fn from_default_str(text: &str) -> Option<serde_json::Value> {
+        serde_json::Value::from_default_str(text)
+    }
+
+    #[inline]
+    fn query_dict(&self, path: &str) -> Result<serde_json::Value, Error> {
+        self.get(path)
+            .cloned()
+            .ok_or_else(|| Error::key_not_exist(String::from(path)))
+    }
+
+    #[inline]
+    fn query_array(&self, idx: usize) -> Result<serde_json::Value, Error> {
+        Err(Error::TypeError(
+            format!("[{}]", idx),
+            QueryKind::Array,
+            QueryKind::Dictionary,
+        ))
+    }
+
+    #[inline]
+    fn into_query_dict(mut self, path: &str) -> Result<serde_json::Value, Error> {
+        self.remove(path)
+            .ok_or_else(|| Error::key_not_exist(String::from(path)))
+    }
+
+    #[inline]
+    fn query_values(&self) -> Result<serde_json::Value, Error> {
+        Ok(serde_json::Value::Array(self.values().cloned().collect()))
+    }
+
+    #[inline]
+    fn query_keys(&self) -> Option<Result<serde_json::Value, Error>> {
+        Some(Ok(serde_json::Value::Array(
+            self.keys()
+                .cloned()
+                .map(serde_json::Value::String)
+                .collect(),
+        )))
+    }
+
+    #[inline]
+    fn children(&self) -> Vec<(Step<'_>, &serde_json::Value)> {
+        self.iter()
+            .map(|(k, v)| (Step::Key(k.as_str().into()), v))
+            .collect()
+    }
+}
+
+///
+/// Bare JSON array, always [QueryKind::Array](QueryKind::Array), same as
+/// the array of [serde_json::Value](serde_json::Value).
+///
+#[cfg(feature = "serde_json")]
+impl Queryable for Vec<serde_json::Value> {
+    type Output = serde_json::Value;
+
+    #[inline]
+    fn query_kind(&self) -> Option<QueryKind> {
+        Some(QueryKind::Array)
+    }
+
+    #[inline]
+    fn query_self(&self) -> Result<serde_json::Value, Error> {
+        Ok(serde_json::Value::Array(self.clone()))
+    }
+
+    #[inline]
+    fn from_default_str(text: &str) -> Option<serde_json::Value> {
+        serde_json::Value::from_default_str(text)
+    }
+
+    #[inline]
+    fn query_dict(&self, path: &str) -> Result<serde_json::Value, Error> {
+        Err(Error::TypeError(
+            String::from(path),
+            QueryKind::Dictionary,
+            QueryKind::Array,
+        ))
+    }
+
+    #[inline]
+    fn query_array(&self, idx: usize) -> Result<serde_json::Value, Error> {
+        self.get(idx).cloned().ok_or(Error::IndexNotExist {
+            index: idx,
+            len: self.len(),
+        })
+    }
+
+    #[inline]
+    fn into_query_array(mut self, idx: usize) -> Result<serde_json::Value, Error> {
+        if idx < self.len() {
+            Ok(self.swap_remove(idx))
+        } else {
+            Err(Error::IndexNotExist {
+                index: idx,
+                len: self.len(),
+            })
+        }
+    }
+
+    #[inline]
+    fn query_values(&self) -> Result<serde_json::Value, Error> {
+        Err(Error::TypeError(
+            String::from(VALUES_SEGMENT),
+            QueryKind::Dictionary,
+            QueryKind::Array,
+        ))
+    }
+
+    #[inline]
+    fn children(&self) -> Vec<(Step<'_>, &serde_json::Value)> {
+        self.iter()
+            .enumerate()
+            .map(|(idx, v)| (Step::Index(idx), v))
+            .collect()
+    }
+}
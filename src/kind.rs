@@ -1,3 +1,5 @@
+use std::fmt;
+
 ///
 /// Since traversals only supports for traversable data structure
 /// like dictionary or array, other than that are mostly literals.
@@ -8,8 +10,20 @@
 /// - [QueryKind::Dictionary](QueryKind::Dictionary) are being used in
 ///   case underlying data structure support fetch value by key/path `&str`.
 ///
-#[derive(Debug, PartialEq)]
+/// Kinds are ordered by declaration, [QueryKind::Array](QueryKind::Array)
+/// comes before [QueryKind::Dictionary](QueryKind::Dictionary).
+///
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
 pub enum QueryKind {
     Array,
     Dictionary,
 }
+
+impl fmt::Display for QueryKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            QueryKind::Array => write!(f, "array"),
+            QueryKind::Dictionary => write!(f, "dictionary"),
+        }
+    }
+}
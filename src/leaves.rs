@@ -0,0 +1,74 @@
+//!
+//! Lazy iterator over the leaves of a [Queryable](Queryable).
+//!
+//! [Queryable::flatten](Queryable::flatten) collects every leaf at once,
+//! [Leaves](Leaves) yields them one at a time, only descending into a
+//! node when the iteration reaches it.
+//!
+use std::{marker::PhantomData, vec};
+
+use crate::{
+    step::Step,
+    types::{Queryable, Tokenizer},
+};
+
+///
+/// Iterator of leaves paired with their path rendered by [Tokenizer](Tokenizer) `T`,
+/// in the same order as [Queryable::flatten](Queryable::flatten).
+///
+/// Returned by [Queryable::leaves](Queryable::leaves).
+///
+pub struct Leaves<'a, V, T> {
+    // children being iterated, one frame per level
+    stack: Vec<vec::IntoIter<(Step<'a>, &'a V)>>,
+    // steps into the node of the topmost frame
+    path: Vec<Step<'a>>,
+    _tokenizer: PhantomData<T>,
+}
+
+impl<'a, V, T> Leaves<'a, V, T>
+where
+    V: Queryable<Output = V>,
+    T: Tokenizer,
+{
+    #[inline]
+    pub(crate) fn new(children: Vec<(Step<'a>, &'a V)>) -> Self {
+        Leaves {
+            stack: vec![children.into_iter()],
+            path: Vec::new(),
+            _tokenizer: PhantomData,
+        }
+    }
+}
+
+impl<'a, V, T> Iterator for Leaves<'a, V, T>
+where
+    V: Queryable<Output = V>,
+    T: Tokenizer,
+{
+    type Item = (String, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let (step, child) = match self.stack.last_mut()?.next() {
+                Some(next) => next,
+                None => {
+                    self.stack.pop();
+                    self.path.pop();
+                    continue;
+                }
+            };
+
+            let children = child.children();
+            self.path.push(step);
+
+            if children.is_empty() {
+                let path = T::render(&self.path);
+                self.path.pop();
+                return Some((path, child));
+            }
+
+            self.stack.push(children.into_iter());
+        }
+    }
+}
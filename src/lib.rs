@@ -11,16 +11,36 @@
 //! There is default `Tokenizer` defined in this crates at `crate::default::DefaultTokenizer`
 //! that uses `[_]` for array index and `path.*` as dictionary resolutions.
 //!
-#![deny(intra_doc_link_resolution_failure)]
+#![deny(rustdoc::broken_intra_doc_links)]
 
 use std::borrow::Cow;
 
+pub mod accessor;
+pub mod bytes;
+#[cfg(feature = "std")]
+pub mod cache;
+pub mod cursor;
 pub mod default;
+pub mod diff;
 pub mod error;
+pub mod fn_dict;
+mod impls;
 pub mod kind;
+pub mod leaves;
+pub mod merge;
+pub mod projected;
+pub mod query;
+#[cfg(feature = "query-cache")]
+pub mod query_cache;
+pub mod step;
 pub mod types;
+#[cfg(feature = "value")]
+pub mod value;
+pub mod versioned;
+pub mod visit;
 
 use error::Error;
+use step::Step;
 use types::{Queryable, Tokenizer};
 
 ///
@@ -33,15 +53,162 @@ use types::{Queryable, Tokenizer};
 /// // lookup<_, _, DefaultTokenizer>(value, "[0]");
 /// ```
 ///
-pub fn lookup<'a, V: 'a, Q, T>(v: &V, query: Q) -> Result<V, Error>
+pub fn lookup<'a, V, Q, T>(v: &V, query: Q) -> Result<V::Output, Error>
 where
     Q: Into<Cow<'a, str>>,
-    V: Queryable,
+    V: Queryable + 'a,
     T: Tokenizer,
 {
     v.query::<T>(&query.into())
 }
 
+///
+/// Same as [lookup](lookup), but takes the query as [Cow](Cow) directly.
+///
+/// Useful for callers that already hold a [Cow](Cow), since
+/// a borrowed query never gets copied into a `String`.
+///
+/// example :
+/// ```
+/// // lookup_cow::<_, DefaultTokenizer>(value, Cow::Borrowed("[0]"));
+/// ```
+///
+pub fn lookup_cow<V, T>(v: &V, query: Cow<'_, str>) -> Result<V::Output, Error>
+where
+    V: Queryable,
+    T: Tokenizer,
+{
+    v.query::<T>(&query)
+}
+
+///
+/// Same as [lookup](lookup), but takes the query as raw bytes, e.g. read
+/// from a socket or a file.
+///
+/// Bytes that aren't valid UTF-8 are
+/// [Error::InvalidQueryEncoding](Error::InvalidQueryEncoding).
+///
+/// example :
+/// ```
+/// // lookup_bytes::<_, DefaultTokenizer>(value, b"users.[0]");
+/// ```
+///
+pub fn lookup_bytes<V, T>(v: &V, query: &[u8]) -> Result<V::Output, Error>
+where
+    V: Queryable,
+    T: Tokenizer,
+{
+    let query = std::str::from_utf8(query).map_err(Error::InvalidQueryEncoding)?;
+
+    v.query::<T>(query)
+}
+
+///
+/// Lookup every value `query` resolves to, paired with its concrete path,
+/// see [Queryable::query_all_paths](Queryable::query_all_paths).
+///
+/// example :
+/// ```
+/// // lookup_all_paths::<_, _, DefaultTokenizer>(value, "users.*.id");
+/// ```
+///
+pub fn lookup_all_paths<'a, V, Q, T>(v: &V, query: Q) -> Result<Vec<(String, V::Output)>, Error>
+where
+    Q: Into<Cow<'a, str>>,
+    V: Queryable + 'a,
+    T: Tokenizer,
+{
+    v.query_all_paths::<T>(&query.into())
+}
+
+///
+/// Lookup the first of `paths` that resolves, like `new_field || old_field`.
+///
+/// Only missing key or index ([Error::KeyNotExist](Error::KeyNotExist),
+/// [Error::IndexNotExist](Error::IndexNotExist)) falls through to the next
+/// path, the last of them is returned when all paths miss. Other errors are
+/// returned right away.
+///
+/// example :
+/// ```
+/// // lookup_first::<_, DefaultTokenizer>(value, &["new_field", "old_field"]);
+/// ```
+///
+pub fn lookup_first<V, T>(v: &V, paths: &[&str]) -> Result<V::Output, Error>
+where
+    V: Queryable,
+    T: Tokenizer,
+{
+    let mut last = Error::KeyError(error::KeyError::EmptyKey);
+
+    for path in paths {
+        match v.query::<T>(path) {
+            Err(e @ Error::KeyNotExist(_)) | Err(e @ Error::IndexNotExist { .. }) => last = e,
+            found => return found,
+        }
+    }
+
+    Err(last)
+}
+
+///
+/// Lookup over data structure by path segments that are already split,
+/// see [Queryable::query_path](Queryable::query_path).
+///
+/// example :
+/// ```
+/// // query_segments(value, &["[0]", "child", "id"]);
+/// ```
+///
+#[inline]
+pub fn query_segments<V>(v: &V, segments: &[&str]) -> Result<V::Output, Error>
+where
+    V: Queryable,
+{
+    v.query_path(segments)
+}
+
+///
+/// Tokenize `path` by using [Tokenizer](Tokenizer) `T` into owned
+/// [Step](Step)s, so it could be parsed once and resolved many times
+/// through [lookup_steps](lookup_steps).
+///
+/// ```rust
+/// use querable::{default::DefaultTokenizer, step::Step, tokenize};
+///
+/// assert_eq!(
+///     tokenize::<DefaultTokenizer>("[0].id"),
+///     Ok(vec![Step::Index(0), Step::Key("id".into())])
+/// );
+/// ```
+///
+#[inline]
+pub fn tokenize<T>(path: &str) -> Result<Vec<Step<'static>>, Error>
+where
+    T: Tokenizer,
+{
+    query::Query::parse::<T>(path).map(|query| query.into_iter().collect())
+}
+
+///
+/// Lookup over data structure by an already tokenized path.
+///
+/// There is no [Tokenizer](Tokenizer) involved, this is useful when
+/// the path are being constructed programmatically or being parsed once
+/// and reused for many lookups.
+///
+/// example :
+/// ```
+/// // lookup_steps(value, &[Step::Index(0), Step::Key("id".into())]);
+/// ```
+///
+pub fn lookup_steps<V>(v: &V, steps: &[Step]) -> Result<V::Output, Error>
+where
+    V: Queryable,
+{
+    v.query_steps(steps)
+}
+
 #[cfg(test)]
 mod tests {
 
@@ -49,14 +216,58 @@ mod tests {
     extern crate log;
 
     use super::{
-        default::{DefaultTokenizer, SlashTokenizer},
-        error::Error,
+        accessor::{AsArray, AsDict, Map},
+        bytes::ByteArray,
+        cursor::Cursor,
+        default::{
+            BackslashTolerantTokenizer, DefaultTokenizer, DelimTokenizer, JsonPointerTokenizer,
+            LenientTokenizer, OneBasedTokenizer, SlashTokenizer, StrictNumericTokenizer,
+            StrictSlashTokenizer,
+        },
+        diff::Change,
+        error::{Error, IndexError, KeyError},
+        fn_dict::FnDict,
         kind::QueryKind,
-        lookup,
-        types::Queryable,
+        lookup, lookup_all_paths, lookup_bytes, lookup_cow, lookup_first, lookup_steps,
+        merge::{ArrayMerge, DictMerge, MergeStrategy},
+        query::{Query, QueryBuilder},
+        query_segments,
+        step::{PathDisplay, Segment, Step},
+        tokenize,
+        types::{Queryable, QueryableMut, Tokenizer, KEYS_SEGMENT, SELF_SEGMENT, VALUES_SEGMENT},
+        versioned::Versioned,
+        visit::QueryVisitor,
+    };
+
+    use std::{
+        borrow::Cow,
+        cell::Cell,
+        collections::{BTreeMap, HashMap},
+        convert::TryFrom,
+        io::Write,
+        rc::Rc,
+        sync::{Arc, Mutex},
     };
 
-    use std::collections::HashMap;
+    thread_local! {
+        // number of `Value::children` calls on the current thread
+        static CHILDREN_CALLS: Cell<usize> = const { Cell::new(0) };
+    }
+
+    // log lines emitted by the tests, so traces could be asserted
+    static CAPTURED: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+    fn init_logger() {
+        let _ = env_logger::builder()
+            .is_test(true)
+            .filter_level(log::LevelFilter::Trace)
+            .format(|buf, record| {
+                let line = record.args().to_string();
+                CAPTURED.lock().unwrap().push(line.clone());
+                writeln!(buf, "{}", line)
+            })
+            .try_init();
+    }
 
     #[derive(Debug, Clone, PartialEq)]
     pub enum Number {
@@ -136,6 +347,13 @@ mod tests {
         Value::bool    => [bool]
     );
 
+    impl From<Vec<Value>> for Value {
+        #[inline]
+        fn from(v: Vec<Value>) -> Self {
+            Value::Array(v)
+        }
+    }
+
     // array!["test", 1, 2 "test"]
     macro_rules! array {
         [] => (Value::Array(Vec::<Value>::new()));
@@ -233,6 +451,8 @@ mod tests {
     }
 
     impl Queryable for Value {
+        type Output = Value;
+
         #[inline]
         fn query_kind(&self) -> Option<QueryKind> {
             match self {
@@ -242,30 +462,224 @@ mod tests {
             }
         }
 
+        #[inline]
+        fn query_self(&self) -> Result<Self, Error> {
+            Ok(self.clone())
+        }
+
+        fn from_default_str(text: &str) -> Option<Self> {
+            let value = match text {
+                "true" | "false" => Value::bool(text == "true"),
+                _ => match (text.parse::<i64>(), text.parse::<f64>()) {
+                    (Ok(v), _) => Value::integer(v),
+                    (_, Ok(v)) => Value::double(v),
+                    _ => Value::string(text),
+                },
+            };
+
+            Some(value)
+        }
+
+        fn index_scalar(&self, idx: usize) -> Option<Result<Self, Error>> {
+            match self {
+                Value::Literal(Literal::String(s)) => Some(
+                    s.chars()
+                        .nth(idx)
+                        .map(|c| Value::string(c.to_string()))
+                        .ok_or(Error::IndexNotExist {
+                            index: idx,
+                            len: s.chars().count(),
+                        }),
+                ),
+                _ => None,
+            }
+        }
+
         fn query_dict(&self, path: &str) -> Result<Self, Error> {
             match self {
                 Value::Dictionary(d) => d
                     .get(path)
                     .cloned()
-                    .ok_or_else(|| Error::KeyNotExist(String::from(path))),
+                    .ok_or_else(|| Error::key_not_exist(String::from(path))),
                 Value::Array(_) => Err(Error::TypeError(
                     String::from(path),
-                    QueryKind::Array,
                     QueryKind::Dictionary,
+                    QueryKind::Array,
                 )),
-                _ => Err(Error::UnknownType(String::from(path))),
+                _ => Err(Error::unknown_type(String::from(path))),
             }
         }
 
         fn query_array(&self, idx: usize) -> Result<Self, Error> {
             match self {
-                Value::Array(d) => d.get(idx).cloned().ok_or(Error::IndexNotExist(idx)),
+                Value::Array(d) => d.get(idx).cloned().ok_or(Error::IndexNotExist {
+                    index: idx,
+                    len: d.len(),
+                }),
+                Value::Dictionary(_) => Err(Error::TypeError(
+                    format!("[{}]", idx),
+                    QueryKind::Array,
+                    QueryKind::Dictionary,
+                )),
+                _ => Err(Error::unknown_type(format!("[{}]", idx))),
+            }
+        }
+
+        fn into_query_dict(self, path: &str) -> Result<Self, Error> {
+            match self {
+                Value::Dictionary(mut d) => d
+                    .remove(path)
+                    .ok_or_else(|| Error::key_not_exist(String::from(path))),
+                other => other.query_dict(path),
+            }
+        }
+
+        fn into_query_array(self, idx: usize) -> Result<Self, Error> {
+            match self {
+                Value::Array(mut d) if idx < d.len() => Ok(d.swap_remove(idx)),
+                other => other.query_array(idx),
+            }
+        }
+
+        fn query_values(&self) -> Result<Self, Error> {
+            match self {
+                Value::Dictionary(d) => {
+                    let mut entries = d.iter().collect::<Vec<_>>();
+                    entries.sort_by_key(|&(k, _)| k);
+
+                    Ok(Value::Array(
+                        entries.into_iter().map(|(_, v)| v.clone()).collect(),
+                    ))
+                }
+                Value::Array(_) => Err(Error::TypeError(
+                    String::from(VALUES_SEGMENT),
+                    QueryKind::Dictionary,
+                    QueryKind::Array,
+                )),
+                _ => Err(Error::unknown_type(VALUES_SEGMENT)),
+            }
+        }
+
+        fn children(&self) -> Vec<(Step<'_>, &Self)> {
+            CHILDREN_CALLS.with(|calls| calls.set(calls.get() + 1));
+
+            match self {
+                Value::Dictionary(d) => d
+                    .iter()
+                    .map(|(k, v)| (Step::Key(k.as_str().into()), v))
+                    .collect(),
+                Value::Array(d) => d
+                    .iter()
+                    .enumerate()
+                    .map(|(idx, v)| (Step::Index(idx), v))
+                    .collect(),
+                _ => Vec::new(),
+            }
+        }
+    }
+
+    impl QueryableMut for Value {
+        #[inline]
+        fn make_empty_dict() -> Self {
+            Value::dict()
+        }
+
+        #[inline]
+        fn rebuild_dict(entries: Vec<(String, Self)>) -> Self {
+            Value::Dictionary(entries.into_iter().collect())
+        }
+
+        #[inline]
+        fn rebuild_array(items: Vec<Self>) -> Self {
+            Value::Array(items)
+        }
+
+        fn dict_entry(&mut self, key: &str) -> Result<&mut Self, Error> {
+            match self {
+                Value::Dictionary(d) => Ok(d
+                    .entry(String::from(key))
+                    .or_insert_with(Value::make_empty_dict)),
+                Value::Array(_) => Err(Error::TypeError(
+                    String::from(key),
+                    QueryKind::Dictionary,
+                    QueryKind::Array,
+                )),
+                _ => Err(Error::unknown_type(String::from(key))),
+            }
+        }
+
+        fn query_dict_mut(&mut self, key: &str) -> Result<&mut Self, Error> {
+            match self {
+                Value::Dictionary(d) => d
+                    .get_mut(key)
+                    .ok_or_else(|| Error::key_not_exist(String::from(key))),
+                Value::Array(_) => Err(Error::TypeError(
+                    String::from(key),
+                    QueryKind::Dictionary,
+                    QueryKind::Array,
+                )),
+                _ => Err(Error::unknown_type(String::from(key))),
+            }
+        }
+
+        fn dict_remove(&mut self, key: &str) -> Result<Self, Error> {
+            match self {
+                Value::Dictionary(d) => d
+                    .remove(key)
+                    .ok_or_else(|| Error::key_not_exist(String::from(key))),
+                Value::Array(_) => Err(Error::TypeError(
+                    String::from(key),
+                    QueryKind::Dictionary,
+                    QueryKind::Array,
+                )),
+                _ => Err(Error::unknown_type(String::from(key))),
+            }
+        }
+
+        fn array_remove(&mut self, idx: usize) -> Result<Self, Error> {
+            match self {
+                Value::Array(d) if idx < d.len() => Ok(d.remove(idx)),
+                Value::Array(d) => Err(Error::IndexNotExist {
+                    index: idx,
+                    len: d.len(),
+                }),
                 Value::Dictionary(_) => Err(Error::TypeError(
                     format!("[{}]", idx),
+                    QueryKind::Array,
+                    QueryKind::Dictionary,
+                )),
+                _ => Err(Error::unknown_type(format!("[{}]", idx))),
+            }
+        }
+
+        fn array_push(&mut self, value: Self) -> Result<(), Error> {
+            match self {
+                Value::Array(d) => {
+                    d.push(value);
+                    Ok(())
+                }
+                Value::Dictionary(_) => Err(Error::TypeError(
+                    String::new(),
+                    QueryKind::Array,
                     QueryKind::Dictionary,
+                )),
+                _ => Err(Error::unknown_type("")),
+            }
+        }
+
+        fn query_array_mut(&mut self, idx: usize) -> Result<&mut Self, Error> {
+            match self {
+                Value::Array(d) => {
+                    let len = d.len();
+                    d.get_mut(idx)
+                        .ok_or(Error::IndexNotExist { index: idx, len })
+                }
+                Value::Dictionary(_) => Err(Error::TypeError(
+                    format!("[{}]", idx),
                     QueryKind::Array,
+                    QueryKind::Dictionary,
                 )),
-                _ => Err(Error::UnknownType(format!("[{}]", idx))),
+                _ => Err(Error::unknown_type(format!("[{}]", idx))),
             }
         }
     }
@@ -283,7 +697,7 @@ mod tests {
 
     #[test]
     fn test_lookup_complex_array() {
-        let _ = env_logger::builder().is_test(true).try_init();
+        init_logger();
 
         let sample = array![array!["Hello world"]];
 
@@ -294,7 +708,7 @@ mod tests {
 
     #[test]
     fn test_lookup_index_not_exists_array() {
-        let _ = env_logger::builder().is_test(true).try_init();
+        init_logger();
 
         let sample = array![array!["test"]];
 
@@ -302,7 +716,7 @@ mod tests {
 
         assert!(found.is_err());
 
-        assert_eq!(found, Err(Error::IndexNotExist(1)),);
+        assert_eq!(found, Err(Error::IndexNotExist { index: 1, len: 1 }),);
     }
 
     #[test]
@@ -318,4 +732,3853 @@ mod tests {
 
         assert!(found.is_ok());
     }
+
+    #[test]
+    fn test_lookup_steps_same_as_query() {
+        let data = array![dict! {
+            "id" => 12,
+            "child" => 2
+        }];
+
+        let steps = [Step::Index(0), Step::Key("id".into())];
+
+        let found = lookup_steps(&data, &steps);
+
+        assert_eq!(found, Ok(Value::integer(12)));
+        assert_eq!(found, lookup::<_, _, DefaultTokenizer>(&data, "[0].id"));
+    }
+
+    #[test]
+    fn test_lookup_steps_type_error() {
+        let data = array![dict! {
+            "id" => 12
+        }];
+
+        let found = lookup_steps(&data, &[Step::Key("id".into())]);
+
+        assert_eq!(
+            found,
+            Err(Error::TypeError(
+                String::from("id"),
+                QueryKind::Dictionary,
+                QueryKind::Array
+            ))
+        );
+    }
+
+    #[test]
+    fn test_query_try_from_scheme() {
+        let data = array![dict! {
+            "id" => 12,
+            "a/b" => 13
+        }];
+
+        let expected = [Step::Index(0), Step::Key("id".into())];
+
+        for query in &["default:[0].id", "slash:/0/id", "jsonptr:/0/id", "[0].id"] {
+            let query = Query::try_from(*query);
+            assert_eq!(query.as_ref().map(Query::steps), Ok(&expected[..]));
+
+            let found = query.and_then(|q| lookup_steps(&data, q.steps()));
+            assert_eq!(found, Ok(Value::integer(12)));
+        }
+
+        let found = Query::try_from("jsonptr:/0/a~1b").and_then(|q| lookup_steps(&data, q.steps()));
+        assert_eq!(found, Ok(Value::integer(13)));
+    }
+
+    #[test]
+    fn test_json_pointer_tokens() {
+        let data = dict! {
+            "a b" => dict! { "c" => 1 },
+            "a" => dict! { "" => dict! { "b" => 2 } },
+            "" => 3,
+            "m~n" => 4
+        };
+
+        let cases = vec![
+            ("/a b/c", Value::integer(1)),
+            ("/a//b", Value::integer(2)),
+            ("/", Value::integer(3)),
+            ("/m~0n", Value::integer(4)),
+        ];
+
+        for (path, expected) in cases {
+            let found = lookup::<_, _, JsonPointerTokenizer>(&data, path);
+            assert_eq!(found, Ok(expected), "{}", path);
+        }
+
+        assert_eq!(
+            Query::parse::<JsonPointerTokenizer>("/a//b").map(|q| q.steps().to_vec()),
+            Ok(vec![
+                Step::Key("a".into()),
+                Step::Key("".into()),
+                Step::Key("b".into())
+            ])
+        );
+    }
+
+    #[test]
+    fn test_query_try_from_unknown_scheme() {
+        assert_eq!(
+            Query::try_from("yaml:[0].id"),
+            Err(Error::UnknownScheme(String::from("yaml")))
+        );
+        assert_eq!(
+            Query::try_from("port:-1").map(|q| q.steps().to_vec()),
+            Query::parse::<DefaultTokenizer>("port:-1").map(|q| q.steps().to_vec())
+        );
+    }
+
+    #[test]
+    fn test_lookup_fixed_size_array() {
+        let sample = [Value::integer(1), Value::string("two"), array![3]];
+
+        let found = lookup::<_, _, DefaultTokenizer>(&sample, "[2]");
+        assert_eq!(found, Ok(array![3]));
+
+        let found = lookup::<_, _, DefaultTokenizer>(&sample, "[2].[0]");
+        assert_eq!(found, Ok(Value::integer(3)));
+
+        let found = lookup::<_, _, DefaultTokenizer>(&sample, "[5]");
+        assert_eq!(found, Err(Error::IndexNotExist { index: 5, len: 3 }));
+    }
+
+    #[test]
+    fn test_lookup_non_index_segment_array() {
+        let data = array![dict! {
+            "id" => 12
+        }];
+
+        let found = lookup::<_, _, SlashTokenizer>(&data, "/id");
+        assert_eq!(
+            found,
+            Err(Error::ExpectedIndex {
+                segment: String::from("id")
+            })
+        );
+
+        let found = lookup::<_, _, DefaultTokenizer>(&data, "id.[0]");
+        assert_eq!(
+            found,
+            Err(Error::ExpectedIndex {
+                segment: String::from("id")
+            })
+        );
+
+        let found = lookup::<_, _, DefaultTokenizer>(&data, "[x]");
+        assert_eq!(
+            found,
+            Err(Error::ExpectedIndex {
+                segment: String::from("[x]")
+            })
+        );
+    }
+
+    #[test]
+    fn test_query_many() {
+        let data = dict! {
+            "id" => 12,
+            "child" => array![1, 2]
+        };
+
+        let found = data.query_many::<DefaultTokenizer>(&["id", "child.[1]"]);
+        assert_eq!(found, Ok(vec![Value::integer(12), Value::integer(2)]));
+
+        let found = data.query_many::<DefaultTokenizer>(&["id", "missing", "child.[1]"]);
+        assert_eq!(found, Err(Error::key_not_exist("missing")));
+    }
+
+    #[test]
+    fn test_query_all_or_errors() {
+        let data = dict! {
+            "id" => 12,
+            "child" => array![1, 2]
+        };
+
+        let found = data.query_all_or_errors::<DefaultTokenizer>(&["id", "child.[0]"]);
+        assert_eq!(found, Ok(vec![Value::integer(12), Value::integer(1)]));
+
+        let found = data.query_all_or_errors::<DefaultTokenizer>(&["id", "missing", "child.[1]"]);
+        assert_eq!(found, Err(vec![(1, Error::key_not_exist("missing"))]));
+    }
+
+    #[test]
+    fn test_default_tokenizer_index_parse_brackets() {
+        let parse_error = |key: &str| Err(IndexError::ParseError(String::from(key)));
+
+        let cases = vec![
+            ("[", parse_error("[")),
+            ("]", parse_error("]")),
+            ("[]", parse_error("[]")),
+            ("[[1]]", parse_error("[[1]]")),
+            ("[1]]", parse_error("[1]]")),
+            ("[[1]", parse_error("[[1]")),
+            ("[0", parse_error("[0")),
+            (
+                "[ 1 ]",
+                Err(IndexError::IntError {
+                    input: String::from(" 1 "),
+                    source: " 1 ".parse::<usize>().unwrap_err(),
+                }),
+            ),
+            ("[+1]", parse_error("[+1]")),
+            ("[01]", Ok(1)),
+            ("[1]", Ok(1)),
+        ];
+
+        for (key, expected) in cases {
+            assert_eq!(DefaultTokenizer::index_parse(key), expected, "{}", key);
+        }
+
+        // a leading `+` is a key rather than an index
+        assert_eq!(SlashTokenizer::index_parse("+1"), parse_error("+1"));
+        assert_eq!(SlashTokenizer::classify("+1"), Segment::Key);
+        assert_eq!(
+            DefaultTokenizer::step_parse("[-+1]"),
+            Err(IndexError::ParseError(String::from("[-+1]")))
+        );
+    }
+
+    #[test]
+    fn test_depth() {
+        assert_eq!(Value::integer(1).depth(), 0);
+        assert_eq!(array![].depth(), 0);
+        assert_eq!(Value::dict().depth(), 0);
+        assert_eq!(array![1, 2].depth(), 1);
+        assert_eq!(array![1, array![dict! { "id" => 1 }]].depth(), 3);
+
+        // same as [2] sample in benches
+        let sample = dict! {
+            "id" => 12,
+            "child" => dict! {
+                "id" => 20,
+                "child" => dict! {
+                    "child" => dict! {
+                        "id" => 20,
+                        "child" => dict! {
+                            "child" => dict! {
+                                "id" => 20,
+                                "child" => dict! {
+                                    "id" => 20,
+                                    "child" => dict! {
+                                        "child" => dict! {
+                                            "id" => 20,
+                                            "child" => dict! {
+                                                "child" => dict! {
+                                                    "id" => 20,
+                                                    "child" => dict! {
+                                                        "id" => 20,
+                                                        "child" => dict! {
+                                                            "child" => dict! {
+                                                                "id" => 20,
+                                                                "child" => dict! {
+                                                                    "child" => dict! {
+                                                                        "id" => 20,
+                                                                        "child" => 1,
+                                                                    },
+                                                                },
+                                                            },
+                                                        },
+                                                    },
+                                                },
+                                            },
+                                        },
+                                    },
+                                },
+                            },
+                        },
+                    },
+                },
+            },
+        };
+
+        assert_eq!(sample.depth(), 16);
+        assert!(sample.depth_exceeds(15));
+        assert!(!sample.depth_exceeds(16));
+        assert!(!Value::integer(1).depth_exceeds(0));
+
+        // nested deep enough to overflow the stack when being recursed into
+        let mut nested = array![];
+        for _ in 0..100_000 {
+            nested = array![nested];
+        }
+
+        assert_eq!(nested.depth(), 100_000);
+        assert!(nested.depth_exceeds(64));
+
+        // dropping it recursively would overflow as well
+        std::mem::forget(nested);
+    }
+
+    #[test]
+    fn test_lenient_tokenizer_index_whitespace() {
+        let data = array![array!["a", "b"]];
+
+        for query in &["[0].[1]", "[ 0 ].[ 1 ]"] {
+            let found = lookup::<_, _, LenientTokenizer>(&data, *query);
+            assert_eq!(found, Ok(Value::string("b")));
+        }
+
+        let found = lookup::<_, _, DefaultTokenizer>(&data, "[0].[1]");
+        assert_eq!(found, Ok(Value::string("b")));
+
+        assert!(lookup::<_, _, DefaultTokenizer>(&data, "[ 0 ].[ 1 ]").is_err());
+        assert!(DefaultTokenizer::index_parse("[ 1 ]").is_err());
+        assert_eq!(LenientTokenizer::index_parse("[ 1 ]"), Ok(1));
+    }
+
+    #[test]
+    fn test_query_from_str() {
+        let data = array![dict! {
+            "id" => 12
+        }];
+
+        let query: Result<Query, _> = "[0].id".parse();
+        assert_eq!(query, Query::try_from("[0].id"));
+
+        let found = query.and_then(|q| lookup_steps(&data, q.steps()));
+        assert_eq!(found, Ok(Value::integer(12)));
+
+        let expected = Err(Error::IndexError(IndexError::IntError {
+            input: String::from("x"),
+            source: "x".parse::<usize>().unwrap_err(),
+        }));
+
+        assert_eq!("[x].id".parse::<Query>(), expected);
+        assert_eq!(Query::try_from("[x].id"), expected);
+    }
+
+    #[test]
+    fn test_default_tokenizer_index_not_an_integer() {
+        for index in &["1.0", "1e3"] {
+            let found = DefaultTokenizer::index_parse(&format!("[{}]", index));
+            assert_eq!(found, Err(IndexError::NotAnInteger(String::from(*index))));
+        }
+
+        assert_eq!(
+            DefaultTokenizer::index_parse("[1.0]")
+                .unwrap_err()
+                .to_string(),
+            "array index must be a whole number, got \"1.0\""
+        );
+
+        assert_eq!(DefaultTokenizer::index_parse("[100]"), Ok(100));
+        assert!(matches!(
+            DefaultTokenizer::index_parse("[one]"),
+            Err(IndexError::IntError { .. })
+        ));
+    }
+
+    #[test]
+    fn test_lookup_values() {
+        let data = dict! {
+            "config" => dict! {
+                "port" => 8080,
+                "host" => "localhost"
+            },
+            "list" => array![1, 2]
+        };
+
+        let found = lookup::<_, _, DefaultTokenizer>(&data, "config.@values");
+        assert_eq!(found, Ok(array!["localhost", 8080]));
+
+        let found = lookup::<_, _, DefaultTokenizer>(&data, "config.@values.[1]");
+        assert_eq!(found, Ok(Value::integer(8080)));
+
+        let found = lookup::<_, _, SlashTokenizer>(&data, "/config/@values/0");
+        assert_eq!(found, Ok(Value::string("localhost")));
+
+        let found = lookup::<_, _, DefaultTokenizer>(&data, "list.@values");
+        assert_eq!(
+            found,
+            Err(Error::TypeError(
+                String::from("@values"),
+                QueryKind::Dictionary,
+                QueryKind::Array
+            ))
+        );
+    }
+
+    #[test]
+    fn test_tokenizer_index_overflow() {
+        let index = format!("{}0", usize::MAX);
+
+        let found = DefaultTokenizer::index_parse(&format!("[{}]", index));
+        assert_eq!(found, Err(IndexError::Overflow(index.clone())));
+
+        let found = SlashTokenizer::index_parse(&index);
+        assert_eq!(found, Err(IndexError::Overflow(index.clone())));
+
+        let found = lookup::<_, _, DefaultTokenizer>(&array![1], format!("[{}]", index));
+        assert_eq!(found, Err(Error::IndexError(IndexError::Overflow(index))));
+    }
+
+    #[test]
+    #[cfg(target_pointer_width = "64")]
+    fn test_tokenizer_index_overflow_64() {
+        let found = DefaultTokenizer::index_parse("[99999999999999999999]");
+        assert_eq!(
+            found,
+            Err(IndexError::Overflow(String::from("99999999999999999999")))
+        );
+
+        assert_eq!(
+            found.unwrap_err().to_string(),
+            "array index \"99999999999999999999\" is too large, maximum is 18446744073709551615"
+        );
+    }
+
+    #[test]
+    fn test_sibling() {
+        let data = array![dict! {
+            "name" => "zerosign",
+            "email" => "r1nlx0@gmail.com",
+            "tags" => array!["a", "b"]
+        }];
+
+        let found = data.sibling::<DefaultTokenizer>("[0].name", "email");
+        assert_eq!(found, Ok(Value::string("r1nlx0@gmail.com")));
+
+        let found = data.sibling::<DefaultTokenizer>("[0].tags.[0]", "[1]");
+        assert_eq!(found, Ok(Value::string("b")));
+
+        let found = data.sibling::<SlashTokenizer>("/0/name", "/email");
+        assert_eq!(found, Ok(Value::string("r1nlx0@gmail.com")));
+
+        let found = data.sibling::<DefaultTokenizer>("[0]", "[1]");
+        assert_eq!(found, Err(Error::NoParent));
+
+        // numeric dictionary keys
+        let data = dict! { "80" => dict! { "x" => 1, "y" => 2 } };
+
+        let found = data.sibling::<SlashTokenizer>("/80/x", "/y");
+        assert_eq!(found, Ok(Value::from(2)));
+    }
+
+    #[test]
+    fn test_error_display_and_source() {
+        use std::error::Error as StdError;
+
+        let e =
+            lookup::<_, _, DefaultTokenizer>(&array![1], "[99999999999999999999999]").unwrap_err();
+        assert!(e.source().is_some());
+
+        let e = lookup::<_, _, DefaultTokenizer>(&array![1], "[x]").unwrap_err();
+        assert_eq!(e.to_string(), "expected an array index, got \"[x]\"");
+
+        let e = Error::IndexError(IndexError::IntError {
+            input: String::from("x"),
+            source: "x".parse::<usize>().unwrap_err(),
+        });
+        let source = e.source().and_then(StdError::source);
+        assert_eq!(
+            source.map(ToString::to_string),
+            Some(String::from("invalid digit found in string"))
+        );
+
+        let e = lookup::<_, _, DefaultTokenizer>(&dict! { "id" => 1 }, "name").unwrap_err();
+        assert_eq!(e.to_string(), "key \"name\" doesn't exist");
+
+        let e = Error::TypeError(String::from("id"), QueryKind::Array, QueryKind::Dictionary);
+        assert_eq!(e.to_string(), "expected array at \"id\", found dictionary");
+    }
+
+    #[test]
+    fn test_kind_at() {
+        let data = array![dict! { "id" => 1, "list" => array![] }];
+
+        assert_eq!(
+            data.kind_at::<DefaultTokenizer>("[0]"),
+            Ok(Some(QueryKind::Dictionary))
+        );
+        assert_eq!(
+            data.kind_at::<DefaultTokenizer>("[0].list"),
+            Ok(Some(QueryKind::Array))
+        );
+        assert_eq!(data.kind_at::<DefaultTokenizer>("[0].id"), Ok(None));
+    }
+
+    #[test]
+    fn test_complete() {
+        let data = array![dict! {
+            "name" => "zerosign",
+            "nationality" => "id",
+            "age" => 17,
+            "tags" => array!["a", "b"]
+        }];
+
+        let found = data.complete::<DefaultTokenizer>("[0].na");
+        assert_eq!(found, vec!["name", "nationality"]);
+
+        let found = data.complete::<DefaultTokenizer>("[0].");
+        assert_eq!(found, vec!["age", "name", "nationality", "tags"]);
+
+        let found = data.complete::<DefaultTokenizer>("[0].tags.[");
+        assert_eq!(found, vec!["[0]", "[1]"]);
+
+        let found = data.complete::<DefaultTokenizer>("");
+        assert_eq!(found, vec!["[0]"]);
+
+        let found = data.complete::<SlashTokenizer>("/0/na");
+        assert_eq!(found, vec!["name", "nationality"]);
+
+        let found = data.complete::<DefaultTokenizer>("[1].na");
+        assert!(found.is_empty());
+    }
+
+    #[test]
+    fn test_lookup_cow() {
+        let data = array![dict! { "id" => 12 }];
+
+        let found = lookup_cow::<_, DefaultTokenizer>(&data, Cow::Borrowed("[0].id"));
+        assert_eq!(found, Ok(Value::integer(12)));
+
+        let found = lookup_cow::<_, SlashTokenizer>(&data, Cow::Owned(String::from("/0/id")));
+        assert_eq!(found, Ok(Value::integer(12)));
+    }
+
+    #[test]
+    fn test_lookup_bytes() {
+        let data = array![dict! { "id" => 12, "név" => "alice" }];
+
+        let found = lookup_bytes::<_, DefaultTokenizer>(&data, b"[0].id");
+        assert_eq!(found, Ok(Value::integer(12)));
+
+        let found = lookup_bytes::<_, DefaultTokenizer>(&data, "[0].név".as_bytes());
+        assert_eq!(found, Ok(Value::string("alice")));
+
+        let found = lookup_bytes::<_, DefaultTokenizer>(&data, b"[0].n\xff\xfe");
+        assert!(matches!(found, Err(Error::InvalidQueryEncoding(ref e)) if e.valid_up_to() == 5));
+
+        let e = found.unwrap_err();
+        assert!(e.is_query_error());
+        assert!(std::error::Error::source(&e).is_some());
+        assert_eq!(
+            e.to_string(),
+            "query isn't valid UTF-8: invalid utf-8 sequence of 1 bytes from index 5"
+        );
+    }
+
+    #[test]
+    fn test_query_path() {
+        let data = array![dict! {
+            "id" => 12,
+            "child" => dict! { "id" => 20 }
+        }];
+
+        let found = data.query_path(&["0", "child", "id"]);
+        assert_eq!(found, Ok(Value::integer(20)));
+        assert_eq!(found, lookup::<_, _, SlashTokenizer>(&data, "/0/child/id"));
+
+        let found = data.query_path(&["child"]);
+        assert_eq!(
+            found,
+            Err(Error::ExpectedIndex {
+                segment: String::from("child")
+            })
+        );
+    }
+
+    #[test]
+    fn test_query_builder() {
+        let data = dict! {
+            "users" => array![
+                dict! { "name" => "zerosign", "a.b" => 1 }
+            ]
+        };
+
+        let query = QueryBuilder::new()
+            .key("users")
+            .index(0)
+            .key("name")
+            .build();
+        let found = lookup_steps(&data, query.steps());
+        assert_eq!(found, Ok(Value::string("zerosign")));
+
+        let query = QueryBuilder::new().key("users").index(0).key("a.b").build();
+        let found = lookup_steps(&data, query.steps());
+        assert_eq!(found, Ok(Value::integer(1)));
+
+        let query = QueryBuilder::new()
+            .key("users")
+            .wildcard()
+            .key("name")
+            .build();
+        let found = lookup_steps(&data, query.steps());
+        assert_eq!(found, Err(Error::UnexpectedWildcard));
+    }
+
+    #[test]
+    fn test_into_query() {
+        let data = array![
+            dict! {
+                "id" => 12,
+                "child" => array![1, 2, 3]
+            },
+            "test"
+        ];
+
+        let found = data.clone().into_query::<DefaultTokenizer>("[0].child.[0]");
+        assert_eq!(found, Ok(Value::integer(1)));
+
+        let found = data.clone().into_query::<SlashTokenizer>("/0/child/2");
+        assert_eq!(found, Ok(Value::integer(3)));
+
+        let found = data.clone().into_query::<DefaultTokenizer>("[0].missing");
+        assert_eq!(found, Err(Error::key_not_exist("missing")));
+
+        let found = data.clone().into_query::<DefaultTokenizer>("[0].@self.id");
+        assert_eq!(found, Ok(Value::integer(12)));
+
+        let found = data.clone().into_query::<DefaultTokenizer>("[0].@self");
+        assert_eq!(found, lookup::<_, _, DefaultTokenizer>(&data, "[0].@self"));
+
+        let found = data.into_query::<DefaultTokenizer>("[2]");
+        assert_eq!(found, Err(Error::IndexNotExist { index: 2, len: 2 }));
+
+        let sample = dict! { "id" => 1 };
+        let found = sample.clone().into_query::<DefaultTokenizer>(SELF_SEGMENT);
+        assert_eq!(found, Ok(sample));
+
+        let sample = [array![1], array![2]];
+        let found = sample.into_query::<DefaultTokenizer>("[1].[0]");
+        assert_eq!(found, Ok(Value::integer(2)));
+    }
+
+    #[test]
+    fn test_query_from_iter_into_iter() {
+        let steps = vec![
+            Step::Key("child".into()),
+            Step::Index(1),
+            Step::Key("id".into()),
+        ];
+
+        let query: Query = steps.clone().into_iter().collect();
+        assert_eq!(query.steps(), &steps[..]);
+        assert_eq!((&query).into_iter().cloned().collect::<Vec<_>>(), steps);
+        assert_eq!(query.into_iter().collect::<Vec<_>>(), steps);
+
+        let data = dict! { "child" => array![dict! { "id" => 1 }, dict! { "id" => 2 }] };
+
+        let parent: Query = steps.into_iter().take(2).collect();
+        let found = lookup_steps(&data, parent.steps());
+        assert_eq!(found, Ok(dict! { "id" => 2 }));
+    }
+
+    #[test]
+    fn test_one_based_tokenizer() {
+        let data = array![array!["a", "b"]];
+
+        let found = lookup::<_, _, OneBasedTokenizer<DefaultTokenizer>>(&data, "[1].[2]");
+        assert_eq!(found, Ok(Value::string("b")));
+
+        let found = lookup::<_, _, OneBasedTokenizer<SlashTokenizer>>(&data, "/1/1");
+        assert_eq!(found, Ok(Value::string("a")));
+
+        let found = lookup::<_, _, OneBasedTokenizer<DefaultTokenizer>>(&data, "[0]");
+        assert_eq!(
+            found,
+            Err(Error::IndexError(IndexError::Underflow(String::from(
+                "[0]"
+            ))))
+        );
+        assert_eq!(
+            found.unwrap_err().to_string(),
+            "array index \"[0]\" must be >= 1"
+        );
+
+        let query = Query::parse::<OneBasedTokenizer<DefaultTokenizer>>("[1].[2]");
+        assert_eq!(
+            query.as_ref().map(Query::steps),
+            Ok(&[Step::Index(0), Step::Index(1)][..])
+        );
+        assert_eq!(
+            query.map(|q| OneBasedTokenizer::<DefaultTokenizer>::render(q.steps())),
+            Ok(String::from("[1].[2]"))
+        );
+    }
+
+    #[test]
+    fn test_query_parent_last() {
+        let query = Query::parse::<DefaultTokenizer>("[0].child.id").unwrap();
+
+        let parent = query.parent();
+        assert_eq!(
+            parent.as_ref().map(|q| DefaultTokenizer::render(q.steps())),
+            Some(String::from("[0].child"))
+        );
+        assert_eq!(query.last(), Some(&Step::Key("id".into())));
+
+        let query = Query::parse::<DefaultTokenizer>("[0]").unwrap();
+        assert_eq!(query.parent(), None);
+        assert_eq!(query.last(), Some(&Step::Index(0)));
+
+        let query = QueryBuilder::new().build();
+        assert_eq!(query.parent(), None);
+        assert_eq!(query.last(), None);
+    }
+
+    #[test]
+    fn test_path_display() {
+        use std::fmt::Write;
+
+        let steps = [Step::Index(0), Step::Key("child".into()), Step::Index(12)];
+
+        let mut buffer = String::with_capacity(32);
+
+        write!(buffer, "{}", PathDisplay::<DefaultTokenizer>::new(&steps)).unwrap();
+        assert_eq!(buffer, "[0].child.[12]");
+
+        buffer.clear();
+
+        write!(buffer, "{}", PathDisplay::<SlashTokenizer>::new(&steps)).unwrap();
+        assert_eq!(buffer, "/0/child/12");
+        assert_eq!(buffer.capacity(), 32);
+    }
+
+    #[test]
+    fn test_lookup_trace() {
+        init_logger();
+
+        let sample = dict! {
+            "trace_outer" => dict! {
+                "trace_inner" => "found"
+            }
+        };
+
+        let found = lookup::<_, _, DefaultTokenizer>(&sample, "trace_outer.trace_inner");
+        assert_eq!(found, Ok(Value::string("found")));
+
+        let found = lookup::<_, _, DefaultTokenizer>(&sample, "trace_outer.trace_missing");
+        assert!(found.is_err());
+
+        let captured = CAPTURED.lock().unwrap();
+
+        assert!(captured
+            .iter()
+            .any(|line| line.contains("\"trace_inner\"") && line.contains("hit")));
+        assert!(captured
+            .iter()
+            .any(|line| line.contains("\"trace_missing\"") && line.contains("miss")));
+    }
+
+    #[test]
+    fn test_lookup_smart_pointer() {
+        let sample = dict! {
+            "users" => array![dict! { "name" => "zerosign" }]
+        };
+
+        let expected = Ok(Value::string("zerosign"));
+
+        let boxed = Box::new(sample.clone());
+        assert_eq!(
+            lookup::<_, _, DefaultTokenizer>(&boxed, "users.[0].name"),
+            expected
+        );
+
+        let shared = Rc::new(sample.clone());
+        assert_eq!(
+            lookup::<_, _, DefaultTokenizer>(&shared, "users.[0].name"),
+            expected
+        );
+        assert_eq!(
+            shared.kind_at::<DefaultTokenizer>("users"),
+            Ok(Some(QueryKind::Array))
+        );
+
+        let atomic = Arc::new(sample);
+        assert_eq!(
+            lookup_steps(
+                &atomic,
+                &[
+                    Step::Key("users".into()),
+                    Step::Index(0),
+                    Step::Key("name".into())
+                ]
+            ),
+            expected
+        );
+    }
+
+    #[derive(Debug, Clone, PartialEq)]
+    enum Doc {
+        Leaf(i64),
+        List(Vec<Doc>),
+        Object(Map<Doc>),
+    }
+
+    impl AsDict for Doc {
+        fn as_dict(&self) -> Option<&Map<Doc>> {
+            match self {
+                Doc::Object(d) => Some(d),
+                _ => None,
+            }
+        }
+    }
+
+    impl AsArray for Doc {
+        fn as_array(&self) -> Option<&[Doc]> {
+            match self {
+                Doc::List(d) => Some(d),
+                _ => None,
+            }
+        }
+    }
+
+    impl From<Vec<Doc>> for Doc {
+        fn from(d: Vec<Doc>) -> Self {
+            Doc::List(d)
+        }
+    }
+
+    crate::queryable_impl!(Doc);
+
+    #[test]
+    fn test_queryable_impl_accessors() {
+        let mut object = Map::new();
+        object.insert(String::from("b"), Doc::Leaf(2));
+        object.insert(String::from("a"), Doc::List(vec![Doc::Leaf(1)]));
+
+        let doc = Doc::List(vec![Doc::Object(object)]);
+
+        assert_eq!(doc.query_kind(), Some(QueryKind::Array));
+        assert_eq!(
+            lookup::<_, _, DefaultTokenizer>(&doc, "[0].a.[0]"),
+            Ok(Doc::Leaf(1))
+        );
+        assert_eq!(
+            lookup::<_, _, DefaultTokenizer>(&doc, "[0].@values.[1]"),
+            Ok(Doc::Leaf(2))
+        );
+        assert_eq!(
+            lookup::<_, _, DefaultTokenizer>(&doc, "[0].c"),
+            Err(Error::key_not_exist("c"))
+        );
+        assert_eq!(
+            lookup::<_, _, DefaultTokenizer>(&doc, "[0].b.[0]"),
+            Err(Error::NotTraversable {
+                path: String::from("[0]"),
+                kind_hint: "scalar"
+            })
+        );
+        assert_eq!(doc.depth(), 3);
+    }
+
+    #[test]
+    fn test_expect_kind() {
+        let sample = array![dict! { "id" => 1 }];
+
+        assert_eq!(
+            sample.expect_kind::<DefaultTokenizer>("[0]", QueryKind::Dictionary),
+            Ok(dict! { "id" => 1 })
+        );
+        assert_eq!(
+            sample.expect_kind::<DefaultTokenizer>("[0]", QueryKind::Array),
+            Err(Error::TypeError(
+                String::from("[0]"),
+                QueryKind::Array,
+                QueryKind::Dictionary
+            ))
+        );
+
+        let found = sample.expect_kind::<DefaultTokenizer>("[0].id", QueryKind::Array);
+
+        assert_eq!(
+            found,
+            Err(Error::ScalarTypeError(
+                String::from("[0].id"),
+                QueryKind::Array
+            ))
+        );
+        assert_eq!(
+            found.unwrap_err().to_string(),
+            "expected array at \"[0].id\", found scalar"
+        );
+    }
+
+    #[test]
+    fn test_entry() {
+        let mut sample = dict! {};
+
+        *sample.entry::<DefaultTokenizer>("a.b.c").unwrap() = Value::from(1);
+
+        assert_eq!(sample, dict! { "a" => dict! { "b" => dict! { "c" => 1 } } });
+
+        *sample.entry::<DefaultTokenizer>("a.d").unwrap() = array![2];
+        *sample.entry::<DefaultTokenizer>("a.d.[0]").unwrap() = Value::from(3);
+
+        assert_eq!(
+            lookup::<_, _, DefaultTokenizer>(&sample, "a.d.[0]"),
+            Ok(Value::from(3))
+        );
+        assert_eq!(
+            sample.entry::<DefaultTokenizer>("a.d.[1]"),
+            Err(Error::IndexNotExist { index: 1, len: 1 })
+        );
+        assert_eq!(
+            sample.entry::<DefaultTokenizer>("a.b.c.e"),
+            Err(Error::NotTraversable {
+                path: String::from("e"),
+                kind_hint: "scalar"
+            })
+        );
+    }
+
+    #[test]
+    fn test_push() {
+        let mut sample = dict! {
+            "users" => dict! {
+                "tags" => array!["a"],
+                "name" => "zerosign"
+            }
+        };
+
+        assert_eq!(sample.len_at::<DefaultTokenizer>("users.tags"), Ok(1));
+
+        assert_eq!(
+            sample.push::<DefaultTokenizer>("users.tags", Value::from("b")),
+            Ok(())
+        );
+        assert_eq!(sample.len_at::<DefaultTokenizer>("users.tags"), Ok(2));
+        assert_eq!(
+            lookup::<_, _, DefaultTokenizer>(&sample, "users.tags.[1]"),
+            Ok(Value::from("b"))
+        );
+
+        assert_eq!(
+            sample.push::<DefaultTokenizer>("users", Value::from("c")),
+            Err(Error::TypeError(
+                String::from("users"),
+                QueryKind::Array,
+                QueryKind::Dictionary
+            ))
+        );
+        assert_eq!(
+            sample.push::<DefaultTokenizer>("users.name", Value::from("c")),
+            Err(Error::ScalarTypeError(
+                String::from("users.name"),
+                QueryKind::Array
+            ))
+        );
+        assert_eq!(
+            sample.push::<DefaultTokenizer>("users.missing", Value::from("c")),
+            Err(Error::key_not_exist("missing"))
+        );
+    }
+
+    #[test]
+    fn test_error_classification() {
+        let sample = dict! { "users" => array![dict! { "id" => 1 }] };
+
+        let malformed = lookup::<_, _, DefaultTokenizer>(&sample, "users.[x]").unwrap_err();
+        assert!(malformed.is_query_error());
+        assert!(!malformed.is_data_error());
+
+        let missing = lookup::<_, _, DefaultTokenizer>(&sample, "users.[0].name").unwrap_err();
+        assert_eq!(missing, Error::key_not_exist("name"));
+        assert!(missing.is_data_error());
+        assert!(!missing.is_query_error());
+
+        let mismatched = sample
+            .expect_kind::<DefaultTokenizer>("users", QueryKind::Dictionary)
+            .unwrap_err();
+        assert!(mismatched.is_data_error());
+    }
+
+    #[test]
+    fn test_count_matches() {
+        let sample = array![
+            dict! { "id" => 1, "active" => true },
+            dict! { "id" => 2 },
+            dict! { "id" => 3, "active" => false }
+        ];
+
+        assert_eq!(sample.count_matches::<DefaultTokenizer>("[*].id"), Ok(3));
+        assert_eq!(sample.count_matches::<DefaultTokenizer>("*.active"), Ok(2));
+        assert_eq!(
+            sample.count_matches::<DefaultTokenizer>("[*].missing"),
+            Ok(0)
+        );
+        assert_eq!(sample.count_matches::<DefaultTokenizer>("[0].id"), Ok(1));
+        assert_eq!(sample.count_matches::<DefaultTokenizer>("[3].id"), Ok(0));
+        assert_eq!(sample.count_matches::<SlashTokenizer>("/*/id"), Ok(3));
+        assert!(sample.count_matches::<DefaultTokenizer>("[x].id").is_err());
+
+        // numeric dictionary keys
+        let sample = dict! { "ports" => dict! { "80" => "http" } };
+
+        assert_eq!(sample.count_matches::<SlashTokenizer>("/ports/80"), Ok(1));
+        assert_eq!(sample.count_matches::<SlashTokenizer>("/ports/*"), Ok(1));
+        assert_eq!(sample.count_matches::<SlashTokenizer>("/ports/443"), Ok(0));
+        assert_eq!(
+            sample.count_matches::<SlashTokenizer>("/ports/80"),
+            lookup::<_, _, SlashTokenizer>(&sample, "/ports/80").map(|_| 1)
+        );
+    }
+
+    #[test]
+    fn test_lookup_root() {
+        let sample = dict! { "users" => array![dict! { "id" => 1 }] };
+
+        assert_eq!(
+            lookup::<_, _, DefaultTokenizer>(&sample, ""),
+            Ok(sample.clone())
+        );
+        assert_eq!(
+            lookup::<_, _, DefaultTokenizer>(&sample, "  "),
+            Ok(sample.clone())
+        );
+        assert_eq!(
+            lookup::<_, _, SlashTokenizer>(&sample, ""),
+            Ok(sample.clone())
+        );
+        assert_eq!(
+            lookup::<_, _, SlashTokenizer>(&sample, "/"),
+            Ok(sample.clone())
+        );
+        assert_eq!(
+            lookup::<_, _, SlashTokenizer>(&sample, "/users/0/id"),
+            Ok(Value::from(1))
+        );
+        assert_eq!(
+            lookup::<_, _, DefaultTokenizer>(&Box::new(sample.clone()), ""),
+            Ok(sample)
+        );
+
+        // trailing separator isn't a root query
+        let sample = array![1];
+        assert!(lookup::<_, _, SlashTokenizer>(&sample, "/0/").is_err());
+        assert!(lookup::<_, _, DefaultTokenizer>(&[Value::from(1)], "").is_err());
+    }
+
+    #[test]
+    fn test_join_paths() {
+        assert_eq!(DefaultTokenizer::join_paths("a.b", "c.d"), "a.b.c.d");
+        assert_eq!(DefaultTokenizer::join_paths("a.b.", "c.d"), "a.b.c.d");
+        assert_eq!(DefaultTokenizer::join_paths("a.b", ".c.d"), "a.b.c.d");
+        assert_eq!(DefaultTokenizer::join_paths("a.b.", ".c.d"), "a.b.c.d");
+        assert_eq!(DefaultTokenizer::join_paths("a.b", ""), "a.b");
+        assert_eq!(
+            DefaultTokenizer::join_paths("users.[3]", "[0]"),
+            "users.[3].[0]"
+        );
+
+        assert_eq!(SlashTokenizer::join_paths("/a/b", "/c/d"), "/a/b/c/d");
+        assert_eq!(SlashTokenizer::join_paths("/a/b/", "/c/d"), "/a/b/c/d");
+        assert_eq!(SlashTokenizer::join_paths("/a/b", "c/d"), "/a/b/c/d");
+        assert_eq!(SlashTokenizer::join_paths("", "c/d"), "/c/d");
+        assert_eq!(SlashTokenizer::join_paths("/a/b", "/"), "/a/b");
+
+        let sample = dict! { "a" => dict! { "b" => dict! { "c" => 1 } } };
+        let path = DefaultTokenizer::join_paths("a.", ".b.c");
+
+        assert_eq!(
+            lookup::<_, _, DefaultTokenizer>(&sample, path),
+            Ok(Value::from(1))
+        );
+    }
+
+    #[test]
+    fn test_index_scalar() {
+        let sample = dict! { "name" => "zérosign", "id" => 1 };
+
+        assert_eq!(
+            lookup::<_, _, DefaultTokenizer>(&sample, "name.[0]"),
+            Ok(Value::from("z"))
+        );
+        assert_eq!(
+            lookup::<_, _, SlashTokenizer>(&sample, "/name/1"),
+            Ok(Value::from("é"))
+        );
+        assert_eq!(
+            lookup::<_, _, DefaultTokenizer>(&sample, "name.[8]"),
+            Err(Error::IndexNotExist { index: 8, len: 8 })
+        );
+        assert_eq!(
+            lookup::<_, _, DefaultTokenizer>(&sample, "id.[0]"),
+            Err(Error::NotTraversable {
+                path: String::from("[0]"),
+                kind_hint: "scalar"
+            })
+        );
+        assert_eq!(
+            lookup::<_, _, DefaultTokenizer>(&sample, "name.first"),
+            Err(Error::NotTraversable {
+                path: String::from("first"),
+                kind_hint: "scalar"
+            })
+        );
+    }
+
+    #[test]
+    fn test_lookup_self_segment() {
+        let sample = array![dict! { "id" => 1 }];
+
+        assert_eq!(
+            lookup::<_, _, DefaultTokenizer>(&sample, SELF_SEGMENT),
+            Ok(sample.clone())
+        );
+        assert_eq!(
+            lookup::<_, _, DefaultTokenizer>(&sample, "[0].id.@self"),
+            Ok(Value::from(1))
+        );
+        assert_eq!(
+            lookup::<_, _, DefaultTokenizer>(&sample, "@self.[0].@self.id"),
+            Ok(Value::from(1))
+        );
+        assert_eq!(
+            lookup::<_, _, SlashTokenizer>(&sample, "/0/id/@self"),
+            Ok(Value::from(1))
+        );
+    }
+
+    #[test]
+    fn test_lookup_first() {
+        let sample = dict! { "old_field" => 1, "list" => array![2] };
+
+        assert_eq!(
+            lookup_first::<_, DefaultTokenizer>(&sample, &["new_field", "old_field"]),
+            Ok(Value::from(1))
+        );
+        assert_eq!(
+            lookup_first::<_, DefaultTokenizer>(&sample, &["list.[1]", "list.[0]"]),
+            Ok(Value::from(2))
+        );
+        assert_eq!(
+            lookup_first::<_, DefaultTokenizer>(&sample, &["new_field", "other_field"]),
+            Err(Error::key_not_exist("other_field"))
+        );
+
+        // type error short-circuits
+        assert!(matches!(
+            lookup_first::<_, DefaultTokenizer>(&sample, &["list.@values", "old_field"]),
+            Err(Error::TypeError(..))
+        ));
+        assert_eq!(
+            lookup_first::<_, DefaultTokenizer>(&sample, &["list.id", "old_field"]),
+            Err(Error::ExpectedIndex {
+                segment: String::from("id")
+            })
+        );
+        assert!(matches!(
+            lookup_first::<_, DefaultTokenizer>(&sample, &["list.[0].id", "old_field"]),
+            Err(Error::NotTraversable { .. })
+        ));
+    }
+
+    // counts scalars and records the deepest path, `None` marks a container
+    // that has been entered but whose children aren't visited yet
+    #[derive(Default)]
+    struct DepthVisitor {
+        scalars: usize,
+        current: Vec<Option<Step<'static>>>,
+        deepest: Vec<Step<'static>>,
+    }
+
+    impl QueryVisitor<Value> for DepthVisitor {
+        fn visit_scalar(&mut self, _node: &Value) {
+            self.scalars += 1;
+
+            if self.current.len() > self.deepest.len() {
+                self.deepest = self.current.iter().flatten().cloned().collect();
+            }
+        }
+
+        fn enter_array(&mut self) {
+            self.current.push(None);
+        }
+
+        fn leave_array(&mut self) {
+            self.current.pop();
+        }
+
+        fn enter_dict(&mut self) {
+            self.current.push(None);
+        }
+
+        fn leave_dict(&mut self) {
+            self.current.pop();
+        }
+
+        fn visit_key(&mut self, key: &str) {
+            if let Some(last) = self.current.last_mut() {
+                *last = Some(Step::Key(Cow::Owned(String::from(key))));
+            }
+        }
+
+        fn visit_index(&mut self, idx: usize) {
+            if let Some(last) = self.current.last_mut() {
+                *last = Some(Step::Index(idx));
+            }
+        }
+    }
+
+    #[test]
+    fn test_accept_visitor() {
+        let sample = dict! {
+            "test" => dict! {
+                "data" => array!["test", 0],
+                "another" => dict! {
+                    "key" => dict! {
+                        "nested" => array![1.0, true]
+                    }
+                }
+            },
+            "flag" => false
+        };
+
+        let mut visitor = DepthVisitor::default();
+        sample.accept(&mut visitor);
+
+        assert_eq!(visitor.scalars, 5);
+        assert!(visitor.current.is_empty());
+        assert_eq!(
+            DefaultTokenizer::render(&visitor.deepest),
+            "test.another.key.nested.[0]"
+        );
+
+        let mut visitor = DepthVisitor::default();
+        Value::from("scalar").accept(&mut visitor);
+
+        assert_eq!(visitor.scalars, 1);
+        assert!(visitor.deepest.is_empty());
+    }
+
+    #[test]
+    fn test_delim_tokenizer() {
+        let sample = dict! {
+            "users" => array![dict! { "name" => "zerosign" }]
+        };
+
+        assert_eq!(
+            lookup::<_, _, DelimTokenizer<':'>>(&sample, "users:0:name"),
+            Ok(Value::from("zerosign"))
+        );
+        assert_eq!(
+            lookup::<_, _, DelimTokenizer<'|'>>(&sample, "users|0|name"),
+            Ok(Value::from("zerosign"))
+        );
+        assert_eq!(
+            lookup::<_, _, DelimTokenizer<'|'>>(&sample, "users|x"),
+            Err(Error::ExpectedIndex {
+                segment: String::from("x")
+            })
+        );
+        assert_eq!(
+            lookup::<_, _, DelimTokenizer<':'>>(&sample, "users::name"),
+            Err(Error::KeyError(KeyError::EmptyKey))
+        );
+
+        let steps = Query::parse::<DelimTokenizer<':'>>("users:*:name").unwrap();
+
+        assert_eq!(
+            steps.steps(),
+            &[
+                Step::Key("users".into()),
+                Step::Wildcard,
+                Step::Key("name".into())
+            ]
+        );
+        assert_eq!(DelimTokenizer::<'|'>::render(steps.steps()), "users|*|name");
+        assert_eq!(DelimTokenizer::<':'>::join_paths("a:", ":b"), "a:b");
+    }
+
+    #[test]
+    fn test_resolve_with_path() {
+        let sample = array![10, 20, 30];
+
+        assert_eq!(
+            sample.resolve_with_path::<DefaultTokenizer>("[-1]"),
+            Ok((Value::from(30), String::from("[2]")))
+        );
+        assert_eq!(
+            sample.resolve_with_path::<DefaultTokenizer>("[last]"),
+            Ok((Value::from(30), String::from("[2]")))
+        );
+        assert_eq!(
+            sample.resolve_with_path::<SlashTokenizer>("/-3"),
+            Ok((Value::from(10), String::from("/0")))
+        );
+        assert_eq!(
+            sample.resolve_with_path::<DefaultTokenizer>("[-4]"),
+            Err(Error::IndexNotExist { index: 4, len: 3 })
+        );
+        assert_eq!(
+            lookup::<_, _, DefaultTokenizer>(&sample, "[-2]"),
+            Ok(Value::from(20))
+        );
+
+        let sample = dict! {
+            "users" => array![
+                dict! { "name" => "a" },
+                dict! { "name" => "b", "email" => "b@example.com" }
+            ]
+        };
+
+        assert_eq!(
+            sample.resolve_with_path::<DefaultTokenizer>("users.[*].email"),
+            Ok((
+                Value::from("b@example.com"),
+                String::from("users.[1].email")
+            ))
+        );
+        assert_eq!(
+            sample.resolve_with_path::<DefaultTokenizer>("users.[last].name"),
+            Ok((Value::from("b"), String::from("users.[1].name")))
+        );
+        assert_eq!(
+            sample.count_matches::<DefaultTokenizer>("users.[-1].email"),
+            Ok(1)
+        );
+
+        // numeric dictionary keys
+        let sample = dict! { "ports" => dict! { "80" => "http" } };
+
+        assert_eq!(
+            sample.resolve_with_path::<SlashTokenizer>("/ports/80"),
+            Ok((Value::from("http"), String::from("/ports/80")))
+        );
+        assert_eq!(
+            sample
+                .resolve_with_path::<SlashTokenizer>("/ports/443")
+                .map(|(found, _)| found),
+            lookup::<_, _, SlashTokenizer>(&sample, "/ports/443")
+        );
+    }
+
+    #[test]
+    fn test_is_scalar_at() {
+        let sample = dict! { "users" => array![dict! { "id" => 1 }] };
+
+        assert_eq!(
+            sample.is_scalar_at::<DefaultTokenizer>("users.[0].id"),
+            Ok(true)
+        );
+        assert_eq!(
+            sample.is_scalar_at::<DefaultTokenizer>("users.[0]"),
+            Ok(false)
+        );
+        assert_eq!(sample.is_scalar_at::<DefaultTokenizer>("users"), Ok(false));
+        assert_eq!(
+            sample.is_scalar_at::<DefaultTokenizer>("missing"),
+            Err(Error::key_not_exist("missing"))
+        );
+    }
+
+    #[test]
+    fn test_default_tokenizer_index_length() {
+        let parse_error = |key: &str| Err(IndexError::ParseError(String::from(key)));
+
+        assert_eq!(DefaultTokenizer::index_parse("[]"), parse_error("[]"));
+        assert_eq!(DefaultTokenizer::index_parse("[5]"), Ok(5));
+        // multibyte digits, `[٢]` is 4 bytes long
+        assert_eq!(DefaultTokenizer::index_parse("[٢]"), parse_error("[٢]"));
+        assert_eq!(DefaultTokenizer::index_parse("[1٢]"), parse_error("[1٢]"));
+        assert_eq!(LenientTokenizer::index_parse("[ ٢ ]"), parse_error("[ ٢ ]"));
+        assert_eq!(
+            lookup::<_, _, DefaultTokenizer>(&array![1, 2, 3], "[٢]"),
+            Err(Error::ExpectedIndex {
+                segment: String::from("[٢]")
+            })
+        );
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_error_into_io_error() {
+        use std::io;
+
+        let sample = dict! { "users" => array![dict! { "id" => 1 }] };
+
+        let cases = vec![
+            ("users.[0].name", io::ErrorKind::NotFound),
+            ("users.[1]", io::ErrorKind::NotFound),
+            ("users.@values", io::ErrorKind::InvalidInput),
+            ("users.[x]", io::ErrorKind::InvalidData),
+            ("users..id", io::ErrorKind::InvalidData),
+        ];
+
+        for (path, kind) in cases {
+            let e = lookup::<_, _, DefaultTokenizer>(&sample, path).unwrap_err();
+            let message = e.to_string();
+            let e = io::Error::from(e);
+
+            assert_eq!(e.kind(), kind, "{}", path);
+            assert_eq!(e.to_string(), message);
+            assert!(e
+                .get_ref()
+                .and_then(|e| e.downcast_ref::<Error>())
+                .is_some());
+        }
+
+        assert_eq!(io::Error::from(Error::Stale).kind(), io::ErrorKind::Other);
+    }
+
+    #[test]
+    fn test_query_segments() {
+        let data = array![dict! {
+            "id" => 12,
+            "child" => dict! { "id" => 20 }
+        }];
+
+        assert_eq!(
+            query_segments(&data, &["[0]", "child", "id"]),
+            Ok(Value::integer(20))
+        );
+        assert_eq!(
+            query_segments(&data, &["0", "child", "id"]),
+            Ok(Value::integer(20))
+        );
+        assert_eq!(
+            query_segments(&data, &["0", "", "id"]),
+            Err(Error::KeyError(KeyError::EmptyKey))
+        );
+        assert_eq!(
+            query_segments(&data, &["[0"]),
+            Err(Error::ExpectedIndex {
+                segment: String::from("[0")
+            })
+        );
+    }
+
+    #[cfg(feature = "query-cache")]
+    #[test]
+    fn test_query_cache() {
+        use crate::query_cache::QueryCache;
+
+        let mut cache = QueryCache::<DefaultTokenizer>::new(2);
+
+        let first = cache.get_or_parse("[0].id").cloned();
+        assert_eq!(first, Query::parse::<DefaultTokenizer>("[0].id"));
+        assert_eq!(cache.parsed(), 1);
+
+        // cached, no parsing involved
+        assert_eq!(cache.get_or_parse("[0].id").cloned(), first);
+        assert_eq!(cache.parsed(), 1);
+
+        assert!(cache.get_or_parse("[1].id").is_ok());
+        // `[0].id` is more recently used than `[1].id`
+        assert!(cache.get_or_parse("[0].id").is_ok());
+        assert!(cache.get_or_parse("[2].id").is_ok());
+
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache.parsed(), 3);
+        assert!(cache.contains("[0].id"));
+        assert!(cache.contains("[2].id"));
+        assert!(!cache.contains("[1].id"));
+
+        // failed parse isn't cached
+        assert!(cache.get_or_parse("[x].id").is_err());
+        assert_eq!(cache.len(), 2);
+        assert!(cache.contains("[0].id"));
+    }
+
+    #[test]
+    fn test_diff() {
+        let old = dict! {
+            "host" => "localhost",
+            "port" => 8080,
+            "debug" => true,
+            "tags" => array!["a", "b"]
+        };
+
+        let new = dict! {
+            "host" => "localhost",
+            "port" => 9090,
+            "timeout" => 30,
+            "tags" => array!["a"]
+        };
+
+        assert_eq!(
+            old.diff::<DefaultTokenizer>(&new),
+            vec![
+                (String::from("debug"), Change::Removed(Value::from(true))),
+                (
+                    String::from("port"),
+                    Change::Modified(Value::from(8080), Value::from(9090))
+                ),
+                (String::from("tags.[1]"), Change::Removed(Value::from("b"))),
+                (String::from("timeout"), Change::Added(Value::from(30))),
+            ]
+        );
+        assert_eq!(
+            new.diff::<SlashTokenizer>(&old)[0],
+            (String::from("/debug"), Change::Added(Value::from(true)))
+        );
+        assert!(old.diff::<DefaultTokenizer>(&old).is_empty());
+    }
+
+    #[test]
+    fn test_lookup_segment_kind_mismatch() {
+        let dict = dict! { "id" => 1, "0" => "zero" };
+        let list = array!["first"];
+
+        let index_on_dict = |key: &str| {
+            Err(Error::TypeError(
+                String::from(key),
+                QueryKind::Array,
+                QueryKind::Dictionary,
+            ))
+        };
+
+        // `[0]` is an index for the bracket tokenizers
+        assert_eq!(
+            lookup::<_, _, DefaultTokenizer>(&dict, "[0]"),
+            index_on_dict("[0]")
+        );
+        assert_eq!(
+            lookup::<_, _, LenientTokenizer>(&dict, "[0]"),
+            index_on_dict("[0]")
+        );
+        assert_eq!(
+            lookup::<_, _, DefaultTokenizer>(&dict, "[-1]"),
+            index_on_dict("[-1]")
+        );
+        // but a plain key for the slash ones
+        assert_eq!(
+            lookup::<_, _, SlashTokenizer>(&dict, "/[0]"),
+            Err(Error::key_not_exist("[0]"))
+        );
+
+        // numeric key still resolves when it exists in the dictionary
+        assert_eq!(
+            lookup::<_, _, SlashTokenizer>(&dict, "/0"),
+            Ok(Value::from("zero"))
+        );
+        assert_eq!(
+            lookup::<_, _, SlashTokenizer>(&dict! { "id" => 1 }, "/0"),
+            index_on_dict("0")
+        );
+
+        // bare `0` is a key for the bracket tokenizers, and an index for the slash ones
+        assert_eq!(
+            lookup::<_, _, DefaultTokenizer>(&list, "0"),
+            Err(Error::ExpectedIndex {
+                segment: String::from("0")
+            })
+        );
+        assert_eq!(
+            lookup::<_, _, LenientTokenizer>(&list, "0"),
+            Err(Error::ExpectedIndex {
+                segment: String::from("0")
+            })
+        );
+        assert_eq!(
+            lookup::<_, _, SlashTokenizer>(&list, "/0"),
+            Ok(Value::from("first"))
+        );
+        assert_eq!(
+            lookup::<_, _, DelimTokenizer<':'>>(&list, "0"),
+            Ok(Value::from("first"))
+        );
+    }
+
+    #[test]
+    fn test_quoted_empty_key() {
+        let data = dict! { "" => dict! { "a.b" => 1 }, "a" => dict! { "b" => 2 } };
+
+        assert_eq!(
+            lookup::<_, _, DefaultTokenizer>(&data, r#"[""]"#),
+            Ok(dict! { "a.b" => 1 })
+        );
+        assert_eq!(
+            lookup::<_, _, DefaultTokenizer>(&data, r#"[""].["a.b"]"#),
+            Ok(Value::from(1))
+        );
+        assert_eq!(
+            lookup::<_, _, LenientTokenizer>(&data, r#"[""].["a.b"]"#),
+            Ok(Value::from(1))
+        );
+        assert_eq!(
+            lookup::<_, _, DefaultTokenizer>(&data, "a.b"),
+            Ok(Value::from(2))
+        );
+
+        // unquoted empty segment is still an error
+        assert_eq!(
+            lookup::<_, _, DefaultTokenizer>(&data, "a..b"),
+            Err(Error::KeyError(KeyError::EmptySegment {
+                query: String::from("a..b"),
+                position: 2
+            }))
+        );
+        assert_eq!(
+            lookup::<_, _, DefaultTokenizer>(&data, r#"[""]x"#),
+            Err(Error::KeyError(KeyError::ParseError(String::from(
+                r#"[""]x"#
+            ))))
+        );
+        assert_eq!(
+            Query::parse::<DefaultTokenizer>(r#"a.[""]"#).map(|q| q.steps().to_vec()),
+            Ok(vec![Step::Key("a".into()), Step::Key("".into())])
+        );
+    }
+
+    #[test]
+    fn test_default_tokenizer_render_round_trip() {
+        let keys = [
+            "id", "", "a.b", "[0]", "*", "a*", r#"a"b"#, r#"a\"#, r#"a"]b"#, "a b", "a:-b",
+        ];
+
+        for key in &keys {
+            let steps = vec![
+                Step::Key((*key).into()),
+                Step::Index(1),
+                Step::Key((*key).into()),
+            ];
+            let path = DefaultTokenizer::render(&steps);
+
+            for found in &[
+                Query::parse::<DefaultTokenizer>(&path),
+                Query::parse::<LenientTokenizer>(&path),
+            ] {
+                assert_eq!(
+                    found.as_ref().map(|q| q.steps()),
+                    Ok(&steps[..]),
+                    "{}",
+                    path
+                );
+            }
+        }
+
+        assert_eq!(
+            DefaultTokenizer::render(&[Step::Key("a.b".into()), Step::Key("".into())]),
+            r#"["a.b"].[""]"#
+        );
+        assert_eq!(
+            DefaultTokenizer::render(&[Step::Key(r#"a"b\"#.into())]),
+            r#"["a\"b\\"]"#
+        );
+
+        // paths being produced point back to the same node
+        let data =
+            dict! { "a.b" => dict! { "c" => 1 }, "a" => dict! { "b" => dict! { "c" => 2 } } };
+
+        assert_eq!(
+            data.resolve_with_path::<DefaultTokenizer>(r#"["a.b"].c"#),
+            Ok((Value::from(1), String::from(r#"["a.b"].c"#)))
+        );
+        for (path, leaf) in data.flatten::<DefaultTokenizer>() {
+            assert_eq!(
+                lookup::<_, _, DefaultTokenizer>(&data, &path).as_ref(),
+                Ok(leaf)
+            );
+        }
+
+        let old = dict! { "a.b" => 1 };
+        let new = dict! { "a" => dict! { "b" => 1 } };
+
+        assert_eq!(
+            old.diff::<DefaultTokenizer>(&new),
+            vec![
+                (String::from(r#"["a.b"]"#), Change::Removed(Value::from(1))),
+                (String::from("a.b"), Change::Added(Value::from(1))),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_query_kind_order() {
+        let mut kinds = BTreeMap::new();
+        kinds.insert(QueryKind::Dictionary, "dictionary");
+        kinds.insert(QueryKind::Array, "array");
+
+        assert!(QueryKind::Array < QueryKind::Dictionary);
+        assert_eq!(
+            kinds.into_iter().collect::<Vec<_>>(),
+            vec![
+                (QueryKind::Array, "array"),
+                (QueryKind::Dictionary, "dictionary")
+            ]
+        );
+    }
+
+    #[test]
+    fn test_query_coerced() {
+        let wrapped = dict! { "tags" => array!["xy"] };
+        let bare = dict! { "tags" => "xy" };
+
+        assert_eq!(
+            wrapped.query_coerced::<DefaultTokenizer>("tags.[0]"),
+            Ok(Value::from("xy"))
+        );
+        assert_eq!(
+            bare.query_coerced::<DefaultTokenizer>("tags.[0]"),
+            Ok(Value::from("xy"))
+        );
+
+        // only the first element is coerced
+        assert_eq!(
+            dict! { "tags" => 1 }.query_coerced::<DefaultTokenizer>("tags.[1]"),
+            Err(Error::NotTraversable {
+                path: String::from("[1]"),
+                kind_hint: "scalar"
+            })
+        );
+        // plain query still indexes into the string
+        assert_eq!(
+            bare.query::<DefaultTokenizer>("tags.[0]"),
+            Ok(Value::from("x"))
+        );
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_cached_queryable_threads() {
+        use super::cache::CachedQueryable;
+        use std::thread;
+
+        let cached = Arc::new(CachedQueryable::new(dict! {
+            "users" => array![dict! { "name" => "alice" }]
+        }));
+
+        let handles = (0..8)
+            .map(|_| {
+                let cached = Arc::clone(&cached);
+                thread::spawn(move || cached.get::<DefaultTokenizer>("users.[0].name"))
+            })
+            .collect::<Vec<_>>();
+
+        for handle in handles {
+            assert_eq!(handle.join().unwrap(), Ok(Value::from("alice")));
+        }
+
+        assert_eq!(cached.len(), 1);
+
+        // same path, cached separately for a different tokenizer
+        assert_eq!(
+            cached.get::<LenientTokenizer>("users.[0].name"),
+            Ok(Value::from("alice"))
+        );
+        assert_eq!(cached.len(), 2);
+
+        // errors aren't cached
+        assert!(cached.get::<DefaultTokenizer>("users.[1]").is_err());
+        assert_eq!(cached.len(), 2);
+    }
+
+    #[test]
+    fn test_index_error_input() {
+        for (tokenizer, found) in [
+            ("default", DefaultTokenizer::index_parse("[one]")),
+            ("slash", SlashTokenizer::index_parse("one")),
+        ] {
+            match found {
+                Err(IndexError::IntError { input, .. }) => {
+                    assert_eq!(input, "one", "{}", tokenizer)
+                }
+                found => panic!("{}: unexpected {:?}", tokenizer, found),
+            }
+        }
+
+        assert_eq!(
+            DefaultTokenizer::index_parse("[one]")
+                .unwrap_err()
+                .to_string(),
+            "invalid array index \"one\": invalid digit found in string"
+        );
+    }
+
+    #[test]
+    fn test_leaves_lazy() {
+        let data = dict! {
+            "users" => Value::Array(
+                (0..1000)
+                    .map(|idx| dict! { "id" => idx, "tags" => array!["a", "b"] })
+                    .collect()
+            )
+        };
+
+        assert_eq!(
+            data.leaves::<DefaultTokenizer>().collect::<Vec<_>>(),
+            data.flatten::<DefaultTokenizer>()
+        );
+
+        CHILDREN_CALLS.with(|calls| calls.set(0));
+
+        let first = data
+            .leaves::<DefaultTokenizer>()
+            .take(1)
+            .collect::<Vec<_>>();
+        assert_eq!(first.len(), 1);
+        assert!(first[0].0.starts_with("users.[0]."));
+
+        // root, `users`, `users.[0]` and its first child
+        let calls = CHILDREN_CALLS.with(Cell::get);
+        assert!(calls <= 5, "children being called {} times", calls);
+    }
+
+    #[test]
+    fn test_query_flat_or_nested() {
+        let flat = dict! { "a.b.c" => 1 };
+        let nested = dict! { "a" => dict! { "b" => dict! { "c" => 1 } } };
+        let mixed = dict! { "a" => dict! { "b.c" => 1 } };
+
+        for data in &[flat, nested, mixed] {
+            assert_eq!(
+                data.query_flat_or_nested::<DefaultTokenizer>("a.b.c"),
+                Ok(Value::from(1))
+            );
+        }
+
+        // flat key wins over the nested one
+        let both = dict! { "a.b" => 1, "a" => dict! { "b" => 2 } };
+        assert_eq!(
+            both.query_flat_or_nested::<DefaultTokenizer>("a.b"),
+            Ok(Value::from(1))
+        );
+
+        // error of the nested traversal
+        assert_eq!(
+            dict! { "a" => dict! { "x" => 1 } }.query_flat_or_nested::<DefaultTokenizer>("a.b.c"),
+            Err(Error::key_not_exist("b"))
+        );
+        assert_eq!(
+            dict! { "a" => array![1] }.query_flat_or_nested::<DefaultTokenizer>("a.[0]"),
+            Ok(Value::from(1))
+        );
+    }
+
+    #[test]
+    fn test_lookup_all_paths() {
+        let data = dict! {
+            "users" => array![
+                dict! { "id" => 1, "name" => "alice" },
+                dict! { "name" => "bob" },
+                dict! { "id" => 3 }
+            ]
+        };
+
+        assert_eq!(
+            lookup_all_paths::<_, _, DefaultTokenizer>(&data, "users.*.id"),
+            Ok(vec![
+                (String::from("users.[0].id"), Value::from(1)),
+                (String::from("users.[2].id"), Value::from(3)),
+            ])
+        );
+        assert_eq!(
+            lookup_all_paths::<_, _, SlashTokenizer>(&data, "/users/-1/*"),
+            Ok(vec![(String::from("/users/2/id"), Value::from(3))])
+        );
+        assert_eq!(
+            lookup_all_paths::<_, _, DefaultTokenizer>(&data, "users.[5]"),
+            Ok(Vec::new())
+        );
+        assert_eq!(
+            lookup_all_paths::<_, _, DefaultTokenizer>(&data, "users.*.id")
+                .map(|found| found.len()),
+            data.count_matches::<DefaultTokenizer>("users.*.id")
+        );
+
+        // numeric dictionary keys
+        let data = dict! { "ports" => dict! { "80" => "http", "443" => "https" } };
+
+        assert_eq!(
+            lookup_all_paths::<_, _, SlashTokenizer>(&data, "/ports/80"),
+            Ok(vec![(String::from("/ports/80"), Value::from("http"))])
+        );
+        assert_eq!(
+            lookup_all_paths::<_, _, SlashTokenizer>(&data, "/ports/22"),
+            Ok(Vec::new())
+        );
+    }
+
+    #[test]
+    fn test_index_not_exist_len() {
+        let data = dict! { "list" => array![1, 2, 3] };
+
+        let e = lookup::<_, _, DefaultTokenizer>(&data, "list.[3]").unwrap_err();
+        assert_eq!(e, Error::IndexNotExist { index: 3, len: 3 });
+        assert_eq!(e.to_string(), "index 3 doesn't exist, length is 3");
+
+        assert_eq!(
+            lookup::<_, _, SlashTokenizer>(&data, "/list/-4"),
+            Err(Error::IndexNotExist { index: 4, len: 3 })
+        );
+        assert_eq!(
+            lookup::<_, _, DefaultTokenizer>(&array![array![]], "[0].[0]"),
+            Err(Error::IndexNotExist { index: 0, len: 0 })
+        );
+    }
+
+    #[test]
+    fn test_lookup_tuple() {
+        let sample = (Value::from(1), Value::from(2), array![3]);
+
+        assert_eq!(
+            lookup::<_, _, DefaultTokenizer>(&sample, "[1]"),
+            Ok(Value::from(2))
+        );
+        assert_eq!(
+            lookup::<_, _, SlashTokenizer>(&sample, "/2/0"),
+            Ok(Value::from(3))
+        );
+        assert_eq!(
+            lookup::<_, _, DefaultTokenizer>(&sample, "[-1]"),
+            Ok(array![3])
+        );
+        assert_eq!(
+            lookup::<_, _, DefaultTokenizer>(&sample, "[3]"),
+            Err(Error::IndexNotExist { index: 3, len: 3 })
+        );
+        assert_eq!(
+            sample.clone().into_query::<DefaultTokenizer>("[0]"),
+            Ok(Value::from(1))
+        );
+        assert_eq!(sample.children().len(), 3);
+
+        let single = (Value::from(1),);
+        assert_eq!(
+            lookup::<_, _, DefaultTokenizer>(&single, "[0]"),
+            Ok(Value::from(1))
+        );
+
+        let wide = (
+            Value::from(0),
+            Value::from(1),
+            Value::from(2),
+            Value::from(3),
+            Value::from(4),
+            Value::from(5),
+            Value::from(6),
+            Value::from(7),
+            Value::from(8),
+            Value::from(9),
+            Value::from(10),
+            Value::from(11),
+        );
+        assert_eq!(
+            lookup::<_, _, DefaultTokenizer>(&wide, "[11]"),
+            Ok(Value::from(11))
+        );
+    }
+
+    #[test]
+    fn test_query_kinded() {
+        let data = array![dict! { "id" => 12 }, array![]];
+
+        assert_eq!(
+            data.query_kinded::<DefaultTokenizer>("[0]"),
+            Ok((Some(QueryKind::Dictionary), dict! { "id" => 12 }))
+        );
+        assert_eq!(
+            data.query_kinded::<DefaultTokenizer>("[0].id"),
+            Ok((None, Value::from(12)))
+        );
+        assert_eq!(
+            data.query_kinded::<DefaultTokenizer>("[1]"),
+            Ok((Some(QueryKind::Array), array![]))
+        );
+        assert_eq!(
+            data.query_kinded::<DefaultTokenizer>("[0].name"),
+            Err(Error::key_not_exist("name"))
+        );
+    }
+
+    #[test]
+    fn test_strict_slash_tokenizer() {
+        let dict = dict! { "0" => "key", "#0" => "marked" };
+        let list = array!["index"];
+
+        // unmarked `0` depends on the node
+        assert_eq!(
+            lookup::<_, _, StrictSlashTokenizer>(&dict, "/0"),
+            Ok(Value::from("key"))
+        );
+        assert_eq!(
+            lookup::<_, _, StrictSlashTokenizer>(&list, "/0"),
+            Ok(Value::from("index"))
+        );
+
+        // forced index
+        assert_eq!(
+            lookup::<_, _, StrictSlashTokenizer>(&list, "/#0"),
+            Ok(Value::from("index"))
+        );
+        assert_eq!(
+            lookup::<_, _, StrictSlashTokenizer>(&dict! { "0" => "key" }, "/#0"),
+            Err(Error::TypeError(
+                String::from("#0"),
+                QueryKind::Array,
+                QueryKind::Dictionary
+            ))
+        );
+
+        // forced key
+        assert_eq!(
+            lookup::<_, _, StrictSlashTokenizer>(&dict, "/$0"),
+            Ok(Value::from("key"))
+        );
+        assert_eq!(
+            lookup::<_, _, StrictSlashTokenizer>(&dict, "/$#0"),
+            Ok(Value::from("marked"))
+        );
+        assert_eq!(
+            lookup::<_, _, StrictSlashTokenizer>(&list, "/$0"),
+            Err(Error::ExpectedIndex {
+                segment: String::from("$0")
+            })
+        );
+
+        assert_eq!(
+            Query::parse::<StrictSlashTokenizer>("/#0/$0/-1").map(|q| q.steps().to_vec()),
+            Ok(vec![
+                Step::Index(0),
+                Step::Key("0".into()),
+                Step::FromEnd(1)
+            ])
+        );
+        assert_eq!(
+            StrictSlashTokenizer::render(&[Step::Key("$x".into()), Step::Key("-1".into())]),
+            "/$$x/$-1"
+        );
+    }
+
+    #[test]
+    fn test_backslash_tolerant_tokenizer() {
+        let data = dict! { "a" => dict! { "b" => array![1, 2] } };
+        let expected = lookup::<_, _, SlashTokenizer>(&data, "/a/b");
+
+        assert!(expected.is_ok());
+
+        for path in &[r"\a\b", r"/a\b", r"\a/b", "/a/b"] {
+            assert_eq!(
+                lookup::<_, _, BackslashTolerantTokenizer>(&data, *path),
+                expected,
+                "{}",
+                path
+            );
+            assert_eq!(
+                Query::parse::<BackslashTolerantTokenizer>(path),
+                Query::parse::<SlashTokenizer>("/a/b")
+            );
+        }
+
+        assert_eq!(
+            lookup::<_, _, BackslashTolerantTokenizer>(&data, r"\a\b\-1"),
+            Ok(Value::from(2))
+        );
+        assert_eq!(
+            lookup::<_, _, BackslashTolerantTokenizer>(&data, r"\a\\b"),
+            Err(Error::KeyError(KeyError::EmptySegment {
+                query: String::from(r"\a\\b"),
+                position: 3
+            }))
+        );
+        // backslash isn't a separator for the plain slash tokenizer
+        assert_eq!(
+            lookup::<_, _, SlashTokenizer>(&dict! { r"a\b" => 1 }, r"/a\b"),
+            Ok(Value::from(1))
+        );
+    }
+
+    #[test]
+    fn test_projected() {
+        use super::projected::Projected;
+
+        let data = dict! {
+            "public" => dict! { "name" => "alice", "secret" => "nested" },
+            "secret" => "hunter2",
+            "list" => array![1, 2]
+        };
+        let view = Projected::new(&data, vec!["public", "list"]);
+
+        assert_eq!(
+            lookup::<_, _, DefaultTokenizer>(&view, "secret"),
+            Err(Error::key_not_exist("secret"))
+        );
+        assert_eq!(
+            lookup::<_, _, DefaultTokenizer>(&view, "public.name"),
+            Ok(Value::from("alice"))
+        );
+        // only top-level keys are projected
+        assert_eq!(
+            lookup::<_, _, DefaultTokenizer>(&view, "public.secret"),
+            Ok(Value::from("nested"))
+        );
+        assert_eq!(
+            lookup::<_, _, SlashTokenizer>(&view, "/list/-1"),
+            Ok(Value::from(2))
+        );
+
+        assert_eq!(
+            lookup::<_, _, DefaultTokenizer>(&view, VALUES_SEGMENT),
+            Err(Error::key_not_exist(VALUES_SEGMENT))
+        );
+        assert_eq!(
+            lookup::<_, _, DefaultTokenizer>(&view, ""),
+            Err(Error::KeyError(KeyError::EmptyKey))
+        );
+
+        let mut paths = view
+            .flatten::<DefaultTokenizer>()
+            .into_iter()
+            .map(|(path, _)| path)
+            .collect::<Vec<_>>();
+        paths.sort();
+        assert_eq!(
+            paths,
+            vec!["list.[0]", "list.[1]", "public.name", "public.secret"]
+        );
+        assert_eq!(view.count_matches::<DefaultTokenizer>("*"), Ok(2));
+
+        // arrays pass through
+        let list = array![dict! { "secret" => 1 }];
+        assert_eq!(
+            lookup::<_, _, DefaultTokenizer>(&Projected::new(&list, vec![]), "[0].secret"),
+            Ok(Value::from(1))
+        );
+    }
+
+    #[test]
+    fn test_not_traversable() {
+        let data = array![dict! { "id" => 12 }];
+
+        let e = lookup::<_, _, DefaultTokenizer>(&data, "[0].id.extra").unwrap_err();
+        assert_eq!(
+            e,
+            Error::NotTraversable {
+                path: String::from("extra"),
+                kind_hint: "scalar"
+            }
+        );
+        assert_eq!(e.to_string(), "can't resolve \"extra\" on a scalar");
+        assert!(e.is_data_error());
+
+        assert_eq!(
+            lookup::<_, _, SlashTokenizer>(&data, "/0/id/extra/more"),
+            Err(Error::NotTraversable {
+                path: String::from("/extra/more"),
+                kind_hint: "scalar"
+            })
+        );
+
+        // same error through every entry point
+        let not_traversable = |path: &str| {
+            Err(Error::NotTraversable {
+                path: String::from(path),
+                kind_hint: "scalar",
+            })
+        };
+        let mut data = data;
+
+        assert_eq!(
+            data.query_checked::<DefaultTokenizer>("[0].id.extra"),
+            not_traversable("extra")
+        );
+        assert_eq!(
+            data.query_path(&["0", "id", "extra"]),
+            not_traversable("extra")
+        );
+        assert_eq!(
+            data.query_steps(&[
+                Step::Index(0),
+                Step::Key("id".into()),
+                Step::Key("extra".into())
+            ]),
+            not_traversable("extra")
+        );
+        assert_eq!(
+            data.clone().into_query::<DefaultTokenizer>("[0].id.extra"),
+            not_traversable("extra")
+        );
+        assert_eq!(
+            data.query_mut::<DefaultTokenizer>("[0].id.extra").err(),
+            not_traversable("extra").err()
+        );
+        assert_eq!(
+            data.set_at::<DefaultTokenizer>("[0].id.extra", Value::from(1))
+                .err(),
+            not_traversable("extra").err()
+        );
+        assert_eq!(
+            data.remove_at::<DefaultTokenizer>("[0].id.extra"),
+            not_traversable("extra")
+        );
+    }
+
+    #[test]
+    fn test_tokenizer_first() {
+        assert_eq!(
+            DefaultTokenizer::first("[0].child.id"),
+            Ok((Step::Index(0), Some("child.id")))
+        );
+        assert_eq!(
+            SlashTokenizer::first("/0/child/id"),
+            Ok((Step::Index(0), Some("/child/id")))
+        );
+        assert_eq!(
+            DefaultTokenizer::first("id"),
+            Ok((Step::Key("id".into()), None))
+        );
+
+        // routing by peeling segments off one at a time
+        let mut steps = Vec::new();
+        let mut rest = Some("[-1].*.id");
+
+        while let Some(path) = rest {
+            let (step, next) = DefaultTokenizer::first(path).unwrap();
+            steps.push(step);
+            rest = next;
+        }
+
+        assert_eq!(
+            steps,
+            vec![Step::FromEnd(1), Step::Wildcard, Step::Key("id".into())]
+        );
+
+        assert!(matches!(
+            DefaultTokenizer::first("[x].id"),
+            Err(Error::IndexError(IndexError::IntError { .. }))
+        ));
+        assert_eq!(
+            DefaultTokenizer::first(""),
+            Err(Error::KeyError(KeyError::EmptyKey))
+        );
+    }
+
+    #[test]
+    fn test_project() {
+        let data = dict! {
+            "users" => array![
+                dict! { "id" => 1 },
+                dict! { "name" => "bob" },
+                dict! { "id" => 2 }
+            ]
+        };
+
+        assert_eq!(
+            data.project::<DefaultTokenizer>("users", "id"),
+            Ok(vec![Value::from(1), Value::from(2)])
+        );
+        assert_eq!(
+            data.project_strict::<DefaultTokenizer>("users", "id"),
+            Err(Error::key_not_exist("id"))
+        );
+        assert_eq!(
+            array![dict! { "id" => 1 }, dict! { "id" => 2 }]
+                .project_strict::<SlashTokenizer>("", "/id"),
+            Ok(vec![Value::from(1), Value::from(2)])
+        );
+        assert_eq!(
+            data.project::<DefaultTokenizer>("", "id"),
+            Err(Error::TypeError(
+                String::new(),
+                QueryKind::Array,
+                QueryKind::Dictionary
+            ))
+        );
+        assert_eq!(
+            data.project::<DefaultTokenizer>("users", "[0]"),
+            Err(Error::TypeError(
+                String::from("[0]"),
+                QueryKind::Array,
+                QueryKind::Dictionary
+            ))
+        );
+    }
+
+    #[test]
+    fn test_query_default_literal() {
+        let data = dict! { "present" => dict! { "key" => 1 }, "list" => array![] };
+
+        // `:-` inside a quoted key isn't a fallback
+        let quoted = dict! { "a:-b" => 1 };
+
+        assert_eq!(
+            lookup::<_, _, DefaultTokenizer>(&quoted, r#"["a:-b"]"#),
+            Ok(Value::integer(1))
+        );
+        assert_eq!(
+            lookup::<_, _, DefaultTokenizer>(&quoted, r#"["a:-c"]:-2"#),
+            Ok(Value::integer(2))
+        );
+        assert_eq!(
+            lookup::<_, _, LenientTokenizer>(&quoted, r#"["a:-b"]"#),
+            Ok(Value::integer(1))
+        );
+
+        assert_eq!(
+            lookup::<_, _, DefaultTokenizer>(&data, "missing.key:-5"),
+            Ok(Value::integer(5))
+        );
+        assert_eq!(
+            lookup::<_, _, DefaultTokenizer>(&data, "present.key:-5"),
+            Ok(Value::integer(1))
+        );
+        assert_eq!(
+            lookup::<_, _, DefaultTokenizer>(&data, "list.[0]:-true"),
+            Ok(Value::bool(true))
+        );
+        assert_eq!(
+            lookup::<_, _, SlashTokenizer>(&data, "/present/name:-alice"),
+            Ok(Value::string("alice"))
+        );
+        assert_eq!(
+            lookup::<_, _, DefaultTokenizer>(&data, "present.ratio:-0.5"),
+            Ok(Value::double(0.5))
+        );
+
+        assert_eq!(
+            lookup::<_, _, DefaultTokenizer>(&Rc::new(data.clone()), "missing:-5"),
+            Ok(Value::integer(5))
+        );
+        assert_eq!(
+            lookup::<_, _, DefaultTokenizer>(&[Value::from(1)], "[1]:-5"),
+            Ok(Value::integer(5))
+        );
+
+        // only misses fall back
+        assert_eq!(
+            lookup::<_, _, DefaultTokenizer>(&data, "present.key.x:-5"),
+            Err(Error::NotTraversable {
+                path: String::from("x"),
+                kind_hint: "scalar"
+            })
+        );
+        // types without the hook fail the same way as without a fallback
+        let doc = Doc::List(vec![Doc::Leaf(1)]);
+        assert_eq!(
+            lookup::<_, _, DefaultTokenizer>(&doc, "[1]:-5"),
+            Err(Error::IndexNotExist { index: 1, len: 1 })
+        );
+    }
+
+    #[test]
+    fn test_cursor() {
+        let data = Value::Dictionary(
+            vec![
+                (
+                    String::from("users"),
+                    Value::Array(vec![
+                        Value::Dictionary(
+                            vec![(String::from("name"), Value::from("alice"))]
+                                .into_iter()
+                                .collect(),
+                        ),
+                        Value::Dictionary(
+                            vec![(String::from("name"), Value::from("bob"))]
+                                .into_iter()
+                                .collect(),
+                        ),
+                    ]),
+                ),
+                (String::from("total"), Value::from(2)),
+            ]
+            .into_iter()
+            .collect(),
+        );
+
+        let mut cursor = Cursor::new(&data);
+
+        assert_eq!(cursor.up().err(), Some(Error::NoParent));
+
+        cursor
+            .down_key("users")
+            .and_then(|c| c.down_index(0))
+            .unwrap();
+
+        assert_eq!(cursor.depth(), 2);
+        assert_eq!(cursor.path::<DefaultTokenizer>(), "users.[0]");
+        assert_eq!(cursor.path::<SlashTokenizer>(), "/users/0");
+
+        cursor.up().and_then(|c| c.down_index(1)).unwrap();
+
+        assert_eq!(cursor.path::<DefaultTokenizer>(), "users.[1]");
+        assert_eq!(
+            cursor.down_key("name").map(|c| c.current()),
+            Ok(&Value::from("bob"))
+        );
+
+        cursor
+            .up()
+            .and_then(|c| c.up())
+            .and_then(|c| c.up())
+            .unwrap();
+
+        assert!(std::ptr::eq(cursor.current(), &data));
+        assert_eq!(cursor.path::<DefaultTokenizer>(), "");
+
+        // failed moves keep the cursor in place
+        assert_eq!(
+            cursor.down_index(0).err(),
+            Some(Error::TypeError(
+                String::from("[0]"),
+                QueryKind::Array,
+                QueryKind::Dictionary
+            ))
+        );
+        assert_eq!(
+            cursor.down_key("missing").err(),
+            Some(Error::key_not_exist("missing"))
+        );
+
+        cursor.down_key("users").unwrap();
+
+        assert_eq!(
+            cursor.down_index(2).err(),
+            Some(Error::IndexNotExist { index: 2, len: 2 })
+        );
+        assert_eq!(cursor.depth(), 1);
+    }
+
+    #[test]
+    fn test_resolve_prefix() {
+        let data = Value::Dictionary(
+            vec![(
+                String::from("a"),
+                Value::Dictionary(
+                    vec![(
+                        String::from("b"),
+                        Value::Dictionary(
+                            vec![(String::from("c"), Value::from(1))]
+                                .into_iter()
+                                .collect(),
+                        ),
+                    )]
+                    .into_iter()
+                    .collect(),
+                ),
+            )]
+            .into_iter()
+            .collect(),
+        );
+
+        let b = lookup::<_, _, DefaultTokenizer>(&data, "a.b").unwrap();
+
+        assert_eq!(
+            data.resolve_prefix::<DefaultTokenizer>("a.b.missing.c"),
+            (
+                b.clone(),
+                String::from("a.b"),
+                Some(Error::key_not_exist("missing"))
+            )
+        );
+        assert_eq!(
+            data.resolve_prefix::<SlashTokenizer>("/a/b/missing/c"),
+            (
+                b,
+                String::from("/a/b"),
+                Some(Error::key_not_exist("missing"))
+            )
+        );
+
+        // fully resolved
+        assert_eq!(
+            data.resolve_prefix::<DefaultTokenizer>("a.b.c"),
+            (Value::from(1), String::from("a.b.c"), None)
+        );
+
+        // nothing resolved, the root is the deepest node
+        assert_eq!(
+            data.resolve_prefix::<DefaultTokenizer>("x.y"),
+            (data.clone(), String::new(), Some(Error::key_not_exist("x")))
+        );
+
+        let list = array![1, 2];
+
+        assert_eq!(
+            list.resolve_prefix::<DefaultTokenizer>("[5]"),
+            (
+                list.clone(),
+                String::new(),
+                Some(Error::IndexNotExist { index: 5, len: 2 })
+            )
+        );
+        assert_eq!(
+            list.resolve_prefix::<DefaultTokenizer>("[-1].x"),
+            (
+                Value::from(2),
+                String::from("[1]"),
+                Some(Error::NotTraversable {
+                    path: String::from("x"),
+                    kind_hint: "scalar"
+                })
+            )
+        );
+
+        // quoted keys are resolved as they are
+        let data = dict! { "a.b" => dict! { "c" => 1 } };
+
+        assert_eq!(
+            data.resolve_prefix::<DefaultTokenizer>(r#"["a.b"].c"#),
+            (Value::from(1), String::from(r#"["a.b"].c"#), None)
+        );
+        assert_eq!(
+            data.resolve_prefix::<DefaultTokenizer>(r#"["a.b"].d"#),
+            (
+                dict! { "c" => 1 },
+                String::from(r#"["a.b"]"#),
+                Some(Error::key_not_exist("d"))
+            )
+        );
+    }
+
+    #[test]
+    fn test_default_tokenizer_stray_dots() {
+        let data = Value::Dictionary(
+            vec![(
+                String::from("foo"),
+                Value::Dictionary(
+                    vec![(String::from("bar"), Value::from(1))]
+                        .into_iter()
+                        .collect(),
+                ),
+            )]
+            .into_iter()
+            .collect(),
+        );
+
+        // leading dot of the whole query
+        assert_eq!(
+            lookup::<_, _, DefaultTokenizer>(&data, ".foo"),
+            Err(Error::KeyError(KeyError::ParseError(String::from(".foo"))))
+        );
+        assert_eq!(
+            lookup::<_, _, DefaultTokenizer>(&data, ".foo.bar"),
+            Err(Error::KeyError(KeyError::ParseError(String::from(
+                ".foo.bar"
+            ))))
+        );
+
+        // trailing dot, the last step is empty
+        assert_eq!(
+            lookup::<_, _, DefaultTokenizer>(&data, "foo."),
+            Err(Error::KeyError(KeyError::EmptyKey))
+        );
+
+        // double dot, the step in between is empty
+        assert_eq!(
+            lookup::<_, _, DefaultTokenizer>(&data, "foo..bar"),
+            Err(Error::KeyError(KeyError::EmptySegment {
+                query: String::from("foo..bar"),
+                position: 4
+            }))
+        );
+        assert_eq!(
+            lookup::<_, _, DefaultTokenizer>(&data, r#"["foo"]..bar"#),
+            Err(Error::KeyError(KeyError::EmptySegment {
+                query: String::from(r#"["foo"]..bar"#),
+                position: 8
+            }))
+        );
+        assert_eq!(
+            LenientTokenizer::dict_parse("[ 0 ]..bar"),
+            Err(KeyError::EmptySegment {
+                query: String::from("[ 0 ]..bar"),
+                position: 6
+            })
+        );
+
+        assert_eq!(
+            lookup::<_, _, DefaultTokenizer>(&data, "foo.bar"),
+            Ok(Value::from(1))
+        );
+    }
+
+    #[test]
+    fn test_tokenizer_segments() {
+        assert_eq!(
+            SlashTokenizer::segments("/0/child/id").collect::<Result<Vec<_>, _>>(),
+            Ok(vec!["0", "child", "id"])
+        );
+        assert_eq!(
+            DefaultTokenizer::segments(r#"["a.b"].[1]"#).collect::<Result<Vec<_>, _>>(),
+            Ok(vec![r#"["a.b"]"#, "[1]"])
+        );
+
+        // stops right after the first error
+        let mut segments = DefaultTokenizer::segments("a..b");
+
+        assert!(matches!(
+            segments.next(),
+            Some(Err(KeyError::EmptySegment { .. }))
+        ));
+        assert_eq!(segments.next(), None);
+    }
+
+    #[test]
+    fn test_consecutive_separators() {
+        let data = Value::Dictionary(
+            vec![(
+                String::from("a"),
+                Value::Dictionary(
+                    vec![(String::from("b"), Value::from(1))]
+                        .into_iter()
+                        .collect(),
+                ),
+            )]
+            .into_iter()
+            .collect(),
+        );
+
+        let empty_segment = |query: &str, position| {
+            Err(Error::KeyError(KeyError::EmptySegment {
+                query: String::from(query),
+                position,
+            }))
+        };
+
+        let cases = [("a..b", 2), ("a...b", 2), ("a.b..c", 4), ("[0]..a", 4)];
+
+        for &(query, position) in cases.iter() {
+            assert_eq!(
+                lookup::<_, _, DefaultTokenizer>(&data, query),
+                empty_segment(query, position),
+                "{}",
+                query
+            );
+        }
+
+        let cases = [("//b", 1), ("/a//b", 3), ("/a///b", 3), ("/a/b//c", 5)];
+
+        for &(query, position) in cases.iter() {
+            assert_eq!(
+                lookup::<_, _, SlashTokenizer>(&data, query),
+                empty_segment(query, position),
+                "{}",
+                query
+            );
+        }
+
+        // the rest of the query doesn't matter once the fallback is split off
+        assert_eq!(
+            lookup::<_, _, DefaultTokenizer>(&data, "a..b:-1"),
+            empty_segment("a..b", 2)
+        );
+        assert_eq!(
+            format!(
+                "{}",
+                Error::KeyError(KeyError::EmptySegment {
+                    query: String::from("a..b"),
+                    position: 2
+                })
+            ),
+            "empty segment at 2 of \"a..b\""
+        );
+    }
+
+    #[test]
+    fn test_map_like() {
+        let mut data = BTreeMap::new();
+        data.insert(String::from("b"), Value::from(2));
+        data.insert(String::from("c"), Value::Array(vec![Value::from(3)]));
+        data.insert(String::from("a"), Value::from(1));
+
+        assert_eq!(
+            lookup::<_, _, DefaultTokenizer>(&data, KEYS_SEGMENT),
+            Ok(Value::Array(vec![
+                Value::from("a"),
+                Value::from("b"),
+                Value::from("c")
+            ]))
+        );
+        assert_eq!(
+            lookup::<_, _, DefaultTokenizer>(&data, "@values.[1]"),
+            Ok(Value::from(2))
+        );
+        assert_eq!(
+            lookup::<_, _, DefaultTokenizer>(&data, "@keys.[2]"),
+            Ok(Value::from("c"))
+        );
+        assert_eq!(
+            lookup::<_, _, SlashTokenizer>(&data, "/c/0"),
+            Ok(Value::from(3))
+        );
+        assert_eq!(
+            lookup::<_, _, DefaultTokenizer>(&data, "missing"),
+            Err(Error::key_not_exist("missing"))
+        );
+        assert_eq!(
+            lookup::<_, _, DefaultTokenizer>(&data, "[0]"),
+            Err(Error::TypeError(
+                String::from("[0]"),
+                QueryKind::Array,
+                QueryKind::Dictionary
+            ))
+        );
+        assert_eq!(
+            data.flatten::<DefaultTokenizer>()
+                .into_iter()
+                .map(|(path, _)| path)
+                .collect::<Vec<_>>(),
+            vec!["a", "b", "c.[0]"]
+        );
+
+        // same order for a HashMap, which has none of its own
+        let data = data.into_iter().collect::<HashMap<_, _>>();
+
+        assert_eq!(
+            lookup::<_, _, DefaultTokenizer>(&data, VALUES_SEGMENT),
+            Ok(Value::Array(vec![
+                Value::from(1),
+                Value::from(2),
+                Value::Array(vec![Value::from(3)])
+            ]))
+        );
+
+        // types without `query_keys` keep `@keys` as an ordinary key
+        let mut object = Map::new();
+        object.insert(String::from(KEYS_SEGMENT), Doc::Leaf(1));
+
+        assert_eq!(
+            lookup::<_, _, DefaultTokenizer>(&Doc::Object(object), KEYS_SEGMENT),
+            Ok(Doc::Leaf(1))
+        );
+    }
+
+    #[cfg(feature = "indexmap")]
+    #[test]
+    fn test_map_like_indexmap() {
+        let mut data = indexmap::IndexMap::new();
+        data.insert(String::from("b"), Value::from(2));
+        data.insert(String::from("a"), Value::from(1));
+
+        // insertion order
+        assert_eq!(
+            lookup::<_, _, DefaultTokenizer>(&data, KEYS_SEGMENT),
+            Ok(Value::Array(vec![Value::from("b"), Value::from("a")]))
+        );
+        assert_eq!(
+            lookup::<_, _, DefaultTokenizer>(&data, "@values.[1]"),
+            Ok(Value::from(1))
+        );
+    }
+
+    #[test]
+    fn test_query_checked() {
+        let data = Value::Dictionary(
+            vec![
+                (
+                    String::from("users"),
+                    Value::Array(vec![Value::from("alice"), Value::from("bob")]),
+                ),
+                (String::from("0"), Value::from("zero")),
+            ]
+            .into_iter()
+            .collect(),
+        );
+
+        assert_eq!(
+            data.query_checked::<SlashTokenizer>("/users/1"),
+            Ok(Value::from("bob"))
+        );
+
+        // index step on a dictionary, even though `0` is one of its keys
+        assert_eq!(data.query::<SlashTokenizer>("/0"), Ok(Value::from("zero")));
+        assert_eq!(
+            data.query_checked::<SlashTokenizer>("/0"),
+            Err(Error::TypeError(
+                String::from("0"),
+                QueryKind::Array,
+                QueryKind::Dictionary
+            ))
+        );
+        assert_eq!(
+            data.query_checked::<DefaultTokenizer>("[-1]"),
+            Err(Error::TypeError(
+                String::from("[-1]"),
+                QueryKind::Array,
+                QueryKind::Dictionary
+            ))
+        );
+
+        // key step on an array
+        assert_eq!(
+            data.query_checked::<DefaultTokenizer>("users.name"),
+            Err(Error::TypeError(
+                String::from("name"),
+                QueryKind::Dictionary,
+                QueryKind::Array
+            ))
+        );
+
+        assert_eq!(
+            data.query_checked::<DefaultTokenizer>("users.[-1]"),
+            Ok(Value::from("bob"))
+        );
+        assert_eq!(
+            data.query_checked::<DefaultTokenizer>("users.[0].x"),
+            Err(Error::NotTraversable {
+                path: String::from("x"),
+                kind_hint: "scalar"
+            })
+        );
+        assert_eq!(
+            data.query_checked::<DefaultTokenizer>("users.*"),
+            Err(Error::UnexpectedWildcard)
+        );
+    }
+
+    #[test]
+    fn test_update_at() {
+        let mut data = Value::Array(vec![Value::Dictionary(
+            vec![(String::from("id"), Value::from(1))]
+                .into_iter()
+                .collect(),
+        )]);
+
+        let increment = |node: &mut Value| {
+            if let Value::Literal(Literal::Number(Number::Integer(v))) = node {
+                *v += 1;
+            }
+        };
+
+        assert_eq!(
+            data.update_at::<DefaultTokenizer, _>("[0].id", increment),
+            Ok(())
+        );
+        assert_eq!(
+            lookup::<_, _, DefaultTokenizer>(&data, "[0].id"),
+            Ok(Value::from(2))
+        );
+
+        // `f` isn't called on a missing path
+        let called = Cell::new(false);
+
+        assert_eq!(
+            data.update_at::<DefaultTokenizer, _>("[0].missing", |_| called.set(true)),
+            Err(Error::key_not_exist("missing"))
+        );
+        assert!(!called.get());
+
+        // nor a path that doesn't exist yet, unlike `entry`
+        assert_eq!(
+            data.update_at::<DefaultTokenizer, _>("[1]", |_| called.set(true)),
+            Err(Error::IndexNotExist { index: 1, len: 1 })
+        );
+        assert!(!called.get());
+    }
+
+    #[test]
+    fn test_map_leaves() {
+        let data = Value::Dictionary(
+            vec![
+                (
+                    String::from("users"),
+                    Value::Array(vec![
+                        Value::Dictionary(
+                            vec![
+                                (String::from("name"), Value::from("alice")),
+                                (String::from("age"), Value::from(30)),
+                            ]
+                            .into_iter()
+                            .collect(),
+                        ),
+                        Value::from("bob"),
+                    ]),
+                ),
+                (String::from("empty"), Value::Array(vec![])),
+            ]
+            .into_iter()
+            .collect(),
+        );
+
+        let mut calls = 0;
+        let upper = data.map_leaves(|leaf| {
+            calls += 1;
+
+            match leaf {
+                Value::Literal(Literal::String(s)) => Value::from(s.to_uppercase()),
+                other => other.clone(),
+            }
+        });
+
+        // scalars only, the empty array isn't a leaf here
+        assert_eq!(calls, 3);
+        assert_eq!(
+            lookup::<_, _, DefaultTokenizer>(&upper, "users.[0].name"),
+            Ok(Value::from("ALICE"))
+        );
+        assert_eq!(
+            lookup::<_, _, DefaultTokenizer>(&upper, "users.[1]"),
+            Ok(Value::from("BOB"))
+        );
+        assert_eq!(
+            lookup::<_, _, DefaultTokenizer>(&upper, "users.[0].age"),
+            Ok(Value::from(30))
+        );
+        assert_eq!(
+            lookup::<_, _, DefaultTokenizer>(&upper, "empty"),
+            Ok(Value::Array(vec![]))
+        );
+        assert_eq!(
+            lookup::<_, _, DefaultTokenizer>(&data, "users.[0].name"),
+            Ok(Value::from("alice"))
+        );
+    }
+
+    #[test]
+    fn test_tokenize() {
+        let steps = tokenize::<DefaultTokenizer>("[0].child.id");
+
+        assert_eq!(
+            steps,
+            Ok(vec![
+                Step::Index(0),
+                Step::Key("child".into()),
+                Step::Key("id".into())
+            ])
+        );
+        assert_eq!(tokenize::<SlashTokenizer>("/0/child/id"), steps);
+        assert_eq!(
+            tokenize::<DefaultTokenizer>(""),
+            Err(Error::KeyError(KeyError::EmptyKey))
+        );
+        assert_eq!(
+            tokenize::<DefaultTokenizer>("a..b"),
+            Err(Error::KeyError(KeyError::EmptySegment {
+                query: String::from("a..b"),
+                position: 2
+            }))
+        );
+
+        let data = [Value::Dictionary(
+            vec![(
+                String::from("child"),
+                Value::Dictionary(
+                    vec![(String::from("id"), Value::from(7))]
+                        .into_iter()
+                        .collect(),
+                ),
+            )]
+            .into_iter()
+            .collect(),
+        )];
+
+        // parsed once, resolved many times
+        let steps = steps.unwrap();
+
+        for _ in 0..2 {
+            assert_eq!(lookup_steps(&data, &steps), Ok(Value::from(7)));
+        }
+    }
+
+    #[test]
+    fn test_merge() {
+        let dict = |entries: Vec<(&str, Value)>| {
+            Value::Dictionary(
+                entries
+                    .into_iter()
+                    .map(|(k, v)| (String::from(k), v))
+                    .collect(),
+            )
+        };
+
+        let mut data = dict(vec![(
+            "server",
+            dict(vec![
+                ("host", Value::from("localhost")),
+                (
+                    "tls",
+                    dict(vec![
+                        ("enabled", Value::from(false)),
+                        ("port", Value::from(443)),
+                    ]),
+                ),
+                ("tags", Value::Array(vec![Value::from("a")])),
+            ]),
+        )]);
+
+        let overlay = dict(vec![
+            ("tls", dict(vec![("enabled", Value::from(true))])),
+            ("tags", Value::Array(vec![Value::from("b")])),
+            ("workers", Value::from(4)),
+        ]);
+
+        // deep dictionary merge, arrays being concatenated
+        assert_eq!(
+            data.merge::<DefaultTokenizer>("server", overlay.clone(), MergeStrategy::default()),
+            Ok(())
+        );
+        assert_eq!(
+            lookup::<_, _, DefaultTokenizer>(&data, "server.tls"),
+            Ok(dict(vec![
+                ("enabled", Value::from(true)),
+                ("port", Value::from(443))
+            ]))
+        );
+        assert_eq!(
+            lookup::<_, _, DefaultTokenizer>(&data, "server.tags"),
+            Ok(Value::Array(vec![Value::from("a"), Value::from("b")]))
+        );
+        assert_eq!(
+            lookup::<_, _, DefaultTokenizer>(&data, "server.workers"),
+            Ok(Value::from(4))
+        );
+        assert_eq!(
+            lookup::<_, _, DefaultTokenizer>(&data, "server.host"),
+            Ok(Value::from("localhost"))
+        );
+
+        // array concat at a nested path
+        assert_eq!(
+            data.merge::<DefaultTokenizer>(
+                "server.tags",
+                Value::Array(vec![Value::from("c")]),
+                MergeStrategy::default()
+            ),
+            Ok(())
+        );
+        assert_eq!(
+            lookup::<_, _, DefaultTokenizer>(&data, "server.tags"),
+            Ok(Value::Array(vec![
+                Value::from("a"),
+                Value::from("b"),
+                Value::from("c")
+            ]))
+        );
+
+        // shallow dictionaries and replaced arrays
+        let strategy = MergeStrategy {
+            arrays: ArrayMerge::Replace,
+            dicts: DictMerge::Shallow,
+        };
+
+        assert_eq!(
+            data.merge::<DefaultTokenizer>("server", overlay, strategy),
+            Ok(())
+        );
+        assert_eq!(
+            lookup::<_, _, DefaultTokenizer>(&data, "server.tls"),
+            Ok(dict(vec![("enabled", Value::from(true))]))
+        );
+        assert_eq!(
+            lookup::<_, _, DefaultTokenizer>(&data, "server.tags"),
+            Ok(Value::Array(vec![Value::from("b")]))
+        );
+
+        // root path, and a missing path merges nothing
+        assert_eq!(
+            data.merge::<DefaultTokenizer>("", dict(vec![("debug", Value::from(true))]), strategy),
+            Ok(())
+        );
+        assert_eq!(
+            lookup::<_, _, DefaultTokenizer>(&data, "debug"),
+            Ok(Value::from(true))
+        );
+        assert_eq!(
+            data.merge::<DefaultTokenizer>("missing.path", Value::from(1), strategy),
+            Err(Error::key_not_exist("missing"))
+        );
+    }
+
+    #[test]
+    fn test_strict_numeric_tokenizer() {
+        let mut data = Value::Dictionary(
+            vec![
+                (
+                    String::from("config"),
+                    Value::Dictionary(
+                        vec![(String::from("0"), Value::from("zero"))]
+                            .into_iter()
+                            .collect(),
+                    ),
+                ),
+                (
+                    String::from("list"),
+                    Value::Array(vec![Value::from("first")]),
+                ),
+            ]
+            .into_iter()
+            .collect(),
+        );
+
+        assert_eq!(
+            lookup::<_, _, DelimTokenizer<'.'>>(&data, "config.0"),
+            Ok(Value::from("zero"))
+        );
+        assert_eq!(
+            lookup::<_, _, StrictNumericTokenizer>(&data, "config.0"),
+            Err(Error::NumericKeyOnDictionary(String::from("0")))
+        );
+        assert_eq!(
+            lookup::<_, _, StrictNumericTokenizer>(&data, "list.0"),
+            Ok(Value::from("first"))
+        );
+        assert_eq!(
+            lookup::<_, _, OneBasedTokenizer<StrictNumericTokenizer>>(&data, "config.1"),
+            Err(Error::NumericKeyOnDictionary(String::from("1")))
+        );
+
+        // mutable and consuming traversal reject it the same way
+        assert_eq!(
+            data.entry::<StrictNumericTokenizer>("config.1").err(),
+            Some(Error::NumericKeyOnDictionary(String::from("1")))
+        );
+        assert_eq!(
+            data.clone()
+                .into_query::<StrictNumericTokenizer>("config.0"),
+            Err(Error::NumericKeyOnDictionary(String::from("0")))
+        );
+        assert_eq!(
+            format!("{}", Error::NumericKeyOnDictionary(String::from("0"))),
+            "numeric segment \"0\" can't be a dictionary key"
+        );
+    }
+
+    #[test]
+    fn test_set_remove_at() {
+        let mut data = dict! { "users" => array![1, 2, 3] };
+
+        assert_eq!(
+            data.set_at::<DefaultTokenizer>("config.debug", true.into()),
+            Ok(())
+        );
+        assert_eq!(
+            lookup::<_, _, DefaultTokenizer>(&data, "config.debug"),
+            Ok(Value::from(true))
+        );
+
+        assert_eq!(
+            data.remove_at::<DefaultTokenizer>("users.[0]"),
+            Ok(Value::from(1))
+        );
+        assert_eq!(
+            data.remove_at::<DefaultTokenizer>("users.[-1]"),
+            Ok(Value::from(3))
+        );
+        assert_eq!(
+            lookup::<_, _, DefaultTokenizer>(&data, "users"),
+            Ok(array![2])
+        );
+
+        assert_eq!(
+            data.remove_at::<SlashTokenizer>("/config"),
+            Ok(dict! { "debug" => true })
+        );
+        assert_eq!(
+            data.remove_at::<DefaultTokenizer>("config"),
+            Err(Error::key_not_exist("config"))
+        );
+        assert_eq!(
+            data.remove_at::<DefaultTokenizer>("users.[1]"),
+            Err(Error::IndexNotExist { index: 1, len: 1 })
+        );
+        assert_eq!(data.remove_at::<DefaultTokenizer>(""), Err(Error::NoParent));
+
+        // quoted keys are removed as they are, not re-tokenized
+        let mut data = dict! { "a" => 1, "a.b" => 2, "" => dict! { "c" => 3 } };
+
+        assert_eq!(
+            data.remove_at::<DefaultTokenizer>(r#"[""].c"#),
+            Ok(Value::from(3))
+        );
+        assert_eq!(
+            data.remove_at::<DefaultTokenizer>(r#"["a.b"]"#),
+            Ok(Value::from(2))
+        );
+        assert_eq!(data.remove_at::<DefaultTokenizer>(r#"[""]"#), Ok(dict! {}));
+        assert_eq!(data, dict! { "a" => 1 });
+
+        let mut data = dict! { "ports" => dict! { "80" => "http" } };
+        assert_eq!(
+            data.remove_at::<SlashTokenizer>("/ports/80"),
+            Ok(Value::from("http"))
+        );
+    }
+
+    #[test]
+    fn test_versioned() {
+        let mut doc = Versioned::new(dict! {
+            "users" => array![dict! { "name" => "alice" }]
+        });
+
+        let mut cursor = doc.cursor();
+        assert_eq!(cursor.depth(), 0);
+
+        cursor
+            .down_key(&doc, "users")
+            .and_then(|c| c.down_index(&doc, 0))
+            .unwrap();
+        assert_eq!(cursor.path::<DefaultTokenizer>(), "users.[0]");
+        assert_eq!(
+            cursor.current(&doc).map(|node| node.query_dict("name")),
+            Ok(Ok(Value::from("alice")))
+        );
+        assert_eq!(
+            cursor.down_key(&doc, "age").err(),
+            Some(Error::key_not_exist("age"))
+        );
+
+        // any mutation makes the cursor stale, even one that doesn't touch its path
+        assert_eq!(doc.set_at::<DefaultTokenizer>("version", 2.into()), Ok(()));
+        assert_eq!(doc.generation(), 1);
+
+        assert_eq!(cursor.current(&doc), Err(Error::Stale));
+        assert_eq!(cursor.up(&doc).err(), Some(Error::Stale));
+        assert_eq!(cursor.down_key(&doc, "name").err(), Some(Error::Stale));
+        assert!(!Error::Stale.is_query_error());
+        assert!(!Error::Stale.is_data_error());
+
+        // a fresh cursor sees the mutation
+        let mut cursor = doc.cursor();
+        assert_eq!(
+            cursor.down_key(&doc, "version").unwrap().current(&doc),
+            Ok(&Value::from(2))
+        );
+
+        assert!(doc.query_mut::<DefaultTokenizer>("users.[0]").is_ok());
+        assert_eq!(cursor.current(&doc), Err(Error::Stale));
+
+        // failed mutations bump the generation too
+        assert!(doc.remove_at::<DefaultTokenizer>("missing").is_err());
+        assert_eq!(doc.generation(), 3);
+
+        assert_eq!(
+            doc.into_inner(),
+            dict! { "users" => array![dict! { "name" => "alice" }], "version" => 2 }
+        );
+    }
+
+    #[test]
+    fn test_tokenizer_classify() {
+        assert_eq!(DefaultTokenizer::classify("[0]"), Segment::Index);
+        assert_eq!(DefaultTokenizer::classify("[-1]"), Segment::Index);
+        assert_eq!(DefaultTokenizer::classify("foo"), Segment::Key);
+        assert_eq!(DefaultTokenizer::classify("0"), Segment::Key);
+        assert_eq!(DefaultTokenizer::classify("01"), Segment::Key);
+        assert_eq!(DefaultTokenizer::classify(r#"["0"]"#), Segment::Key);
+        assert_eq!(DefaultTokenizer::classify("[x]"), Segment::Key);
+        assert_eq!(DefaultTokenizer::classify("*"), Segment::Key);
+
+        assert_eq!(SlashTokenizer::classify("[0]"), Segment::Key);
+        assert_eq!(SlashTokenizer::classify("foo"), Segment::Key);
+        assert_eq!(SlashTokenizer::classify("0"), Segment::Index);
+        assert_eq!(SlashTokenizer::classify("01"), Segment::Index);
+        assert_eq!(SlashTokenizer::classify("-1"), Segment::Index);
+        assert_eq!(SlashTokenizer::classify(""), Segment::Key);
+
+        assert_eq!(
+            OneBasedTokenizer::<SlashTokenizer>::classify("01"),
+            Segment::Index
+        );
+
+        // every segment of a query, without any document
+        let found = DefaultTokenizer::segments("users.[0].name")
+            .map(|segment| segment.map(DefaultTokenizer::classify))
+            .collect::<Result<Vec<_>, _>>();
+
+        assert_eq!(found, Ok(vec![Segment::Key, Segment::Index, Segment::Key]));
+    }
+
+    #[test]
+    fn test_byte_array() {
+        let data = ByteArray::from(&b"\x89PNG"[..]);
+
+        assert_eq!(data.query::<DefaultTokenizer>("[0]"), Ok(0x89));
+        assert_eq!(data.query::<DefaultTokenizer>("[3]"), Ok(b'G'));
+        assert_eq!(data.query::<DefaultTokenizer>("[-1]"), Ok(b'G'));
+        assert_eq!(data.query::<SlashTokenizer>("/1"), Ok(b'P'));
+
+        assert_eq!(
+            data.query::<DefaultTokenizer>("[4]"),
+            Err(Error::IndexNotExist { index: 4, len: 4 })
+        );
+        assert_eq!(data.query::<DefaultTokenizer>("[4]:-0"), Ok(0));
+        assert_eq!(
+            data.query::<DefaultTokenizer>("magic"),
+            Err(Error::ExpectedIndex {
+                segment: String::from("magic")
+            })
+        );
+        assert!(data.query::<DefaultTokenizer>("[0].[0]").is_err());
+
+        assert_eq!(data.children().len(), 4);
+        assert_eq!(data.into_query::<DefaultTokenizer>("[2]"), Ok(b'N'));
+    }
+
+    #[test]
+    fn test_set_many() {
+        let mut data = dict! { "users" => array![dict! { "name" => "alice" }] };
+
+        // parents that can't be created: a missing array element and a scalar
+        let found = data.set_many::<DefaultTokenizer>(&[
+            ("config.debug", Value::from(true)),
+            ("users.[1].name", Value::from("bob")),
+            ("config.level", Value::from(3)),
+            ("users.[0].name.first", Value::from("alice")),
+        ]);
+
+        assert_eq!(
+            found,
+            Err(vec![
+                (1, Error::IndexNotExist { index: 1, len: 1 }),
+                (
+                    3,
+                    Error::NotTraversable {
+                        path: String::from("first"),
+                        kind_hint: "scalar"
+                    }
+                ),
+            ])
+        );
+        assert_eq!(
+            lookup::<_, _, DefaultTokenizer>(&data, "users.[0].name"),
+            Ok(Value::from("alice"))
+        );
+
+        // missing dictionaries are created, so they never fail an update
+        let found = data.set_many::<DefaultTokenizer>(&[("meta.owner.name", Value::from("ops"))]);
+
+        assert_eq!(found, Ok(()));
+        assert_eq!(
+            lookup::<_, _, DefaultTokenizer>(&data, "meta"),
+            Ok(dict! { "owner" => dict! { "name" => "ops" } })
+        );
+
+        // updates around the failing one are still applied
+        assert_eq!(
+            lookup::<_, _, DefaultTokenizer>(&data, "config"),
+            Ok(dict! { "debug" => true, "level" => 3 })
+        );
+
+        // later updates see what the earlier ones created
+        let found = data.set_many::<DefaultTokenizer>(&[
+            ("users.[0].address", Value::dict()),
+            ("users.[0].address.city", Value::from("Jakarta")),
+        ]);
+
+        assert_eq!(found, Ok(()));
+        assert_eq!(
+            lookup::<_, _, DefaultTokenizer>(&data, "users.[0].address.city"),
+            Ok(Value::from("Jakarta"))
+        );
+        assert_eq!(data.set_many::<DefaultTokenizer>(&[]), Ok(()));
+    }
+
+    #[test]
+    fn test_tokenizer_peek_kind() {
+        assert_eq!(DefaultTokenizer::peek_kind("[1"), Some(QueryKind::Array));
+        assert_eq!(DefaultTokenizer::peek_kind("["), Some(QueryKind::Array));
+        assert_eq!(
+            DefaultTokenizer::peek_kind("[1].abc"),
+            Some(QueryKind::Array)
+        );
+        assert_eq!(
+            DefaultTokenizer::peek_kind("abc"),
+            Some(QueryKind::Dictionary)
+        );
+        assert_eq!(
+            DefaultTokenizer::peek_kind("abc.[1"),
+            Some(QueryKind::Dictionary)
+        );
+        assert_eq!(
+            DefaultTokenizer::peek_kind(r#"["a."#),
+            Some(QueryKind::Dictionary)
+        );
+        assert_eq!(DefaultTokenizer::peek_kind(""), None);
+
+        assert_eq!(SlashTokenizer::peek_kind("/1"), Some(QueryKind::Array));
+        assert_eq!(SlashTokenizer::peek_kind("1"), Some(QueryKind::Array));
+        assert_eq!(
+            SlashTokenizer::peek_kind("/abc"),
+            Some(QueryKind::Dictionary)
+        );
+        assert_eq!(
+            SlashTokenizer::peek_kind("abc"),
+            Some(QueryKind::Dictionary)
+        );
+        assert_eq!(SlashTokenizer::peek_kind("[1"), Some(QueryKind::Dictionary));
+        assert_eq!(SlashTokenizer::peek_kind("/"), None);
+        assert_eq!(SlashTokenizer::peek_kind(""), None);
+
+        assert_eq!(
+            OneBasedTokenizer::<DefaultTokenizer>::peek_kind("[1"),
+            Some(QueryKind::Array)
+        );
+    }
+
+    #[cfg(feature = "serde_json")]
+    #[test]
+    fn test_serde_json() {
+        let data = serde_json::json!({
+            "users": [{ "name": "alice", "manager": null, "age": 30 }]
+        });
+
+        assert_eq!(
+            lookup::<_, _, DefaultTokenizer>(&data, "users.[0].name"),
+            Ok(serde_json::json!("alice"))
+        );
+        assert_eq!(
+            lookup::<_, _, SlashTokenizer>(&data, "/users/0/@keys"),
+            Ok(serde_json::json!(["age", "manager", "name"]))
+        );
+
+        let found = lookup::<_, _, DefaultTokenizer>(&data, "users.[0].manager.name");
+        assert_eq!(
+            found,
+            Err(Error::NotTraversable {
+                path: String::from("name"),
+                kind_hint: "null"
+            })
+        );
+        assert_eq!(
+            found.unwrap_err().to_string(),
+            r#"can't resolve "name" on a null"#
+        );
+
+        let found = lookup::<_, _, DefaultTokenizer>(&data, "users.[0].name.first");
+        assert_eq!(
+            found.unwrap_err().to_string(),
+            r#"can't resolve "first" on a string"#
+        );
+
+        let found = data.query_checked::<DefaultTokenizer>("users.[0].age.value");
+        assert_eq!(
+            found.unwrap_err().to_string(),
+            r#"can't resolve "value" on a number"#
+        );
+
+        assert_eq!(
+            lookup::<_, _, DefaultTokenizer>(&data, "users.[0].missing:-null"),
+            Ok(serde_json::Value::Null)
+        );
+    }
+
+    #[test]
+    fn test_empty_root() {
+        let empty_array = Value::Array(vec![]);
+        let empty_dict = Value::dict();
+
+        let index_missing = Err(Error::IndexNotExist { index: 0, len: 0 });
+        let key_missing = Err(Error::key_not_exist("foo"));
+
+        assert_eq!(empty_array.query::<DefaultTokenizer>("[0]"), index_missing);
+        assert_eq!(empty_array.query::<SlashTokenizer>("/0"), index_missing);
+        assert_eq!(empty_dict.query::<DefaultTokenizer>("foo"), key_missing);
+        assert_eq!(empty_dict.query::<SlashTokenizer>("/foo"), key_missing);
+
+        // deeper paths fail on the first missing segment
+        assert_eq!(
+            empty_array.query::<DefaultTokenizer>("[0].foo"),
+            index_missing
+        );
+        assert_eq!(empty_array.query::<SlashTokenizer>("/0/foo"), index_missing);
+        assert_eq!(empty_dict.query::<DefaultTokenizer>("foo.[0]"), key_missing);
+        assert_eq!(empty_dict.query::<SlashTokenizer>("/foo/0"), key_missing);
+
+        // same through the consuming and checked traversals
+        assert_eq!(
+            empty_array.clone().into_query::<SlashTokenizer>("/0"),
+            index_missing
+        );
+        assert_eq!(
+            empty_dict.clone().into_query::<DefaultTokenizer>("foo"),
+            key_missing
+        );
+        assert_eq!(
+            empty_array.query_checked::<DefaultTokenizer>("[0]"),
+            index_missing
+        );
+        assert_eq!(
+            empty_dict.query_checked::<SlashTokenizer>("/foo"),
+            key_missing
+        );
+
+        // a root query is the empty container itself
+        assert_eq!(
+            empty_array.query::<DefaultTokenizer>(""),
+            Ok(Value::Array(vec![]))
+        );
+        assert_eq!(
+            empty_array.query::<SlashTokenizer>("/"),
+            Ok(Value::Array(vec![]))
+        );
+        assert_eq!(empty_dict.query::<DefaultTokenizer>(""), Ok(Value::dict()));
+        assert_eq!(empty_dict.query::<SlashTokenizer>(""), Ok(Value::dict()));
+    }
+
+    #[test]
+    fn test_fn_dict() {
+        std::env::set_var("QUERABLE_TEST_FN_DICT", "enabled");
+
+        let env = FnDict::new(|key: &str| std::env::var(key).ok().map(Value::from));
+
+        assert_eq!(
+            lookup::<_, _, DefaultTokenizer>(&env, "QUERABLE_TEST_FN_DICT"),
+            Ok(Value::from("enabled"))
+        );
+        assert_eq!(
+            lookup::<_, _, DefaultTokenizer>(&env, "QUERABLE_TEST_FN_DICT_MISSING"),
+            Err(Error::key_not_exist("QUERABLE_TEST_FN_DICT_MISSING"))
+        );
+        assert_eq!(
+            lookup::<_, _, DefaultTokenizer>(&env, "[0]"),
+            Err(Error::TypeError(
+                String::from("[0]"),
+                QueryKind::Array,
+                QueryKind::Dictionary
+            ))
+        );
+        assert_eq!(env.query_kind(), Some(QueryKind::Dictionary));
+        assert!(env.children().is_empty());
+
+        // every level is a separate closure-backed dictionary
+        type Getter = Box<dyn Fn(&str) -> Option<Lazy>>;
+
+        #[derive(Clone)]
+        enum Lazy {
+            Text(String),
+            Dict(Rc<FnDict<Getter>>),
+        }
+
+        impl Queryable for Lazy {
+            type Output = Lazy;
+
+            fn query_kind(&self) -> Option<QueryKind> {
+                match self {
+                    Lazy::Text(_) => None,
+                    Lazy::Dict(d) => d.query_kind(),
+                }
+            }
+
+            fn query_dict(&self, path: &str) -> Result<Lazy, Error> {
+                match self {
+                    Lazy::Text(_) => Err(Error::unknown_type(String::from(path))),
+                    Lazy::Dict(d) => d.query_dict(path),
+                }
+            }
+
+            fn query_array(&self, idx: usize) -> Result<Lazy, Error> {
+                match self {
+                    Lazy::Text(_) => Err(Error::unknown_type(format!("[{}]", idx))),
+                    Lazy::Dict(d) => d.query_array(idx),
+                }
+            }
+
+            fn query_values(&self) -> Result<Lazy, Error> {
+                match self {
+                    Lazy::Text(_) => Err(Error::unknown_type(VALUES_SEGMENT)),
+                    Lazy::Dict(d) => d.query_values(),
+                }
+            }
+
+            fn children(&self) -> Vec<(Step<'_>, &Lazy)> {
+                Vec::new()
+            }
+        }
+
+        let calls = Rc::new(Cell::new(0));
+
+        let database = {
+            let calls = calls.clone();
+            move |key: &str| {
+                calls.set(calls.get() + 1);
+                match key {
+                    "host" => Some(Lazy::Text(String::from("localhost"))),
+                    _ => None,
+                }
+            }
+        };
+
+        let config = FnDict::new(move |key: &str| match key {
+            "database" => Some(Lazy::Dict(Rc::new(FnDict::new(Box::new(database.clone()))))),
+            _ => None,
+        });
+
+        let found = lookup::<_, _, DefaultTokenizer>(&config, "database.host");
+        assert!(matches!(found, Ok(Lazy::Text(ref host)) if host == "localhost"));
+        assert_eq!(calls.get(), 1);
+
+        let found = lookup::<_, _, SlashTokenizer>(&config, "/database/port");
+        assert!(matches!(found, Err(Error::KeyNotExist(ref key)) if key == "port"));
+        assert_eq!(calls.get(), 2);
+
+        let found = lookup::<_, _, DefaultTokenizer>(&config, "cache.host");
+        assert!(matches!(found, Err(Error::KeyNotExist(ref key)) if key == "cache"));
+        assert_eq!(calls.get(), 2);
+    }
+
+    #[cfg(feature = "serde_json")]
+    #[test]
+    fn test_serde_json_bare_containers() {
+        let map = match serde_json::json!({ "id": 7, "tags": ["a", "b"] }) {
+            serde_json::Value::Object(map) => map,
+            _ => unreachable!(),
+        };
+
+        assert_eq!(map.query_kind(), Some(QueryKind::Dictionary));
+        assert_eq!(
+            lookup::<_, _, DefaultTokenizer>(&map, "tags.[1]"),
+            Ok(serde_json::json!("b"))
+        );
+        assert_eq!(
+            lookup::<_, _, SlashTokenizer>(&map, "/id"),
+            Ok(serde_json::json!(7))
+        );
+        assert_eq!(
+            lookup::<_, _, DefaultTokenizer>(&map, "missing"),
+            Err(Error::key_not_exist("missing"))
+        );
+        assert_eq!(
+            lookup::<_, _, DefaultTokenizer>(&map, "[0]"),
+            Err(Error::TypeError(
+                String::from("[0]"),
+                QueryKind::Array,
+                QueryKind::Dictionary
+            ))
+        );
+        assert_eq!(
+            lookup::<_, _, DefaultTokenizer>(&map, KEYS_SEGMENT),
+            Ok(serde_json::json!(["id", "tags"]))
+        );
+        assert_eq!(
+            lookup::<_, _, DefaultTokenizer>(&map, ""),
+            Ok(serde_json::Value::Object(map.clone()))
+        );
+        assert_eq!(
+            map.into_query::<DefaultTokenizer>("tags.[0]"),
+            Ok(serde_json::json!("a"))
+        );
+
+        let list = vec![serde_json::json!({ "id": 1 }), serde_json::json!(null)];
+
+        assert_eq!(list.query_kind(), Some(QueryKind::Array));
+        assert_eq!(
+            lookup::<_, _, DefaultTokenizer>(&list, "[0].id"),
+            Ok(serde_json::json!(1))
+        );
+        assert_eq!(
+            lookup::<_, _, SlashTokenizer>(&list, "/-1"),
+            Ok(serde_json::Value::Null)
+        );
+        assert_eq!(
+            lookup::<_, _, DefaultTokenizer>(&list, "[2]"),
+            Err(Error::IndexNotExist { index: 2, len: 2 })
+        );
+        assert_eq!(
+            lookup::<_, _, DefaultTokenizer>(&list, "[1].id"),
+            Err(Error::NotTraversable {
+                path: String::from("id"),
+                kind_hint: "null"
+            })
+        );
+        assert_eq!(
+            list.into_query::<DefaultTokenizer>("[0]"),
+            Ok(serde_json::json!({ "id": 1 }))
+        );
+    }
+
+    #[test]
+    fn test_query_entry_ci() {
+        let data = dict! {
+            "host" => "localhost",
+            "Servers" => array![dict! { "Name" => "a" }, dict! { "Name" => "b" }],
+            "mode" => "lower",
+            "MODE" => "upper",
+            "Mode" => "title"
+        };
+
+        assert_eq!(
+            data.query_entry_ci::<DefaultTokenizer>("HOST"),
+            Ok((String::from("host"), Value::from("localhost")))
+        );
+        assert_eq!(
+            data.query_entry_ci::<DefaultTokenizer>("host"),
+            Ok((String::from("host"), Value::from("localhost")))
+        );
+
+        // every dictionary segment matches regardless of case
+        assert_eq!(
+            data.query_entry_ci::<DefaultTokenizer>("servers.[1].NAME"),
+            Ok((String::from("Name"), Value::from("b")))
+        );
+        assert_eq!(
+            data.query_entry_ci::<SlashTokenizer>("/SERVERS/-1"),
+            Ok((String::from("1"), dict! { "Name" => "b" }))
+        );
+
+        // exact match first, otherwise the smallest of the keys
+        assert_eq!(
+            data.query_entry_ci::<DefaultTokenizer>("Mode"),
+            Ok((String::from("Mode"), Value::from("title")))
+        );
+        assert_eq!(
+            data.query_entry_ci::<DefaultTokenizer>("mODE"),
+            Ok((String::from("MODE"), Value::from("upper")))
+        );
+
+        assert_eq!(
+            data.query_entry_ci::<DefaultTokenizer>("port"),
+            Err(Error::key_not_exist("port"))
+        );
+        assert_eq!(
+            data.query_entry_ci::<DefaultTokenizer>("servers.[2]"),
+            Err(Error::IndexNotExist { index: 2, len: 2 })
+        );
+        assert_eq!(
+            data.query_entry_ci::<DefaultTokenizer>("host.name"),
+            Err(Error::NotTraversable {
+                path: String::from("name"),
+                kind_hint: "scalar"
+            })
+        );
+        assert_eq!(
+            data.query_entry_ci::<DefaultTokenizer>(""),
+            Err(Error::KeyError(KeyError::EmptyKey))
+        );
+
+        // keys that can't be listed still match exactly
+        let lookup = FnDict::new(|key: &str| match key {
+            "host" => Some(Value::from("h")),
+            _ => None,
+        });
+
+        assert_eq!(
+            lookup.query_entry_ci::<DefaultTokenizer>("host"),
+            Ok((String::from("host"), Value::from("h")))
+        );
+        assert_eq!(
+            lookup.query_entry_ci::<DefaultTokenizer>("HOST"),
+            Err(Error::key_not_exist("HOST"))
+        );
+    }
+
+    // same lookups as above, through the exported `Value`
+    #[cfg(feature = "value")]
+    mod value {
+        use crate::{
+            default::{DefaultTokenizer, SlashTokenizer},
+            error::{Error, KeyError},
+            kind::QueryKind,
+            lookup,
+            types::{Queryable, QueryableMut},
+            value::Value,
+        };
+
+        #[test]
+        fn test_empty_root() {
+            let index_missing = Err(Error::IndexNotExist { index: 0, len: 0 });
+            let key_missing = Err(Error::key_not_exist("foo"));
+
+            for (array_path, dict_path) in [("[0]", "foo"), ("[0].foo", "foo.[0]")].iter() {
+                assert_eq!(
+                    lookup::<_, _, DefaultTokenizer>(&crate::array![], *array_path),
+                    index_missing
+                );
+                assert_eq!(
+                    lookup::<_, _, DefaultTokenizer>(&Value::dict(), *dict_path),
+                    key_missing
+                );
+            }
+
+            assert_eq!(
+                lookup::<_, _, SlashTokenizer>(&crate::array![], "/0"),
+                index_missing
+            );
+            assert_eq!(
+                lookup::<_, _, SlashTokenizer>(&Value::dict(), "/foo"),
+                key_missing
+            );
+
+            assert_eq!(
+                lookup::<_, _, SlashTokenizer>(&crate::array![], "/"),
+                Ok(crate::array![])
+            );
+            assert_eq!(
+                lookup::<_, _, DefaultTokenizer>(&Value::dict(), ""),
+                Ok(Value::dict())
+            );
+        }
+
+        #[test]
+        fn test_macro_rule_value() {
+            assert_eq!(crate::dict! {}, Value::dict());
+            assert_eq!(crate::array![], Value::Array(vec![]));
+            assert_eq!(
+                crate::array![1, 3.2, "test", true],
+                Value::Array(vec![
+                    Value::integer(1),
+                    Value::double(3.2),
+                    Value::string("test"),
+                    Value::bool(true),
+                ])
+            );
+        }
+
+        #[test]
+        fn test_lookup_value() {
+            let data = crate::array![crate::dict! {
+                "id" => 12,
+                "name" => "alice",
+                "tags" => crate::array!["a", "b"],
+            }];
+
+            assert_eq!(
+                lookup::<_, _, DefaultTokenizer>(&data, "[0].id"),
+                Ok(Value::integer(12))
+            );
+            assert_eq!(
+                lookup::<_, _, SlashTokenizer>(&data, "/0/tags/-1"),
+                Ok(Value::string("b"))
+            );
+            assert_eq!(
+                lookup::<_, _, DefaultTokenizer>(&data, "[0].name.[0]"),
+                Ok(Value::string("a"))
+            );
+            assert_eq!(
+                lookup::<_, _, DefaultTokenizer>(&data, "[1]"),
+                Err(Error::IndexNotExist { index: 1, len: 1 })
+            );
+            assert_eq!(
+                lookup::<_, _, DefaultTokenizer>(&data, "[0].tags.x"),
+                Err(Error::ExpectedIndex {
+                    segment: String::from("x")
+                })
+            );
+            assert_eq!(
+                lookup::<_, _, DefaultTokenizer>(&data, "[0].missing"),
+                Err(Error::key_not_exist("missing"))
+            );
+            assert_eq!(
+                lookup::<_, _, DefaultTokenizer>(&data, "[0]..id"),
+                Err(Error::KeyError(KeyError::EmptySegment {
+                    query: String::from("[0]..id"),
+                    position: 4
+                }))
+            );
+            assert_eq!(
+                data.query_kinded::<DefaultTokenizer>("[0].tags")
+                    .map(|(kind, _)| kind),
+                Ok(Some(QueryKind::Array))
+            );
+        }
+
+        #[test]
+        fn test_value_accessors() {
+            let data = crate::dict! { "id" => 12, "name" => "alice", "active" => true };
+
+            let field = |path| lookup::<_, _, DefaultTokenizer>(&data, path).unwrap();
+
+            assert_eq!(field("id").as_i64(), Some(12));
+            assert_eq!(field("name").as_str(), Some("alice"));
+            assert_eq!(field("active").as_bool(), Some(true));
+            assert_eq!(field("name").as_i64(), None);
+            assert_eq!(Value::double(1.0).as_i64(), None);
+        }
+
+        #[test]
+        fn test_value_mut() {
+            let mut data = Value::dict();
+
+            *data.entry::<DefaultTokenizer>("a.b").unwrap() = crate::array![1];
+            data.push::<DefaultTokenizer>("a.b", Value::integer(2))
+                .unwrap();
+
+            assert_eq!(
+                lookup::<_, _, DefaultTokenizer>(&data, "a.b"),
+                Ok(crate::array![1, 2])
+            );
+            assert_eq!(
+                data.push::<DefaultTokenizer>("a", Value::integer(3)),
+                Err(Error::TypeError(
+                    String::from("a"),
+                    QueryKind::Array,
+                    QueryKind::Dictionary
+                ))
+            );
+        }
+    }
 }
@@ -0,0 +1,98 @@
+//!
+//! Deep merge of [QueryableMut](QueryableMut) structures.
+//!
+//! [MergeStrategy](MergeStrategy) decides what happens when both sides of
+//! [QueryableMut::merge](QueryableMut::merge) have a dictionary or an array
+//! at the same place, e.g. for layering config overlays.
+//!
+use crate::{error::Error, kind::QueryKind, step::Step, types::QueryableMut};
+
+///
+/// How two arrays at the same place are merged.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArrayMerge {
+    /// Elements of the other array are appended.
+    Concat,
+    /// The other array replaces the array.
+    Replace,
+}
+
+///
+/// How two dictionaries at the same place are merged.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DictMerge {
+    /// Keys of both sides are merged recursively.
+    Deep,
+    /// Keys of the other dictionary replace the keys with the same name.
+    Shallow,
+}
+
+///
+/// Strategy of [QueryableMut::merge](QueryableMut::merge), a deep merge
+/// that concatenates arrays by default.
+///
+/// Anything else (scalars, or a dictionary against an array) is always
+/// replaced by the other side.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MergeStrategy {
+    pub arrays: ArrayMerge,
+    pub dicts: DictMerge,
+}
+
+impl Default for MergeStrategy {
+    #[inline]
+    fn default() -> Self {
+        MergeStrategy {
+            arrays: ArrayMerge::Concat,
+            dicts: DictMerge::Deep,
+        }
+    }
+}
+
+// merge `other` into `node` according to `strategy`
+pub(crate) fn merge_into<Q>(node: &mut Q, other: Q, strategy: MergeStrategy) -> Result<(), Error>
+where
+    Q: QueryableMut,
+{
+    match (node.query_kind(), other.query_kind()) {
+        (Some(QueryKind::Dictionary), Some(QueryKind::Dictionary)) => {
+            let keys = other
+                .children()
+                .into_iter()
+                .filter_map(|(step, _)| match step {
+                    Step::Key(key) => Some(key.into_owned()),
+                    _ => None,
+                })
+                .collect::<Vec<_>>();
+
+            for key in keys {
+                let value = other.query_dict(&key)?;
+
+                match (strategy.dicts, node.query_dict_mut(&key)) {
+                    (DictMerge::Deep, Ok(existing)) => merge_into(existing, value, strategy)?,
+                    (_, Ok(existing)) => *existing = value,
+                    (_, Err(Error::KeyNotExist(_))) => *node.dict_entry(&key)? = value,
+                    (_, Err(e)) => return Err(e),
+                }
+            }
+
+            Ok(())
+        }
+        (Some(QueryKind::Array), Some(QueryKind::Array))
+            if strategy.arrays == ArrayMerge::Concat =>
+        {
+            for idx in 0..other.children().len() {
+                node.array_push(other.query_array(idx)?)?;
+            }
+
+            Ok(())
+        }
+        _ => {
+            *node = other;
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,111 @@
+//!
+//! Read-only view over an allow-list of top-level keys.
+//!
+//! [Projected](Projected) hides every top-level key of a dictionary that
+//! isn't explicitly allowed, e.g. for redacting a document before letting
+//! arbitrary queries run against it.
+//!
+use std::collections::HashSet;
+
+use crate::{
+    error::Error,
+    kind::QueryKind,
+    step::Step,
+    types::{Queryable, VALUES_SEGMENT},
+};
+
+///
+/// [Queryable](Queryable) view of `V` where only the allowed top-level
+/// keys could be resolved, any other key is
+/// [Error::KeyNotExist](Error::KeyNotExist) even if it exists in `V`.
+///
+/// - Allowed keys resolve into the plain `V::Output`, everything under
+///   them is visible.
+/// - Array access passes through unchanged.
+/// - [VALUES_SEGMENT](VALUES_SEGMENT) and the root itself (see
+///   [Queryable::query_self](Queryable::query_self)) can't be resolved,
+///   since they would expose the hidden keys.
+///
+pub struct Projected<'a, V> {
+    inner: &'a V,
+    allow: HashSet<&'a str>,
+}
+
+impl<'a, V> Projected<'a, V>
+where
+    V: Queryable,
+{
+    pub fn new<I>(inner: &'a V, keys: I) -> Self
+    where
+        I: IntoIterator<Item = &'a str>,
+    {
+        Projected {
+            inner,
+            allow: keys.into_iter().collect(),
+        }
+    }
+
+    #[inline]
+    fn is_allowed(&self, key: &str) -> bool {
+        self.allow.contains(key)
+    }
+}
+
+impl<'a, V> Queryable for Projected<'a, V>
+where
+    V: Queryable,
+{
+    type Output = V::Output;
+
+    #[inline]
+    fn query_kind(&self) -> Option<QueryKind> {
+        self.inner.query_kind()
+    }
+
+    #[inline]
+    fn from_default_str(text: &str) -> Option<V::Output> {
+        V::from_default_str(text)
+    }
+
+    #[inline]
+    fn index_scalar(&self, idx: usize) -> Option<Result<V::Output, Error>> {
+        self.inner.index_scalar(idx)
+    }
+
+    #[inline]
+    fn scalar_kind(&self) -> &'static str {
+        self.inner.scalar_kind()
+    }
+
+    fn query_dict(&self, path: &str) -> Result<V::Output, Error> {
+        match self.inner.query_kind() {
+            Some(QueryKind::Dictionary) if !self.is_allowed(path) => {
+                Err(Error::key_not_exist(String::from(path)))
+            }
+            _ => self.inner.query_dict(path),
+        }
+    }
+
+    #[inline]
+    fn query_array(&self, idx: usize) -> Result<V::Output, Error> {
+        self.inner.query_array(idx)
+    }
+
+    fn query_values(&self) -> Result<V::Output, Error> {
+        match self.inner.query_kind() {
+            Some(QueryKind::Dictionary) => Err(Error::key_not_exist(VALUES_SEGMENT)),
+            _ => self.inner.query_values(),
+        }
+    }
+
+    fn children(&self) -> Vec<(Step<'_>, &V::Output)> {
+        self.inner
+            .children()
+            .into_iter()
+            .filter(|(step, _)| match step {
+                Step::Key(key) => self.is_allowed(key),
+                _ => true,
+            })
+            .collect()
+    }
+}
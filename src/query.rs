@@ -0,0 +1,219 @@
+//!
+//! Pre-tokenized query.
+//!
+//! [Query](Query) are a path that already being tokenized into
+//! [Step](Step)s, so it could be parsed once and being used for
+//! many lookups through [lookup_steps](crate::lookup_steps).
+//!
+use crate::{
+    default::{DefaultTokenizer, JsonPointerTokenizer, SlashTokenizer},
+    error::Error,
+    step::Step,
+    types::Tokenizer,
+};
+use std::{borrow::Cow, convert::TryFrom, iter::FromIterator, slice, str::FromStr, vec};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Query {
+    steps: Vec<Step<'static>>,
+}
+
+impl Query {
+    ///
+    /// Tokenize `path` into [Step](Step)s by using [Tokenizer](Tokenizer) `T`.
+    ///
+    pub fn parse<T>(path: &str) -> Result<Self, Error>
+    where
+        T: Tokenizer,
+    {
+        let mut steps = Vec::new();
+        let mut rest = Some(path);
+
+        while let Some(current) = rest {
+            let (key, next) = T::dict_parse(current)?;
+
+            if let Some(key) = key {
+                steps.push(T::step_parse(key)?.into_owned());
+            }
+
+            rest = next;
+        }
+
+        Ok(Query { steps })
+    }
+
+    #[inline]
+    pub fn steps(&self) -> &[Step<'static>] {
+        &self.steps
+    }
+
+    ///
+    /// Query without its last step, `None` when there is no parent step.
+    ///
+    pub fn parent(&self) -> Option<Query> {
+        match self.steps.split_last() {
+            Some((_, parent)) if !parent.is_empty() => Some(Query {
+                steps: parent.to_vec(),
+            }),
+            _ => None,
+        }
+    }
+
+    #[inline]
+    pub fn last(&self) -> Option<&Step<'static>> {
+        self.steps.last()
+    }
+}
+
+///
+/// Parse query that might be prefixed by a scheme that selects the tokenizer.
+///
+/// - `default:` for [DefaultTokenizer](DefaultTokenizer)
+/// - `slash:` for [SlashTokenizer](SlashTokenizer)
+/// - `jsonptr:` for [JsonPointerTokenizer](JsonPointerTokenizer)
+///
+/// Query without a scheme are being parsed by [DefaultTokenizer](DefaultTokenizer).
+///
+/// ```rust
+/// use std::convert::TryFrom;
+/// use querable::{query::Query, error::Error};
+///
+/// assert_eq!(Query::try_from("slash:/0/id"), Query::try_from("default:[0].id"));
+/// assert_eq!(Query::try_from("xml:/0"), Err(Error::UnknownScheme(String::from("xml"))));
+/// ```
+///
+impl TryFrom<&str> for Query {
+    type Error = Error;
+
+    fn try_from(query: &str) -> Result<Self, Self::Error> {
+        let scheme = query
+            .find(':')
+            .map(|idx| (&query[..idx], &query[idx + 1..]))
+            // `port:-1` is a key with an inline fallback rather than a scheme
+            .filter(|(scheme, path)| {
+                !scheme.is_empty()
+                    && scheme.chars().all(|c| c.is_ascii_alphanumeric())
+                    && !path.starts_with('-')
+            });
+
+        match scheme {
+            None => Query::parse::<DefaultTokenizer>(query),
+            Some(("default", path)) => Query::parse::<DefaultTokenizer>(path),
+            Some(("slash", path)) => Query::parse::<SlashTokenizer>(path),
+            Some(("jsonptr", path)) => Query::parse::<JsonPointerTokenizer>(path),
+            Some((scheme, _)) => Err(Error::UnknownScheme(String::from(scheme))),
+        }
+    }
+}
+
+///
+/// Same as [Query::try_from](Query::try_from), so a query could be parsed
+/// by [str::parse](str::parse).
+///
+/// ```rust
+/// use querable::query::Query;
+///
+/// let query: Query = "slash:/0/id".parse().unwrap();
+/// ```
+///
+impl FromStr for Query {
+    type Err = Error;
+
+    #[inline]
+    fn from_str(query: &str) -> Result<Self, Self::Err> {
+        Query::try_from(query)
+    }
+}
+
+///
+/// Collect [Step](Step)s into a [Query](Query).
+///
+/// ```rust
+/// use querable::{query::Query, step::Step};
+///
+/// let query: Query = vec![Step::Key("child".into()), Step::Index(0)].into_iter().collect();
+/// let parent: Query = query.into_iter().take(1).collect();
+///
+/// assert_eq!(parent.steps(), &[Step::Key("child".into())]);
+/// ```
+///
+impl<'a> FromIterator<Step<'a>> for Query {
+    fn from_iter<I>(iter: I) -> Self
+    where
+        I: IntoIterator<Item = Step<'a>>,
+    {
+        Query {
+            steps: iter.into_iter().map(Step::into_owned).collect(),
+        }
+    }
+}
+
+impl IntoIterator for Query {
+    type Item = Step<'static>;
+    type IntoIter = vec::IntoIter<Step<'static>>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.steps.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a Query {
+    type Item = &'a Step<'static>;
+    type IntoIter = slice::Iter<'a, Step<'static>>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.steps.iter()
+    }
+}
+
+///
+/// Fluent builder for [Query](Query), so keys that contain separators
+/// don't need any escaping.
+///
+/// ```rust
+/// use querable::{query::QueryBuilder, step::Step};
+///
+/// let query = QueryBuilder::new().key("users").index(0).key("name").build();
+///
+/// assert_eq!(
+///     query.steps(),
+///     &[Step::Key("users".into()), Step::Index(0), Step::Key("name".into())]
+/// );
+/// ```
+///
+#[derive(Debug, Default, Clone)]
+pub struct QueryBuilder {
+    steps: Vec<Step<'static>>,
+}
+
+impl QueryBuilder {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[inline]
+    pub fn key(mut self, key: &str) -> Self {
+        self.steps.push(Step::Key(Cow::Owned(String::from(key))));
+        self
+    }
+
+    #[inline]
+    pub fn index(mut self, idx: usize) -> Self {
+        self.steps.push(Step::Index(idx));
+        self
+    }
+
+    #[inline]
+    pub fn wildcard(mut self) -> Self {
+        self.steps.push(Step::Wildcard);
+        self
+    }
+
+    #[inline]
+    pub fn build(self) -> Query {
+        Query { steps: self.steps }
+    }
+}
@@ -0,0 +1,107 @@
+//!
+//! Bounded cache of parsed [Query](Query).
+//!
+//! Tokenizing the same query strings over and over could be amortized by
+//! keeping the parsed [Query](Query) around, [QueryCache](QueryCache) keeps
+//! the most recently used ones, keyed by the raw query string.
+//!
+use std::marker::PhantomData;
+
+use crate::{error::Error, query::Query, types::Tokenizer};
+
+///
+/// Least-recently-used cache of [Query](Query) parsed by [Tokenizer](Tokenizer) `T`.
+///
+/// Entries are kept in recency order in a small vector, since the cache
+/// is meant for a small set of rotating query strings.
+///
+/// ```rust
+/// use querable::{default::DefaultTokenizer, query_cache::QueryCache};
+///
+/// let mut cache = QueryCache::<DefaultTokenizer>::new(8);
+///
+/// let query = cache.get_or_parse("[0].id").unwrap().clone();
+///
+/// assert_eq!(cache.get_or_parse("[0].id"), Ok(&query));
+/// assert_eq!(cache.parsed(), 1);
+/// ```
+///
+#[derive(Debug)]
+pub struct QueryCache<T> {
+    capacity: usize,
+    // least recently used first
+    entries: Vec<(String, Query)>,
+    parsed: usize,
+    _tokenizer: PhantomData<T>,
+}
+
+impl<T> QueryCache<T>
+where
+    T: Tokenizer,
+{
+    ///
+    /// Cache that holds at most `capacity` queries, at least one query
+    /// is always being kept.
+    ///
+    pub fn new(capacity: usize) -> Self {
+        let capacity = capacity.max(1);
+
+        QueryCache {
+            capacity,
+            entries: Vec::with_capacity(capacity),
+            parsed: 0,
+            _tokenizer: PhantomData,
+        }
+    }
+
+    ///
+    /// Cached [Query](Query) of `path`, parsing it on a miss. The least
+    /// recently used query is evicted when the cache is full.
+    ///
+    pub fn get_or_parse(&mut self, path: &str) -> Result<&Query, Error> {
+        match self.entries.iter().position(|(key, _)| key == path) {
+            Some(idx) => {
+                let entry = self.entries.remove(idx);
+                self.entries.push(entry);
+            }
+            None => {
+                let query = Query::parse::<T>(path)?;
+                self.parsed += 1;
+
+                if self.entries.len() == self.capacity {
+                    self.entries.remove(0);
+                }
+
+                self.entries.push((String::from(path), query));
+            }
+        }
+
+        Ok(self.entries.last().map(|(_, query)| query).unwrap())
+    }
+
+    ///
+    /// Whether `path` is being cached, without touching its recency.
+    ///
+    #[inline]
+    pub fn contains(&self, path: &str) -> bool {
+        self.entries.iter().any(|(key, _)| key == path)
+    }
+
+    ///
+    /// Number of queries being parsed (cache misses) so far.
+    ///
+    #[inline]
+    pub fn parsed(&self) -> usize {
+        self.parsed
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
@@ -0,0 +1,89 @@
+//!
+//! Pre-tokenized path segment.
+//!
+//! A query string is a sequence of segments, each of them either an
+//! array index or a dictionary key. [Step](Step) is that segment once
+//! the tokenizer has done its job, so a path could be built (or parsed)
+//! once and traversed many times without going back to string parsing.
+//!
+use std::{borrow::Cow, fmt, marker::PhantomData};
+
+use crate::types::Tokenizer;
+
+///
+/// One segment of a query path.
+///
+/// - [Step::Index](Step::Index) resolves through
+///   [Queryable::query_array](crate::types::Queryable::query_array).
+///
+/// - [Step::Key](Step::Key) resolves through
+///   [Queryable::query_dict](crate::types::Queryable::query_dict).
+///
+/// - [Step::FromEnd](Step::FromEnd) resolves the n-th element from the end
+///   of an array, `FromEnd(1)` being the last element.
+///
+/// - [Step::Wildcard](Step::Wildcard) matches every child, so it can't be
+///   resolved into a single value.
+///
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Step<'a> {
+    Index(usize),
+    Key(Cow<'a, str>),
+    FromEnd(usize),
+    Wildcard,
+}
+
+impl<'a> Step<'a> {
+    ///
+    /// Detach the step from the query string it was parsed from.
+    ///
+    pub fn into_owned(self) -> Step<'static> {
+        match self {
+            Step::Index(idx) => Step::Index(idx),
+            Step::Key(key) => Step::Key(Cow::Owned(key.into_owned())),
+            Step::FromEnd(n) => Step::FromEnd(n),
+            Step::Wildcard => Step::Wildcard,
+        }
+    }
+}
+
+///
+/// What a single path segment addresses, judged from the query alone,
+/// see [Tokenizer::classify](Tokenizer::classify).
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Segment {
+    Index,
+    Key,
+}
+
+///
+/// [Display](fmt::Display) adapter that renders [Step](Step)s in the
+/// query syntax of [Tokenizer](Tokenizer) `T`, without building a `String`.
+///
+/// ```rust
+/// use querable::{default::DefaultTokenizer, step::{PathDisplay, Step}};
+///
+/// let steps = [Step::Index(0), Step::Key("id".into())];
+///
+/// assert_eq!(format!("{}", PathDisplay::<DefaultTokenizer>::new(&steps)), "[0].id");
+/// ```
+///
+pub struct PathDisplay<'a, T>(pub &'a [Step<'a>], pub PhantomData<T>);
+
+impl<'a, T> PathDisplay<'a, T> {
+    #[inline]
+    pub fn new(steps: &'a [Step<'a>]) -> Self {
+        PathDisplay(steps, PhantomData)
+    }
+}
+
+impl<'a, T> fmt::Display for PathDisplay<'a, T>
+where
+    T: Tokenizer,
+{
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        T::write_path(f, self.0)
+    }
+}
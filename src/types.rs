@@ -5,78 +5,1617 @@
 //! both `query_array` and `query_dict`. So, most of the implementor for
 //! the data structure only need to implement which type of Self ~ QueryKind.
 //!
+use log::trace;
+use std::{borrow::Cow, fmt, marker::PhantomData, slice};
+
 use crate::{
+    diff::{self, Change},
     error::{Error, IndexError, KeyError},
     kind::QueryKind,
+    leaves::Leaves,
+    merge::{self, MergeStrategy},
+    query::Query,
+    step::{Segment, Step},
+    visit::{self, QueryVisitor},
 };
 
-///
-/// Type that represents the return state of [Tokenizer::dict_parse](Tokenizer::dict_parse).
-///
-/// (current, next).
-///
-pub type State<'a> = (Option<&'a str>, Option<&'a str>);
+///
+/// Type that represents the return state of [Tokenizer::dict_parse](Tokenizer::dict_parse).
+///
+/// (current, next).
+///
+pub type State<'a> = (Option<&'a str>, Option<&'a str>);
+
+///
+/// Reserved path segment that resolves into
+/// [Queryable::query_values](Queryable::query_values).
+///
+pub const VALUES_SEGMENT: &str = "@values";
+
+///
+/// Reserved path segment that resolves into
+/// [Queryable::query_keys](Queryable::query_keys), for types that support it.
+///
+pub const KEYS_SEGMENT: &str = "@keys";
+
+///
+/// Reserved path segment that resolves into the current node itself,
+/// through [Queryable::query_self](Queryable::query_self).
+///
+pub const SELF_SEGMENT: &str = "@self";
+
+/// Tokenizer trait.
+///
+/// This trait should be implemented if you need to have custom
+/// tokenizer for parsing array index & dictionary index.
+///
+/// On how you might want to implemented it, you could see
+/// [SlashTokenizer](crate::default::SlashTokenizer) or
+/// [DefaultTokenizer](crate::default::DefaultTokenizer)
+///
+pub trait Tokenizer {
+    /// Parse key passed when [Queryable::query_kind](Queryable::query_kind)
+    /// returns [QueryKind::Array](QueryKind::Array).
+    ///
+    fn index_parse(key: &str) -> Result<usize, IndexError>;
+
+    /// Tokenizing path steps.
+    ///
+    fn dict_parse(key: &str) -> Result<State<'_>, KeyError>;
+
+    /// Concatenate two partial paths, dropping stray delimiters
+    /// between `base` and `relative`.
+    ///
+    fn join_paths(base: &str, relative: &str) -> String;
+
+    /// Whether `path` refers to the root itself, an empty (or whitespace
+    /// only) path by default.
+    ///
+    #[inline]
+    fn is_root(path: &str) -> bool {
+        path.trim().is_empty()
+    }
+
+    /// Classify a single path step (as returned by
+    /// [Tokenizer::dict_parse](Tokenizer::dict_parse)) into a [Step](Step).
+    ///
+    /// By default, any step that could be parsed by
+    /// [Tokenizer::index_parse](Tokenizer::index_parse) are an index,
+    /// otherwise it's a key.
+    ///
+    fn step_parse(key: &str) -> Result<Step<'_>, IndexError> {
+        if key == "*" {
+            return Ok(Step::Wildcard);
+        }
+
+        Ok(Self::index_parse(key)
+            .map(Step::Index)
+            .unwrap_or_else(|_| Step::Key(key.into())))
+    }
+
+    /// Classify a single path step (as returned by
+    /// [Tokenizer::dict_parse](Tokenizer::dict_parse)) as addressing an
+    /// array index or a dictionary key, without any document, e.g. for
+    /// validating a query in a UI.
+    ///
+    /// By default, anything [Tokenizer::step_parse](Tokenizer::step_parse)
+    /// parses as an index (counting from the end included) is
+    /// [Segment::Index](Segment::Index), everything else (wildcards and
+    /// malformed indexes included) is [Segment::Key](Segment::Key).
+    ///
+    fn classify(segment: &str) -> Segment {
+        match Self::step_parse(segment) {
+            Ok(Step::Index(_)) | Ok(Step::FromEnd(_)) => Segment::Index,
+            _ => Segment::Key,
+        }
+    }
+
+    /// Kind of node the next segment of `remaining` would be resolved on,
+    /// without consuming it, e.g. for autocompletion of a query that is
+    /// still being typed. `None` when there is no next segment.
+    ///
+    /// By default, the next segment is split off by
+    /// [Tokenizer::dict_parse](Tokenizer::dict_parse) (or taken as a
+    /// whole when it can't be split yet), it's
+    /// [QueryKind::Array](QueryKind::Array) when
+    /// [Tokenizer::index_parse](Tokenizer::index_parse) accepts it and
+    /// [QueryKind::Dictionary](QueryKind::Dictionary) otherwise.
+    ///
+    fn peek_kind(remaining: &str) -> Option<QueryKind> {
+        let segment = match Self::dict_parse(remaining) {
+            Ok((Some(segment), _)) => segment,
+            _ => remaining,
+        };
+
+        if segment.is_empty() {
+            None
+        } else if Self::index_parse(segment).is_ok() {
+            Some(QueryKind::Array)
+        } else {
+            Some(QueryKind::Dictionary)
+        }
+    }
+
+    /// Whether a segment that parses as an array index (see
+    /// [Tokenizer::index_parse](Tokenizer::index_parse)) could still be
+    /// looked up as a dictionary key, `true` by default.
+    ///
+    /// Otherwise such segment on a dictionary is
+    /// [Error::NumericKeyOnDictionary](Error::NumericKeyOnDictionary).
+    ///
+    #[inline]
+    fn numeric_keys() -> bool {
+        true
+    }
+
+    /// Split an inline fallback off `path`, e.g. `5` of `port:-5`, see
+    /// [Queryable::query](Queryable::query). Splits on the first `:-` by default.
+    ///
+    /// ```rust
+    /// use querable::{types::Tokenizer, default::DefaultTokenizer};
+    ///
+    /// assert_eq!(DefaultTokenizer::default_split("server.port:-8080"), ("server.port", Some("8080")));
+    /// assert_eq!(DefaultTokenizer::default_split("server.port"), ("server.port", None));
+    /// ```
+    ///
+    #[inline]
+    fn default_split(path: &str) -> (&str, Option<&str>) {
+        match path.find(":-") {
+            Some(idx) => (&path[..idx], Some(&path[idx + 2..])),
+            None => (path, None),
+        }
+    }
+
+    /// Split `path` into its leading [Step](Step) and the rest of the query,
+    /// `None` when the leading step is the last one.
+    ///
+    /// ```rust
+    /// use querable::{types::Tokenizer, default::SlashTokenizer, step::Step};
+    ///
+    /// assert_eq!(SlashTokenizer::first("/0/id"), Ok((Step::Index(0), Some("/id"))));
+    /// assert_eq!(SlashTokenizer::first("/id"), Ok((Step::Key("id".into()), None)));
+    /// ```
+    ///
+    fn first(path: &str) -> Result<(Step<'_>, Option<&str>), Error> {
+        match Self::dict_parse(path)? {
+            (Some(key), next) => Ok((Self::step_parse(key)?, next)),
+            (None, _) => Err(Error::KeyError(KeyError::EmptyKey)),
+        }
+    }
+
+    /// Iterate over the raw segments of `path`, as returned by
+    /// [Tokenizer::dict_parse](Tokenizer::dict_parse) one after another.
+    ///
+    /// ```rust
+    /// use querable::{types::Tokenizer, default::DefaultTokenizer};
+    ///
+    /// let segments = DefaultTokenizer::segments("[0].child.id").collect::<Result<Vec<_>, _>>();
+    ///
+    /// assert_eq!(segments, Ok(vec!["[0]", "child", "id"]));
+    /// ```
+    ///
+    #[inline]
+    fn segments(path: &str) -> Segments<'_, Self>
+    where
+        Self: Sized,
+    {
+        Segments {
+            rest: Some(path),
+            _tokenizer: PhantomData,
+        }
+    }
+
+    /// Write a single [Step](Step) the way it's written in a query,
+    /// without any separator.
+    ///
+    fn write_step<W>(out: &mut W, step: &Step<'_>) -> fmt::Result
+    where
+        W: fmt::Write + ?Sized;
+
+    /// Write [Step](Step)s as a full query path.
+    ///
+    fn write_path<W>(out: &mut W, steps: &[Step<'_>]) -> fmt::Result
+    where
+        W: fmt::Write + ?Sized;
+
+    /// Render [Step](Step)s as a full query path.
+    ///
+    fn render(steps: &[Step<'_>]) -> String {
+        let mut out = String::new();
+        // writing into String never fails
+        let _ = Self::write_path(&mut out, steps);
+        out
+    }
+}
+
+///
+/// Parse `key` as an array index while traversing an array.
+///
+/// A segment that doesn't look like an index at all is reported as
+/// [Error::ExpectedIndex](Error::ExpectedIndex) instead of leaking the
+/// tokenizer's parse error.
+///
+fn expect_index<T>(key: &str) -> Result<usize, Error>
+where
+    T: Tokenizer,
+{
+    T::index_parse(key).map_err(|e| match e {
+        IndexError::IntError { .. } | IndexError::ParseError(_) => Error::ExpectedIndex {
+            segment: String::from(key),
+        },
+        e => Error::IndexError(e),
+    })
+}
+
+///
+/// Iterator of the raw segments of a query, returned by
+/// [Tokenizer::segments](Tokenizer::segments).
+///
+/// The iteration stops after the first [KeyError](KeyError).
+///
+pub struct Segments<'a, T> {
+    rest: Option<&'a str>,
+    _tokenizer: PhantomData<T>,
+}
+
+impl<'a, T> Iterator for Segments<'a, T>
+where
+    T: Tokenizer,
+{
+    type Item = Result<&'a str, KeyError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            match T::dict_parse(self.rest.take()?) {
+                Ok((Some(key), next)) => {
+                    self.rest = next;
+                    return Some(Ok(key));
+                }
+                Ok((None, next)) => self.rest = next,
+                Err(e) => return Some(Err(e)),
+            }
+        }
+    }
+}
+
+// resolve a non-root `path` segment by segment, `coerce` treats a scalar
+// as a one-element array (see `Queryable::query_coerced`)
+fn traverse<Q, T>(node: &Q, path: &str, coerce: bool) -> Result<Q::Output, Error>
+where
+    Q: Queryable,
+    T: Tokenizer,
+{
+    descend::<Q, T>(node, path, coerce).map_err(|e| match e {
+        // tokenizers only see the rest of the query, re-anchor the empty
+        // segment into `path` so the top-most call reports the whole query
+        Error::KeyError(KeyError::EmptySegment { query, position })
+            if path.ends_with(query.as_str()) =>
+        {
+            Error::KeyError(KeyError::EmptySegment {
+                position: position + path.len() - query.len(),
+                query: String::from(path),
+            })
+        }
+        e => e,
+    })
+}
+
+fn descend<Q, T>(node: &Q, path: &str, coerce: bool) -> Result<Q::Output, Error>
+where
+    Q: Queryable,
+    T: Tokenizer,
+{
+    let tokens = T::dict_parse(path)?;
+
+    if let (Some(SELF_SEGMENT), next) = tokens {
+        return match next {
+            Some(next) => traverse::<Q, T>(node, next, coerce),
+            None => node.query_self(),
+        };
+    }
+
+    if let (Some(VALUES_SEGMENT), next) = tokens {
+        let values = node.query_values()?;
+
+        return match next {
+            Some(next) => traverse::<Q::Output, T>(&values, next, coerce),
+            None => Ok(values),
+        };
+    }
+
+    if let (Some(KEYS_SEGMENT), next) = tokens {
+        if let Some(keys) = node.query_keys() {
+            let keys = keys?;
+
+            return match next {
+                Some(next) => traverse::<Q::Output, T>(&keys, next, coerce),
+                None => Ok(keys),
+            };
+        }
+    }
+
+    let (found, next) = match (node.query_kind(), tokens) {
+        (Some(QueryKind::Dictionary), (Some(key), next)) => {
+            let found = dict_key::<T>(key)
+                .and_then(|dict_key| node.query_dict(&dict_key))
+                .map_err(|e| index_on_dict::<T>(key, e));
+            trace_segment(key, Some(QueryKind::Dictionary), &found);
+            (found, next)
+        }
+        (Some(QueryKind::Array), (Some(key), next)) => {
+            let index = array_index::<Q, T>(node, key)?;
+            let found = node.query_array(index);
+            trace_segment(key, Some(QueryKind::Array), &found);
+
+            match next {
+                Some(_) => (
+                    found.map_err(|e| match e {
+                        e @ Error::IndexNotExist { .. } => e,
+                        _ => Error::IndexNotExist {
+                            index,
+                            len: node.children().len(),
+                        },
+                    }),
+                    next,
+                ),
+                // base case
+                None => (found, next),
+            }
+        }
+        (Some(kind), _) => return Err(Error::EmptyPath(kind)),
+        (None, (key, next)) => {
+            let index = key.and_then(|key| T::index_parse(key).ok());
+
+            let scalar = match index {
+                // scalar as a one-element array
+                Some(0) if coerce => Some(node.query_self()),
+                // opt-in indexing into scalar, e.g characters of a string
+                Some(index) => node.index_scalar(index),
+                None => None,
+            };
+
+            match scalar {
+                Some(found) => {
+                    trace_segment(key.unwrap_or_default(), None, &found);
+                    (found, next)
+                }
+                None => {
+                    let found = Err(not_traversable(node, String::from(path)));
+                    trace_segment(key.unwrap_or_default(), None, &found);
+                    return found;
+                }
+            }
+        }
+    };
+
+    match next {
+        Some(next) => found.and_then(move |child| traverse::<Q::Output, T>(&child, next, coerce)),
+        // base case
+        None => found,
+    }
+}
+
+// dictionary key of a path segment, as classified by the tokenizer,
+// e.g `a.b` of a quoted `["a.b"]`. segments that parse as an index are
+// rejected for tokenizers without numeric keys
+#[inline]
+fn dict_key<T>(key: &str) -> Result<Cow<'_, str>, Error>
+where
+    T: Tokenizer,
+{
+    match T::step_parse(key) {
+        Ok(Step::Key(key)) => Ok(key),
+        _ if !T::numeric_keys() && T::index_parse(key).is_ok() => {
+            Err(Error::NumericKeyOnDictionary(String::from(key)))
+        }
+        _ => Ok(Cow::Borrowed(key)),
+    }
+}
+
+// missing `key` in a dictionary that the tokenizer classifies as an index
+// (e.g `[0]`) is a type error rather than a missing key
+fn index_on_dict<T>(key: &str, e: Error) -> Error
+where
+    T: Tokenizer,
+{
+    match (e, T::step_parse(key)) {
+        (Error::KeyNotExist(_), Ok(Step::Index(_)))
+        | (Error::KeyNotExist(_), Ok(Step::FromEnd(_))) => {
+            Error::TypeError(String::from(key), QueryKind::Array, QueryKind::Dictionary)
+        }
+        (e, _) => e,
+    }
+}
+
+// index of `key` in array `node`, `key` might count from the end, e.g `[-1]`
+fn array_index<Q, T>(node: &Q, key: &str) -> Result<usize, Error>
+where
+    Q: Queryable,
+    T: Tokenizer,
+{
+    expect_index::<T>(key).or_else(|e| match T::step_parse(key) {
+        Ok(Step::FromEnd(n)) => from_end(node, n),
+        _ => Err(e),
+    })
+}
+
+// index of the n-th element from the end of array `node`
+#[inline]
+fn from_end<Q>(node: &Q, n: usize) -> Result<usize, Error>
+where
+    Q: Queryable,
+{
+    let len = node.children().len();
+    len.checked_sub(n)
+        .ok_or(Error::IndexNotExist { index: n, len })
+}
+
+// resolve `steps` under `node`, the concrete steps being taken (e.g an
+// index instead of a wildcard) are pushed into `taken`
+fn resolve_steps<Q, T>(
+    node: &Q,
+    steps: &[Step],
+    taken: &mut Vec<Step<'static>>,
+) -> Result<Q::Output, Error>
+where
+    Q: Queryable,
+    T: Tokenizer,
+{
+    let (current, next) = match steps.split_first() {
+        Some(pair) => pair,
+        None => return Err(Error::KeyError(KeyError::EmptyKey)),
+    };
+
+    if let Step::Wildcard = current {
+        // first child that resolves the rest of the steps
+        let mut last = Error::UnexpectedWildcard;
+
+        for (step, _) in node.children() {
+            let concrete = std::iter::once(step)
+                .chain(next.iter().cloned())
+                .collect::<Vec<_>>();
+            let mark = taken.len();
+
+            match resolve_steps::<Q, T>(node, &concrete, taken) {
+                Ok(found) => return Ok(found),
+                Err(e) => {
+                    taken.truncate(mark);
+                    last = e;
+                }
+            }
+        }
+
+        return Err(last);
+    }
+
+    let (concrete, child) = resolve_step::<Q, T>(node, current)?;
+    taken.push(concrete);
+
+    if next.is_empty() {
+        Ok(child)
+    } else {
+        resolve_steps::<Q::Output, T>(&child, next, taken)
+    }
+}
+
+// scalar `node` being reached while `path` still has to be resolved
+#[inline]
+pub(crate) fn not_traversable<Q>(node: &Q, path: String) -> Error
+where
+    Q: Queryable,
+{
+    Error::NotTraversable {
+        path,
+        kind_hint: node.scalar_kind(),
+    }
+}
+
+// trace the outcome of resolving a single segment of a query
+#[inline]
+fn trace_segment<V>(segment: &str, kind: Option<QueryKind>, found: &Result<V, Error>) {
+    match found {
+        Ok(_) => trace!("segment {:?} in {:?}: hit", segment, kind),
+        Err(e @ Error::TypeError(..)) => {
+            trace!("segment {:?} in {:?}: type error, {}", segment, kind, e)
+        }
+        Err(e) => trace!("segment {:?} in {:?}: miss, {}", segment, kind, e),
+    }
+}
+
+// resolve `path` into a mutable node, missing keys are being filled
+// with an empty dictionary when `create` is set
+fn traverse_mut<'a, Q, T>(node: &'a mut Q, path: &str, create: bool) -> Result<&'a mut Q, Error>
+where
+    Q: QueryableMut,
+    T: Tokenizer,
+{
+    let (key, next) = T::dict_parse(path)?;
+
+    let child = match (node.query_kind(), key) {
+        (Some(QueryKind::Dictionary), Some(key)) if create => {
+            node.dict_entry(&dict_key::<T>(key)?)?
+        }
+        (Some(QueryKind::Dictionary), Some(key)) => node
+            .query_dict_mut(&dict_key::<T>(key)?)
+            .map_err(|e| index_on_dict::<T>(key, e))?,
+        (Some(QueryKind::Array), Some(key)) => {
+            let index = array_index::<Q, T>(node, key)?;
+            node.query_array_mut(index)?
+        }
+        (Some(kind), None) => return Err(Error::EmptyPath(kind)),
+        (None, _) => return Err(not_traversable(node, String::from(path))),
+    };
+
+    match next {
+        Some(next) => traverse_mut::<Q, T>(child, next, create),
+        // base case
+        None => Ok(child),
+    }
+}
+
+// resolve `path` trying the whole remaining path as a single dictionary
+// key first, then descending a segment and trying again under the child
+fn flat_or_nested<Q, T>(node: &Q, path: &str) -> Result<Q::Output, Error>
+where
+    Q: Queryable,
+    T: Tokenizer,
+{
+    if let Some(QueryKind::Dictionary) = node.query_kind() {
+        match node.query_dict(path) {
+            Err(Error::KeyNotExist(_)) => (),
+            found => return found,
+        }
+    }
+
+    match T::dict_parse(path)? {
+        (Some(key), Some(next)) => {
+            let child = node.query::<T>(key)?;
+            flat_or_nested::<Q::Output, T>(&child, next)
+        }
+        (Some(key), None) => node.query::<T>(key),
+        (None, _) => Err(Error::KeyError(KeyError::EmptyKey)),
+    }
+}
+
+// resolve `path` step by step, checking each step against the kind of the
+// node it's applied to (see `Queryable::query_checked`)
+fn checked<Q, T>(node: &Q, path: &str) -> Result<Q::Output, Error>
+where
+    Q: Queryable,
+    T: Tokenizer,
+{
+    let (key, next) = match T::dict_parse(path)? {
+        (Some(key), next) => (key, next),
+        (None, _) => return Err(Error::KeyError(KeyError::EmptyKey)),
+    };
+
+    let found = match (node.query_kind(), T::step_parse(key)?) {
+        (_, Step::Wildcard) => Err(Error::UnexpectedWildcard),
+        (Some(QueryKind::Dictionary), Step::Key(key)) => node.query_dict(&key),
+        (Some(QueryKind::Array), Step::Index(idx)) => node.query_array(idx),
+        (Some(QueryKind::Array), Step::FromEnd(n)) => {
+            from_end(node, n).and_then(|idx| node.query_array(idx))
+        }
+        (Some(QueryKind::Dictionary), _) => Err(Error::TypeError(
+            String::from(key),
+            QueryKind::Array,
+            QueryKind::Dictionary,
+        )),
+        (Some(QueryKind::Array), Step::Key(_)) => Err(Error::TypeError(
+            String::from(key),
+            QueryKind::Dictionary,
+            QueryKind::Array,
+        )),
+        (None, _) => Err(not_traversable(node, String::from(path))),
+    }?;
+
+    match next {
+        Some(next) => checked::<Q::Output, T>(&found, next),
+        None => Ok(found),
+    }
+}
+
+// resolve a single `segment` of `node` matching dictionary keys
+// case-insensitively, paired with the key (or index) being resolved
+fn entry_ci<Q, T>(node: &Q, segment: &str) -> Result<(String, Q::Output), Error>
+where
+    Q: Queryable,
+    T: Tokenizer,
+{
+    match node.query_kind() {
+        Some(QueryKind::Dictionary) => {
+            let key = dict_key::<T>(segment)?;
+
+            // exact match first, dictionaries that can't list their keys
+            // (e.g `FnDict`) might still have it
+            match node.query_dict(&key) {
+                Err(Error::KeyNotExist(_)) => (),
+                found => return found.map(|value| (key.into_owned(), value)),
+            }
+
+            let mut keys = node
+                .children()
+                .into_iter()
+                .filter_map(|(step, _)| match step {
+                    Step::Key(k) if eq_ignore_case(&k, &key) => Some(k.into_owned()),
+                    _ => None,
+                })
+                .collect::<Vec<_>>();
+            keys.sort();
+
+            let found = match keys.into_iter().next() {
+                Some(found) => found,
+                None => {
+                    let e = Error::key_not_exist(key.into_owned());
+                    return Err(index_on_dict::<T>(segment, e));
+                }
+            };
+
+            node.query_dict(&found).map(|value| (found, value))
+        }
+        Some(QueryKind::Array) => {
+            let index = array_index::<Q, T>(node, segment)?;
+            node.query_array(index)
+                .map(|value| (index.to_string(), value))
+        }
+        None => Err(not_traversable(node, String::from(segment))),
+    }
+}
+
+#[inline]
+fn eq_ignore_case(a: &str, b: &str) -> bool {
+    a.chars()
+        .flat_map(char::to_lowercase)
+        .eq(b.chars().flat_map(char::to_lowercase))
+}
+
+// `node` with every scalar mapped through `f`, see `QueryableMut::map_leaves`
+fn map_node<Q, F>(node: &Q, f: &mut F) -> Q
+where
+    Q: QueryableMut,
+    F: FnMut(&Q) -> Q,
+{
+    match node.query_kind() {
+        Some(QueryKind::Dictionary) => Q::rebuild_dict(
+            node.children()
+                .into_iter()
+                .filter_map(|(step, child)| match step {
+                    Step::Key(key) => Some((key.into_owned(), map_node(child, f))),
+                    _ => None,
+                })
+                .collect(),
+        ),
+        Some(QueryKind::Array) => Q::rebuild_array(
+            node.children()
+                .into_iter()
+                .map(|(_, child)| map_node(child, f))
+                .collect(),
+        ),
+        None => f(node),
+    }
+}
+
+// `step` written the way it's written in a query of `T`
+fn step_segment<T>(step: &Step) -> String
+where
+    T: Tokenizer,
+{
+    let mut segment = String::new();
+    let _ = T::write_step(&mut segment, step);
+    segment
+}
+
+// a single non-wildcard `step` under `node` made concrete the same way
+// `descend` resolves the segment it was parsed from, that is a key of a
+// dictionary (e.g a numeric segment for tokenizers with numeric keys) or
+// an index of an array
+fn step_target<Q, T>(node: &Q, step: &Step) -> Result<Step<'static>, Error>
+where
+    Q: Queryable,
+    T: Tokenizer,
+{
+    match (node.query_kind(), step) {
+        (_, Step::Wildcard) => Err(Error::UnexpectedWildcard),
+        (Some(QueryKind::Dictionary), Step::Key(key)) => Ok(Step::Key(Cow::Owned(key.to_string()))),
+        (Some(QueryKind::Dictionary), step) => {
+            let segment = step_segment::<T>(step);
+            dict_key::<T>(&segment).map(|key| Step::Key(Cow::Owned(key.into_owned())))
+        }
+        (Some(QueryKind::Array), Step::Key(key)) => Err(Error::TypeError(
+            String::from(key.as_ref()),
+            QueryKind::Dictionary,
+            QueryKind::Array,
+        )),
+        (Some(QueryKind::Array), Step::FromEnd(n)) => from_end(node, *n).map(Step::Index),
+        (Some(QueryKind::Array), step) => Ok(step.clone().into_owned()),
+        (None, step) => Err(not_traversable(node, step_segment::<T>(step))),
+    }
+}
+
+// missing key for an index `step` on a dictionary is a type error,
+// see `index_on_dict`
+#[inline]
+fn step_miss<T>(step: &Step, e: Error) -> Error
+where
+    T: Tokenizer,
+{
+    match step {
+        Step::Index(_) | Step::FromEnd(_) => index_on_dict::<T>(&step_segment::<T>(step), e),
+        _ => e,
+    }
+}
+
+// resolve a single non-wildcard `step` under `node`, see `step_target`.
+// the concrete step being taken is returned along with the child
+fn resolve_step<Q, T>(node: &Q, step: &Step) -> Result<(Step<'static>, Q::Output), Error>
+where
+    Q: Queryable,
+    T: Tokenizer,
+{
+    let target = step_target::<Q, T>(node, step)?;
+
+    node.query_steps(slice::from_ref(&target))
+        .map(|found| (target, found))
+        .map_err(|e| step_miss::<T>(step, e))
+}
+
+// mutable version of `resolve_step` over every step of `steps`
+fn resolve_steps_mut<'a, Q, T>(node: &'a mut Q, steps: &[Step]) -> Result<&'a mut Q, Error>
+where
+    Q: QueryableMut,
+    T: Tokenizer,
+{
+    steps.iter().try_fold(node, |node, step| {
+        match step_target::<Q, T>(node, step)? {
+            Step::Key(key) => node.query_dict_mut(&key),
+            Step::Index(idx) => node.query_array_mut(idx),
+            _ => Err(Error::UnexpectedWildcard),
+        }
+        .map_err(|e| step_miss::<T>(step, e))
+    })
+}
+
+// remove the child at a single non-wildcard `step` of `node`, resolved
+// the same way as `resolve_step`
+fn remove_step<Q, T>(node: &mut Q, step: &Step) -> Result<Q, Error>
+where
+    Q: QueryableMut,
+    T: Tokenizer,
+{
+    match step_target::<Q, T>(node, step)? {
+        Step::Key(key) => node.dict_remove(&key),
+        Step::Index(idx) => node.array_remove(idx),
+        _ => Err(Error::UnexpectedWildcard),
+    }
+    .map_err(|e| step_miss::<T>(step, e))
+}
+
+// number of nodes matched by `steps` under `node`
+fn count_steps<Q, T>(node: &Q, steps: &[Step]) -> usize
+where
+    Q: Queryable,
+    T: Tokenizer,
+{
+    let (current, next) = match steps.split_first() {
+        Some(pair) => pair,
+        None => return 1,
+    };
+
+    match current {
+        Step::Wildcard => node
+            .children()
+            .into_iter()
+            .map(|(_, child)| count_steps::<Q::Output, T>(child, next))
+            .sum(),
+        step => match resolve_step::<Q, T>(node, step) {
+            Ok((_, child)) => count_steps::<Q::Output, T>(&child, next),
+            Err(_) => 0,
+        },
+    }
+}
+
+// push nodes matched by `steps` under `node` along with their concrete
+// path (wildcards being expanded) into `out`
+fn collect_matches<Q, T>(
+    node: &Q,
+    steps: &[Step],
+    taken: &mut Vec<Step<'static>>,
+    out: &mut Vec<(String, Q::Output)>,
+) -> Result<(), Error>
+where
+    Q: Queryable,
+    T: Tokenizer,
+{
+    let (current, next) = match steps.split_first() {
+        Some(pair) => pair,
+        None => return Ok(()),
+    };
+
+    if let Step::Wildcard = current {
+        for (step, child) in node.children() {
+            taken.push(step.into_owned());
+
+            if next.is_empty() {
+                out.push((T::render(taken), child.query_self()?));
+            } else {
+                collect_matches::<Q::Output, T>(child, next, taken, out)?;
+            }
+
+            taken.pop();
+        }
+
+        return Ok(());
+    }
+
+    if let Ok((step, child)) = resolve_step::<Q, T>(node, current) {
+        taken.push(step);
+
+        if next.is_empty() {
+            out.push((T::render(taken), child));
+        } else {
+            collect_matches::<Q::Output, T>(&child, next, taken, out)?;
+        }
+
+        taken.pop();
+    }
+
+    Ok(())
+}
+
+// rendered children steps of `node` that starts with `prefix`
+fn candidates<Q, T>(node: &Q, prefix: &str) -> Vec<String>
+where
+    Q: Queryable,
+    T: Tokenizer,
+{
+    let mut found = node
+        .children()
+        .into_iter()
+        .filter_map(|(step, _)| {
+            let mut out = String::new();
+            T::write_step(&mut out, &step).ok()?;
+            Some(out).filter(|rendered| rendered.starts_with(prefix))
+        })
+        .collect::<Vec<_>>();
+
+    found.sort();
+    found
+}
+
+/// Queryable trait.
+///
+/// The main trait that need to be implemented by data structure.
+/// This trait assume that `Self` are sum types or linear? type.
+///
+pub trait Queryable
+where
+    Self: Sized,
+{
+    ///
+    /// Type of the node being resolved by a query.
+    ///
+    /// For recursive sum types (most of the document types), this is `Self`.
+    /// Containers like `[T; N]` resolve into their element type instead.
+    ///
+    type Output: Queryable<Output = Self::Output>;
+
+    ///
+    /// Resolve `path` tokenized by [Tokenizer](Tokenizer) `T`.
+    ///
+    /// A root path (see [Tokenizer::is_root](Tokenizer::is_root)), e.g. `""`,
+    /// resolves into the root itself through
+    /// [Queryable::query_self](Queryable::query_self).
+    ///
+    /// `path` might carry an inline fallback, e.g. `server.port:-8080` (see
+    /// [Tokenizer::default_split](Tokenizer::default_split)). When the path
+    /// misses ([Error::KeyNotExist](Error::KeyNotExist) or
+    /// [Error::IndexNotExist](Error::IndexNotExist)), the fallback is built by
+    /// [Queryable::from_default_str](Queryable::from_default_str) instead.
+    ///
+    fn query<T>(&self, path: &str) -> Result<Self::Output, Error>
+    where
+        T: Tokenizer,
+    {
+        if T::is_root(path) {
+            return self.query_self();
+        }
+
+        match T::default_split(path) {
+            (path, Some(fallback)) => match traverse::<Self, T>(self, path, false) {
+                Err(e @ Error::KeyNotExist(_)) | Err(e @ Error::IndexNotExist { .. }) => {
+                    Self::Output::from_default_str(fallback).ok_or(e)
+                }
+                found => found,
+            },
+            (path, None) => traverse::<Self, T>(self, path, false),
+        }
+    }
+
+    ///
+    /// Same as [Queryable::query](Queryable::query), except a scalar
+    /// (its [Queryable::query_kind](Queryable::query_kind) is `None`) is
+    /// treated as a one-element array, for data that wraps a single value
+    /// in an array inconsistently.
+    ///
+    /// Only index `0` is coerced, it resolves into the scalar itself through
+    /// [Queryable::query_self](Queryable::query_self) and takes precedence
+    /// over [Queryable::index_scalar](Queryable::index_scalar). Other
+    /// segments on a scalar behave the same as in
+    /// [Queryable::query](Queryable::query).
+    ///
+    fn query_coerced<T>(&self, path: &str) -> Result<Self::Output, Error>
+    where
+        T: Tokenizer,
+    {
+        if T::is_root(path) {
+            return self.query_self();
+        }
+
+        traverse::<Self, T>(self, path, true)
+    }
+
+    ///
+    /// Same as [Queryable::query](Queryable::query), except every step is
+    /// checked against the kind of the node it's applied to before
+    /// descending, the same way as [Queryable::query_steps](Queryable::query_steps).
+    ///
+    /// An index step (as classified by
+    /// [Tokenizer::step_parse](Tokenizer::step_parse)) on a dictionary, or a
+    /// key step on an array, is an [Error::TypeError](Error::TypeError) with
+    /// the segment as written in `path`, instead of being looked up as a key.
+    /// Reserved segments (e.g. [VALUES_SEGMENT](VALUES_SEGMENT)) are ordinary
+    /// keys here.
+    ///
+    fn query_checked<T>(&self, path: &str) -> Result<Self::Output, Error>
+    where
+        T: Tokenizer,
+    {
+        if T::is_root(path) {
+            return self.query_self();
+        }
+
+        checked::<Self, T>(self, path)
+    }
+
+    ///
+    /// Resolve `path` matching dictionary keys case-insensitively, e.g.
+    /// `HOST` resolves a stored `host`, and return the key actually
+    /// stored for the last segment together with its value, e.g. to
+    /// normalize user input back to its canonical form.
+    ///
+    /// An exact match always wins, when several keys only differ in case
+    /// the smallest of them is taken. Dictionaries without
+    /// [children](Queryable::children) only match exactly. An index as the last segment returns
+    /// the index it resolves to, e.g. `"4"` for `[-1]` of 5 elements.
+    /// Reserved segments and fallbacks aren't supported, they are
+    /// ordinary keys here.
+    ///
+    fn query_entry_ci<T>(&self, path: &str) -> Result<(String, Self::Output), Error>
+    where
+        T: Tokenizer,
+    {
+        let mut segments = T::segments(path);
+
+        let first = segments.next().ok_or(KeyError::EmptyKey)??;
+        let mut entry = entry_ci::<Self, T>(self, first)?;
+
+        for segment in segments {
+            entry = entry_ci::<Self::Output, T>(&entry.1, segment?)?;
+        }
+
+        Ok(entry)
+    }
+
+    ///
+    /// Resolve `path` where dictionary keys might be stored either flat
+    /// (e.g. a literal `"a.b.c"` key) or nested (`a` → `b` → `c`).
+    ///
+    /// On a dictionary, the whole remaining `path` is looked up as a single
+    /// key through [Queryable::query_dict](Queryable::query_dict) first. Only
+    /// when it's [Error::KeyNotExist](Error::KeyNotExist), the first segment
+    /// is resolved the same way as [Queryable::query](Queryable::query) and
+    /// the rest of the path is resolved under it in the same manner, so
+    /// flat and nested keys could be mixed, e.g. `a` → `"b.c"`.
+    ///
+    /// When both fail, the error of the nested traversal is returned.
+    ///
+    fn query_flat_or_nested<T>(&self, path: &str) -> Result<Self::Output, Error>
+    where
+        T: Tokenizer,
+    {
+        if T::is_root(path) {
+            return self.query_self();
+        }
+
+        flat_or_nested::<Self, T>(self, path)
+    }
+
+    ///
+    /// Resolve [QueryKind](QueryKind) of the node at `path`.
+    ///
+    fn kind_at<T>(&self, path: &str) -> Result<Option<QueryKind>, Error>
+    where
+        T: Tokenizer,
+    {
+        self.query::<T>(path).map(|node| node.query_kind())
+    }
+
+    ///
+    /// Resolve the node at `path` along with its [QueryKind](QueryKind),
+    /// so the result could be matched on right away.
+    ///
+    fn query_kinded<T>(&self, path: &str) -> Result<(Option<QueryKind>, Self::Output), Error>
+    where
+        T: Tokenizer,
+    {
+        self.query::<T>(path).map(|node| (node.query_kind(), node))
+    }
+
+    ///
+    /// Resolve `path` as deep as it goes, returning the deepest resolved
+    /// node, its path rendered by [Tokenizer](Tokenizer) `T` and the error
+    /// of the first segment that failed, if any.
+    ///
+    /// Segments are resolved one at a time the same way as
+    /// [Queryable::query](Queryable::query) resolves them, the rendered path
+    /// is made of the concrete steps being taken (e.g `[2]` for `[-1]` of 3
+    /// elements). Reserved segments and fallbacks aren't supported, they are
+    /// ordinary keys here. When not even the first segment resolves, the
+    /// node is the root itself.
+    ///
+    fn resolve_prefix<T>(&self, path: &str) -> (Self, String, Option<Error>)
+    where
+        T: Tokenizer,
+        Self: Queryable<Output = Self> + Clone,
+    {
+        let mut node = self.clone();
+        let mut steps = Vec::new();
+        let mut rest = Some(path);
+
+        while let Some(remaining) = rest.filter(|remaining| !T::is_root(remaining)) {
+            let found = match T::dict_parse(remaining) {
+                Ok((Some(key), next)) => T::step_parse(key)
+                    .map_err(Error::IndexError)
+                    .and_then(|step| resolve_step::<Self, T>(&node, &step))
+                    .map(|found| (found, next)),
+                Ok((None, _)) => Err(Error::KeyError(KeyError::EmptyKey)),
+                Err(e) => Err(Error::KeyError(e)),
+            };
+
+            match found {
+                Ok(((step, child), next)) => {
+                    steps.push(step);
+                    node = child;
+                    rest = next;
+                }
+                Err(e) => return (node, T::render(&steps), Some(e)),
+            }
+        }
+
+        (node, T::render(&steps), None)
+    }
+
+    ///
+    /// Whether the node at `path` is a scalar (leaf), that is its
+    /// [QueryKind](QueryKind) is `None`.
+    ///
+    #[inline]
+    fn is_scalar_at<T>(&self, path: &str) -> Result<bool, Error>
+    where
+        T: Tokenizer,
+    {
+        self.kind_at::<T>(path).map(|kind| kind.is_none())
+    }
+
+    ///
+    /// Number of nodes `path` resolves to, `path` might contain
+    /// [Step::Wildcard](Step::Wildcard)s.
+    ///
+    /// Missing keys or indexes aren't an error, they just don't match.
+    /// A path without any wildcard is either 0 or 1.
+    ///
+    fn count_matches<T>(&self, path: &str) -> Result<usize, Error>
+    where
+        T: Tokenizer,
+    {
+        let query = Query::parse::<T>(path)?;
+        Ok(count_steps::<Self, T>(self, query.steps()))
+    }
+
+    ///
+    /// Every node `path` resolves to, paired with its concrete path rendered
+    /// by [Tokenizer](Tokenizer) `T`, that is [Step::Wildcard](Step::Wildcard)s
+    /// being expanded into the actual key or index.
+    ///
+    /// Same as [Queryable::count_matches](Queryable::count_matches), missing
+    /// keys or indexes just don't match. Matches are being materialized
+    /// through [Queryable::query_self](Queryable::query_self).
+    ///
+    fn query_all_paths<T>(&self, path: &str) -> Result<Vec<(String, Self::Output)>, Error>
+    where
+        T: Tokenizer,
+    {
+        if T::is_root(path) {
+            return self.query_self().map(|root| vec![(String::new(), root)]);
+        }
+
+        let query = Query::parse::<T>(path)?;
+        let mut found = Vec::new();
+
+        collect_matches::<Self, T>(self, query.steps(), &mut Vec::new(), &mut found)?;
+
+        Ok(found)
+    }
+
+    ///
+    /// Number of direct children of the node at `path`, scalars have none.
+    ///
+    fn len_at<T>(&self, path: &str) -> Result<usize, Error>
+    where
+        T: Tokenizer,
+    {
+        self.query::<T>(path).map(|node| node.children().len())
+    }
+
+    ///
+    /// Resolve the node at `path`, only when it is of [QueryKind](QueryKind) `kind`.
+    ///
+    /// Returns [Error::TypeError](Error::TypeError) when the node is of the other kind,
+    /// or [Error::ScalarTypeError](Error::ScalarTypeError) when it is a scalar.
+    ///
+    fn expect_kind<T>(&self, path: &str, kind: QueryKind) -> Result<Self::Output, Error>
+    where
+        T: Tokenizer,
+    {
+        let node = self.query::<T>(path)?;
+
+        match node.query_kind() {
+            Some(found) if found == kind => Ok(node),
+            Some(found) => Err(Error::TypeError(String::from(path), kind, found)),
+            None => Err(Error::ScalarTypeError(String::from(path), kind)),
+        }
+    }
+
+    ///
+    /// Resolve `field` on every element of the array at `array_path`,
+    /// e.g. `id` of every user in `users`.
+    ///
+    /// Elements where `field` is missing ([Error::KeyNotExist](Error::KeyNotExist)
+    /// or [Error::IndexNotExist](Error::IndexNotExist)) are skipped, see
+    /// [Queryable::project_strict](Queryable::project_strict) to fail instead.
+    /// Other errors are returned right away.
+    ///
+    fn project<T>(&self, array_path: &str, field: &str) -> Result<Vec<Self::Output>, Error>
+    where
+        T: Tokenizer,
+    {
+        let array = self.expect_kind::<T>(array_path, QueryKind::Array)?;
+        let mut found = Vec::new();
+
+        for (_, element) in array.children() {
+            match element.query::<T>(field) {
+                Ok(value) => found.push(value),
+                Err(Error::KeyNotExist(_)) | Err(Error::IndexNotExist { .. }) => (),
+                Err(e) => return Err(e),
+            }
+        }
+
+        Ok(found)
+    }
+
+    ///
+    /// Same as [Queryable::project](Queryable::project), except an element
+    /// where `field` is missing is an error as well.
+    ///
+    fn project_strict<T>(&self, array_path: &str, field: &str) -> Result<Vec<Self::Output>, Error>
+    where
+        T: Tokenizer,
+    {
+        self.expect_kind::<T>(array_path, QueryKind::Array)?
+            .children()
+            .into_iter()
+            .map(|(_, element)| element.query::<T>(field))
+            .collect()
+    }
+
+    ///
+    /// Completion candidates for a partially written query.
+    ///
+    /// All complete segments of `partial` are being resolved, then the
+    /// children of the resolved node whose rendered step starts with the
+    /// trailing (incomplete) segment are returned, sorted. Returns nothing
+    /// when the complete segments couldn't be resolved.
+    ///
+    fn complete<T>(&self, partial: &str) -> Vec<String>
+    where
+        T: Tokenizer,
+    {
+        let mut steps = Vec::new();
+        let mut rest = partial;
+
+        let incomplete = loop {
+            match T::dict_parse(rest) {
+                Ok((Some(current), Some(next))) => {
+                    match T::step_parse(current) {
+                        Ok(step) => steps.push(step),
+                        Err(_) => return Vec::new(),
+                    }
+                    rest = next;
+                }
+                Ok((None, Some(next))) => rest = next,
+                Ok((current, None)) => break current.unwrap_or_default(),
+                // partial that ends with a separator (or an empty partial)
+                Err(_) if rest.is_empty() => break rest,
+                Err(_) => return Vec::new(),
+            }
+        };
+
+        if steps.is_empty() {
+            candidates::<_, T>(self, incomplete)
+        } else {
+            self.query_steps(&steps)
+                .map(|node| candidates::<_, T>(&node, incomplete))
+                .unwrap_or_default()
+        }
+    }
+
+    ///
+    /// Querying many paths at once, stops at the first failing path.
+    ///
+    fn query_many<T>(&self, paths: &[&str]) -> Result<Vec<Self::Output>, Error>
+    where
+        T: Tokenizer,
+    {
+        paths.iter().map(|path| self.query::<T>(path)).collect()
+    }
+
+    ///
+    /// Querying many paths at once, collecting every failure.
+    ///
+    /// Returns all resolved values when every path resolves, otherwise
+    /// returns each failure paired with the index of its path in `paths`.
+    ///
+    fn query_all_or_errors<T>(
+        &self,
+        paths: &[&str],
+    ) -> Result<Vec<Self::Output>, Vec<(usize, Error)>>
+    where
+        T: Tokenizer,
+    {
+        let mut values = Vec::with_capacity(paths.len());
+        let mut errors = Vec::new();
+
+        for (idx, path) in paths.iter().enumerate() {
+            match self.query::<T>(path) {
+                // no need to keep values around once there is a failure
+                Ok(value) if errors.is_empty() => values.push(value),
+                Ok(_) => (),
+                Err(e) => errors.push((idx, e)),
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(values)
+        } else {
+            Err(errors)
+        }
+    }
+
+    ///
+    /// Querying by path segments that are already split, e.g from a router.
+    ///
+    /// There is no [Tokenizer](Tokenizer) involved, each part is a key when
+    /// the node is a dictionary, or an `usize` index (optionally in brackets,
+    /// e.g `[0]`) when the node is an array. Empty part is an error.
+    ///
+    fn query_path(&self, parts: &[&str]) -> Result<Self::Output, Error> {
+        let (current, next) = match parts.split_first() {
+            Some((&"", _)) | None => return Err(Error::KeyError(KeyError::EmptyKey)),
+            Some(pair) => pair,
+        };
+
+        let child = match self.query_kind() {
+            Some(QueryKind::Dictionary) => self.query_dict(current),
+            Some(QueryKind::Array) => current
+                .strip_prefix('[')
+                .and_then(|index| index.strip_suffix(']'))
+                .unwrap_or(current)
+                .parse::<usize>()
+                .map_err(|_| Error::ExpectedIndex {
+                    segment: String::from(*current),
+                })
+                .and_then(|idx| self.query_array(idx)),
+            None => Err(not_traversable(self, String::from(*current))),
+        }?;
+
+        if next.is_empty() {
+            Ok(child)
+        } else {
+            child.query_path(next)
+        }
+    }
+
+    ///
+    /// The longest distance from `Self` to any of its leaves.
+    ///
+    /// A literal has depth 0, a dictionary or an array of literals has
+    /// depth 1 and so on. An empty dictionary or array also has depth 0,
+    /// since there is no further structure under it.
+    ///
+    /// Nodes are visited through a worklist rather than recursion, so
+    /// absurdly nested documents don't overflow the stack, see
+    /// [Queryable::depth_exceeds](Queryable::depth_exceeds) to stop early.
+    ///
+    fn depth(&self) -> usize {
+        let mut deepest = 0;
+        let mut pending = self
+            .children()
+            .into_iter()
+            .map(|(_, child)| (child, 1))
+            .collect::<Vec<_>>();
+
+        while let Some((node, depth)) = pending.pop() {
+            deepest = deepest.max(depth);
+            pending.extend(
+                node.children()
+                    .into_iter()
+                    .map(|(_, child)| (child, depth + 1)),
+            );
+        }
+
+        deepest
+    }
+
+    ///
+    /// Whether [Queryable::depth](Queryable::depth) is greater than `limit`,
+    /// without descending any further than `limit + 1` levels, e.g. to
+    /// reject untrusted documents that are nested too deep.
+    ///
+    fn depth_exceeds(&self, limit: usize) -> bool {
+        let mut pending = self
+            .children()
+            .into_iter()
+            .map(|(_, child)| (child, 1))
+            .collect::<Vec<_>>();
+
+        while let Some((node, depth)) = pending.pop() {
+            if depth > limit {
+                return true;
+            }
+
+            pending.extend(
+                node.children()
+                    .into_iter()
+                    .map(|(_, child)| (child, depth + 1)),
+            );
+        }
+
+        false
+    }
+
+    ///
+    /// Resolve `path` along with the concrete path being taken, rendered by
+    /// [Tokenizer](Tokenizer) `T`.
+    ///
+    /// Steps that could match more than one node are made concrete, e.g
+    /// `[-1]` over 3 elements array is `[2]`, and a wildcard is the first
+    /// child that resolves the rest of the path.
+    ///
+    fn resolve_with_path<T>(&self, path: &str) -> Result<(Self::Output, String), Error>
+    where
+        T: Tokenizer,
+    {
+        if T::is_root(path) {
+            return self.query_self().map(|root| (root, String::new()));
+        }
+
+        let query = Query::parse::<T>(path)?;
+        let mut taken = Vec::with_capacity(query.steps().len());
+
+        let found = resolve_steps::<Self, T>(self, query.steps(), &mut taken)?;
+
+        Ok((found, T::render(&taken)))
+    }
 
-/// Tokenizer trait.
-///
-/// This trait should be implemented if you need to have custom
-/// tokenizer for parsing array index & dictionary index.
-///
-/// On how you might want to implemented it, you could see
-/// [SlashTokenizer](crate::default::SlashTokenizer) or
-/// [DefaultTokenizer](crate::default::DefaultTokenizer)
-///
-pub trait Tokenizer {
-    /// Parse key passed when [Queryable::query_kind](Queryable::query_kind)
-    /// returns [QueryKind::Array](QueryKind::Array).
     ///
-    fn index_parse(key: &str) -> Result<usize, IndexError>;
+    /// Leaves under `Self` paired with their path rendered by [Tokenizer](Tokenizer) `T`.
+    ///
+    /// Empty dictionaries or arrays under `Self` are leaves as well, since
+    /// there is nothing to descend into. `Self` itself is never included.
+    ///
+    fn flatten<T>(&self) -> Vec<(String, &Self::Output)>
+    where
+        T: Tokenizer,
+    {
+        self.leaves::<T>().collect()
+    }
 
-    /// Tokenizing path steps.
     ///
-    fn dict_parse(key: &str) -> Result<State, KeyError>;
-}
+    /// Lazy version of [Queryable::flatten](Queryable::flatten), a node is
+    /// only being descended into when the iteration reaches it.
+    ///
+    #[inline]
+    fn leaves<T>(&self) -> Leaves<'_, Self::Output, T>
+    where
+        T: Tokenizer,
+    {
+        Leaves::new(self.children())
+    }
 
-/// Queryable trait.
-///
-/// The main trait that need to be implemented by data structure.
-/// This trait assume that `Self` are sum types or linear? type.
-///
-pub trait Queryable
-where
-    Self: Sized,
-{
-    fn query<T>(&self, path: &str) -> Result<Self, Error>
+    ///
+    /// Changes from `self` into `other` by comparing their flattened leaves,
+    /// ordered by path rendered by [Tokenizer](Tokenizer) `T`.
+    ///
+    /// Elements over the length of the shorter array are added (or removed).
+    ///
+    fn diff<T>(&self, other: &Self) -> Vec<(String, Change<Self::Output>)>
+    where
+        T: Tokenizer,
+        Self::Output: PartialEq + Clone,
+    {
+        diff::diff_leaves(self.flatten::<T>(), other.flatten::<T>())
+    }
+
+    ///
+    /// Walk `Self` depth-first, calling back into `visitor` for every node.
+    ///
+    /// A scalar root is visited through
+    /// [Queryable::query_self](Queryable::query_self), it's skipped when
+    /// `Self` couldn't be represented as its output.
+    ///
+    fn accept<V>(&self, visitor: &mut V)
+    where
+        V: QueryVisitor<Self::Output>,
+    {
+        match self.query_kind() {
+            Some(kind) => visit::walk(self, kind, visitor),
+            None => {
+                if let Ok(node) = self.query_self() {
+                    visitor.visit_scalar(&node);
+                }
+            }
+        }
+    }
+
+    ///
+    /// Querying `sibling` path relative to the parent of `path`.
+    ///
+    /// `sibling` are being resolved by the same [Tokenizer](Tokenizer),
+    /// so it could be a key or an index. A path with a single segment
+    /// has no parent, returns [Error::NoParent](Error::NoParent).
+    ///
+    fn sibling<T>(&self, path: &str, sibling: &str) -> Result<Self::Output, Error>
+    where
+        T: Tokenizer,
+    {
+        let query = Query::parse::<T>(path)?;
+
+        match query.steps().split_last() {
+            Some((_, parent)) if !parent.is_empty() => {
+                resolve_steps::<Self, T>(self, parent, &mut Vec::new())
+                    .and_then(|parent| parent.query::<T>(sibling))
+            }
+            _ => Err(Error::NoParent),
+        }
+    }
+
+    ///
+    /// Consuming version of [Queryable::query](Queryable::query).
+    ///
+    /// `self` is consumed, the resolved node are being moved out of
+    /// the structure (through [Queryable::into_query_dict](Queryable::into_query_dict)
+    /// and [Queryable::into_query_array](Queryable::into_query_array)) instead
+    /// of being cloned, which matters when extracting from a large document.
+    ///
+    /// [SELF_SEGMENT](SELF_SEGMENT) is resolved the same way as in a query,
+    /// a trailing one is materialized through
+    /// [Queryable::query_self](Queryable::query_self).
+    ///
+    fn into_query<T>(self, path: &str) -> Result<Self::Output, Error>
     where
         T: Tokenizer,
     {
         let tokens = T::dict_parse(path)?;
 
+        if let (Some(SELF_SEGMENT), next) = tokens {
+            return match next {
+                Some(next) => self.into_query::<T>(next),
+                None => self.query_self(),
+            };
+        }
+
+        if let (Some(VALUES_SEGMENT), next) = tokens {
+            let values = self.query_values()?;
+
+            return match next {
+                Some(next) => values.into_query::<T>(next),
+                None => Ok(values),
+            };
+        }
+
+        if let (Some(KEYS_SEGMENT), next) = tokens {
+            if let Some(keys) = self.query_keys() {
+                let keys = keys?;
+
+                return match next {
+                    Some(next) => keys.into_query::<T>(next),
+                    None => Ok(keys),
+                };
+            }
+        }
+
         match self.query_kind() {
             Some(QueryKind::Dictionary) => match tokens {
                 (Some(key), Some(next)) => self
-                    .query_dict(key)
-                    .and_then(move |child| child.query::<T>(next)),
-                // base case
-                (Some(key), None) => self.query_dict(key),
+                    .into_query_dict(&dict_key::<T>(key)?)
+                    .map_err(|e| index_on_dict::<T>(key, e))
+                    .and_then(move |child| child.into_query::<T>(next)),
+                (Some(key), None) => self
+                    .into_query_dict(&dict_key::<T>(key)?)
+                    .map_err(|e| index_on_dict::<T>(key, e)),
                 _ => Err(Error::EmptyPath(QueryKind::Dictionary)),
             },
             Some(QueryKind::Array) => match tokens {
                 (Some(key), Some(next)) => {
-                    let index = T::index_parse(key)?;
-                    match self.query_array(index) {
-                        Ok(child) => child.query::<T>(next),
-                        _ => Err(Error::IndexNotExist(index)),
-                    }
+                    let index = array_index::<Self, T>(&self, key)?;
+                    self.into_query_array(index)
+                        .and_then(move |child| child.into_query::<T>(next))
                 }
-                // base case
                 (Some(key), None) => {
-                    let index = T::index_parse(key)?;
-                    self.query_array(index)
+                    let index = array_index::<Self, T>(&self, key)?;
+                    self.into_query_array(index)
                 }
                 _ => Err(Error::EmptyPath(QueryKind::Array)),
             },
-            _ => Err(Error::UnknownType(String::from(path))),
+            _ => Err(not_traversable(&self, String::from(path))),
+        }
+    }
+
+    ///
+    /// Querying by pre-tokenized [Step](Step)s.
+    ///
+    /// Unlike [Queryable::query](Queryable::query), there is no tokenizer
+    /// involved, each step is explicit about being an index or a key, so
+    /// a [Step::Index](Step::Index) against a dictionary (or a
+    /// [Step::Key](Step::Key) against an array) is a type error.
+    ///
+    fn query_steps(&self, steps: &[Step]) -> Result<Self::Output, Error> {
+        let (current, next) = match steps.split_first() {
+            Some(pair) => pair,
+            None => return Err(Error::KeyError(KeyError::EmptyKey)),
+        };
+
+        let child = match (self.query_kind(), current) {
+            (Some(QueryKind::Array), Step::Index(idx)) => self.query_array(*idx),
+            (Some(QueryKind::Array), Step::FromEnd(n)) => {
+                from_end(self, *n).and_then(|idx| self.query_array(idx))
+            }
+            (Some(QueryKind::Dictionary), Step::Key(key)) => self.query_dict(key),
+            (Some(QueryKind::Dictionary), Step::Index(idx)) => Err(Error::TypeError(
+                format!("[{}]", idx),
+                QueryKind::Array,
+                QueryKind::Dictionary,
+            )),
+            (Some(QueryKind::Array), Step::Key(key)) => Err(Error::TypeError(
+                String::from(key.as_ref()),
+                QueryKind::Dictionary,
+                QueryKind::Array,
+            )),
+            (Some(QueryKind::Dictionary), Step::FromEnd(n)) => Err(Error::TypeError(
+                format!("[-{}]", n),
+                QueryKind::Array,
+                QueryKind::Dictionary,
+            )),
+            (None, Step::Index(idx)) => Err(not_traversable(self, format!("[{}]", idx))),
+            (None, Step::FromEnd(n)) => Err(not_traversable(self, format!("[-{}]", n))),
+            (None, Step::Key(key)) => Err(not_traversable(self, String::from(key.as_ref()))),
+            (_, Step::Wildcard) => Err(Error::UnexpectedWildcard),
+        }?;
+
+        if next.is_empty() {
+            Ok(child)
+        } else {
+            child.query_steps(next)
         }
     }
 
@@ -94,13 +1633,364 @@ where
     /// This method need to be implemented in case `Self` supports
     /// querying by path/key `&str`.
     ///
-    fn query_dict(&self, path: &str) -> Result<Self, Error>;
+    fn query_dict(&self, path: &str) -> Result<Self::Output, Error>;
+
+    ///
+    /// Build a value out of the inline fallback of a query, e.g. `8080` of
+    /// `server.port:-8080`. Meant for scalars, like strings, numbers or booleans.
+    ///
+    /// Defaults to `None`, the query then fails the same way as without
+    /// a fallback.
+    ///
+    #[inline]
+    fn from_default_str(_text: &str) -> Option<Self::Output> {
+        None
+    }
+
+    ///
+    /// `Self` as [Queryable::Output](Queryable::Output), being resolved by a root path.
+    ///
+    /// Defaults to [KeyError::EmptyKey](KeyError::EmptyKey) for types that
+    /// couldn't represent themselves as their output, e.g. `[T; N]`.
+    ///
+    #[inline]
+    fn query_self(&self) -> Result<Self::Output, Error> {
+        Err(Error::KeyError(KeyError::EmptyKey))
+    }
+
+    ///
+    /// Index into a scalar (leaf), e.g. characters of a string.
+    ///
+    /// Being consulted when [Queryable::query_kind](Queryable::query_kind)
+    /// is `None` but there is an index segment left to resolve. Defaults to
+    /// `None`, meaning scalars couldn't be indexed.
+    ///
+    #[inline]
+    fn index_scalar(&self, _idx: usize) -> Option<Result<Self::Output, Error>> {
+        None
+    }
+
+    ///
+    /// Name of the scalar (leaf) kind of `Self`, e.g. `"null"` or
+    /// `"number"`, being reported as the `kind_hint` of
+    /// [Error::NotTraversable](Error::NotTraversable) when a path descends
+    /// into it. Defaults to `"scalar"`.
+    ///
+    #[inline]
+    fn scalar_kind(&self) -> &'static str {
+        "scalar"
+    }
+
+    ///
+    /// Consuming version of [Queryable::query_dict](Queryable::query_dict).
+    ///
+    /// Defaults to [Queryable::query_dict](Queryable::query_dict), implementors
+    /// could move the value out of `self` instead of cloning it.
+    ///
+    #[inline]
+    fn into_query_dict(self, path: &str) -> Result<Self::Output, Error> {
+        self.query_dict(path)
+    }
+
+    ///
+    /// Consuming version of [Queryable::query_array](Queryable::query_array).
+    ///
+    /// Defaults to [Queryable::query_array](Queryable::query_array), implementors
+    /// could move the value out of `self` instead of cloning it.
+    ///
+    #[inline]
+    fn into_query_array(self, idx: usize) -> Result<Self::Output, Error> {
+        self.query_array(idx)
+    }
+
+    ///
+    /// All values of a dictionary as an array, being resolved by
+    /// [VALUES_SEGMENT](VALUES_SEGMENT) in a query.
+    ///
+    /// Values should be ordered by their keys, so the position of each
+    /// value is stable across calls. Other than dictionary should return
+    /// [Error::TypeError](Error::TypeError).
+    ///
+    fn query_values(&self) -> Result<Self::Output, Error>;
+
+    ///
+    /// All keys of a dictionary as an array, being resolved by
+    /// [KEYS_SEGMENT](KEYS_SEGMENT) in a query.
+    ///
+    /// Keys should be in the same order as
+    /// [Queryable::query_values](Queryable::query_values). Defaults to
+    /// `None`, meaning [KEYS_SEGMENT](KEYS_SEGMENT) is an ordinary key.
+    ///
+    #[inline]
+    fn query_keys(&self) -> Option<Result<Self::Output, Error>> {
+        None
+    }
+
+    ///
+    /// Direct children of `Self` paired with the [Step](Step) resolving them.
+    ///
+    /// Literals (leaf) have no children. Defaults to none, structures that
+    /// can't list their children (e.g. [FnDict](crate::fn_dict::FnDict))
+    /// are then treated as leaves by the methods walking the document.
+    ///
+    #[inline]
+    fn children(&self) -> Vec<(Step<'_>, &Self::Output)> {
+        Vec::new()
+    }
 
     ///
     /// Querying based on index on `Self`.
     ///
     /// This method need to be implemented in case of `Self` supports
-    /// querying by index `usize`.
+    /// querying by index `usize`. An out of bound `idx` should be reported
+    /// as [Error::IndexNotExist](Error::IndexNotExist) along with the
+    /// length of the array.
+    ///
+    fn query_array(&self, idx: usize) -> Result<Self::Output, Error>;
+}
+
+///
+/// Mutable traversal for [Queryable](Queryable) data structure.
+///
+/// Only recursive sum types (where [Queryable::Output](Queryable::Output)
+/// is `Self`) could be mutated in place, since the node being resolved
+/// should be able to hold any other node.
+///
+pub trait QueryableMut: Queryable<Output = Self> {
+    ///
+    /// Resolve `path` into a mutable reference of its node, creating empty
+    /// dictionaries by [QueryableMut::make_empty_dict](QueryableMut::make_empty_dict)
+    /// for missing keys along the way.
+    ///
+    /// Missing array index or a scalar in the middle of the path are still an error.
+    ///
+    fn entry<T>(&mut self, path: &str) -> Result<&mut Self, Error>
+    where
+        T: Tokenizer,
+    {
+        traverse_mut::<Self, T>(self, path, true)
+    }
+
+    ///
+    /// Mutable version of [Queryable::query](Queryable::query).
+    ///
+    fn query_mut<T>(&mut self, path: &str) -> Result<&mut Self, Error>
+    where
+        T: Tokenizer,
+    {
+        traverse_mut::<Self, T>(self, path, false)
+    }
+
+    ///
+    /// Append `value` into the array at `path`.
+    ///
+    /// Returns [Error::TypeError](Error::TypeError) or
+    /// [Error::ScalarTypeError](Error::ScalarTypeError) when the node
+    /// at `path` isn't an array.
+    ///
+    fn push<T>(&mut self, path: &str, value: Self) -> Result<(), Error>
+    where
+        T: Tokenizer,
+    {
+        let node = self.query_mut::<T>(path)?;
+
+        match node.query_kind() {
+            Some(QueryKind::Array) => node.array_push(value),
+            Some(found) => Err(Error::TypeError(
+                String::from(path),
+                QueryKind::Array,
+                found,
+            )),
+            None => Err(Error::ScalarTypeError(String::from(path), QueryKind::Array)),
+        }
+    }
+
+    ///
+    /// Replace the node at `path` by `value`, creating empty dictionaries
+    /// for missing keys along the way like [QueryableMut::entry](QueryableMut::entry).
+    ///
+    fn set_at<T>(&mut self, path: &str, value: Self) -> Result<(), Error>
+    where
+        T: Tokenizer,
+    {
+        *self.entry::<T>(path)? = value;
+        Ok(())
+    }
+
+    ///
+    /// [QueryableMut::set_at](QueryableMut::set_at) every `(path, value)`
+    /// of `updates` in order, so an update could rely on the dictionaries
+    /// created by the ones before it.
+    ///
+    /// Missing dictionaries along a path are created, so a missing parent
+    /// only fails an update when it can't be created, e.g. the path runs
+    /// through a scalar or a missing array element.
+    ///
+    /// A failing update doesn't stop the rest of them, every failure is
+    /// returned together with its position in `updates`. This isn't
+    /// transactional: updates that succeeded stay applied, clone the
+    /// document beforehand to be able to roll back.
+    ///
+    fn set_many<T>(&mut self, updates: &[(&str, Self)]) -> Result<(), Vec<(usize, Error)>>
+    where
+        T: Tokenizer,
+        Self: Clone,
+    {
+        let failures = updates
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, (path, value))| {
+                self.set_at::<T>(path, value.clone())
+                    .err()
+                    .map(|e| (idx, e))
+            })
+            .collect::<Vec<_>>();
+
+        if failures.is_empty() {
+            Ok(())
+        } else {
+            Err(failures)
+        }
+    }
+
+    ///
+    /// Remove the node at `path` from its parent through
+    /// [QueryableMut::dict_remove](QueryableMut::dict_remove) or
+    /// [QueryableMut::array_remove](QueryableMut::array_remove), and return it.
+    ///
+    /// The root can't be removed, a root path is [Error::NoParent](Error::NoParent).
+    ///
+    fn remove_at<T>(&mut self, path: &str) -> Result<Self, Error>
+    where
+        T: Tokenizer,
+    {
+        if T::is_root(path) {
+            return Err(Error::NoParent);
+        }
+
+        let query = Query::parse::<T>(path)?;
+        let (last, parent) = query.steps().split_last().ok_or(Error::NoParent)?;
+
+        let node = resolve_steps_mut::<Self, T>(self, parent)?;
+
+        remove_step::<Self, T>(node, last)
+    }
+
+    ///
+    /// Apply `f` in place on the node at `path`, resolved through
+    /// [QueryableMut::query_mut](QueryableMut::query_mut).
+    ///
+    /// `f` isn't called when `path` can't be resolved, the error is
+    /// returned instead. The node might be a scalar.
+    ///
+    fn update_at<T, F>(&mut self, path: &str, f: F) -> Result<(), Error>
+    where
+        T: Tokenizer,
+        F: FnOnce(&mut Self),
+    {
+        self.query_mut::<T>(path).map(f)
+    }
+
+    ///
+    /// Copy of `self` where every scalar (a node without
+    /// [QueryKind](QueryKind)) is replaced by `f(scalar)`, while
+    /// dictionaries and arrays are rebuilt from their mapped children by
+    /// [QueryableMut::rebuild_dict](QueryableMut::rebuild_dict) and
+    /// [QueryableMut::rebuild_array](QueryableMut::rebuild_array).
+    ///
+    /// Empty dictionaries and arrays stay empty, `f` is only called on scalars.
+    ///
+    fn map_leaves<F>(&self, mut f: F) -> Self
+    where
+        F: FnMut(&Self) -> Self,
+    {
+        map_node(self, &mut f)
+    }
+
+    ///
+    /// Merge `other` into the node at `path` (the root for an empty path)
+    /// according to `strategy`, see [MergeStrategy](MergeStrategy).
+    ///
+    /// Nothing is created along the way, a missing `path` is an error and
+    /// nothing is merged.
+    ///
+    fn merge<T>(&mut self, path: &str, other: Self, strategy: MergeStrategy) -> Result<(), Error>
+    where
+        T: Tokenizer,
+    {
+        let node = if T::is_root(path) {
+            self
+        } else {
+            self.query_mut::<T>(path)?
+        };
+
+        merge::merge_into(node, other, strategy)
+    }
+
+    ///
+    /// Empty dictionary of `Self`, being used to fill missing keys.
+    ///
+    fn make_empty_dict() -> Self;
+
+    ///
+    /// Mutable reference of the value at `key` on `Self`, inserting
+    /// [QueryableMut::make_empty_dict](QueryableMut::make_empty_dict)
+    /// when `key` doesn't exist yet.
+    ///
+    fn dict_entry(&mut self, key: &str) -> Result<&mut Self, Error>;
+
+    ///
+    /// Mutable version of [Queryable::query_array](Queryable::query_array).
+    ///
+    fn query_array_mut(&mut self, idx: usize) -> Result<&mut Self, Error>;
+
+    ///
+    /// Mutable version of [Queryable::query_dict](Queryable::query_dict).
+    ///
+    fn query_dict_mut(&mut self, key: &str) -> Result<&mut Self, Error>;
+
+    ///
+    /// Append `value` at the end of `Self`, only being called when `Self`
+    /// is [QueryKind::Array](QueryKind::Array).
+    ///
+    fn array_push(&mut self, value: Self) -> Result<(), Error>;
+
+    ///
+    /// Remove `key` from `Self` and return its value, only being called
+    /// when `Self` is [QueryKind::Dictionary](QueryKind::Dictionary).
+    ///
+    fn dict_remove(&mut self, key: &str) -> Result<Self, Error>;
+
+    ///
+    /// Remove the element at `idx` from `Self` and return it, shifting the
+    /// elements after it, only being called when `Self` is
+    /// [QueryKind::Array](QueryKind::Array).
+    ///
+    fn array_remove(&mut self, idx: usize) -> Result<Self, Error>;
+
+    ///
+    /// Dictionary of `entries`, being used by
+    /// [QueryableMut::map_leaves](QueryableMut::map_leaves).
+    ///
+    /// Defaults to filling [QueryableMut::make_empty_dict](QueryableMut::make_empty_dict)
+    /// through [QueryableMut::dict_entry](QueryableMut::dict_entry).
+    ///
+    fn rebuild_dict(entries: Vec<(String, Self)>) -> Self {
+        let mut dict = Self::make_empty_dict();
+
+        for (key, value) in entries {
+            // a fresh dictionary always accepts new keys
+            if let Ok(entry) = dict.dict_entry(&key) {
+                *entry = value;
+            }
+        }
+
+        dict
+    }
+
+    ///
+    /// Array of `items`, being used by
+    /// [QueryableMut::map_leaves](QueryableMut::map_leaves).
     ///
-    fn query_array(&self, idx: usize) -> Result<Self, Error>;
+    fn rebuild_array(items: Vec<Self>) -> Self;
 }
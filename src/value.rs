@@ -0,0 +1,436 @@
+//!
+//! Reference document type.
+//!
+//! [Value](Value) is a plain JSON-like document with a complete
+//! [Queryable](Queryable) and [QueryableMut](QueryableMut) implementation,
+//! for users that don't have a document type of their own, or as an
+//! example on how to implement one.
+//!
+//! ```rust
+//! use querable::{array, default::DefaultTokenizer, dict, lookup, value::Value};
+//!
+//! let data = dict! {
+//!     "users" => array![dict! { "name" => "alice", "age" => 30 }]
+//! };
+//!
+//! let found = lookup::<_, _, DefaultTokenizer>(&data, "users.[0].age");
+//!
+//! assert_eq!(found, Ok(Value::integer(30)));
+//! assert_eq!(found.unwrap().as_i64(), Some(30));
+//! ```
+//!
+use crate::{
+    accessor::Map,
+    error::Error,
+    kind::QueryKind,
+    step::Step,
+    types::{Queryable, QueryableMut, VALUES_SEGMENT},
+};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Number {
+    Integer(i64),
+    Double(f64),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Literal {
+    Number(Number),
+    String(String),
+    Bool(bool),
+}
+
+///
+/// Document that is either a [Literal](Literal) (leaf), a dictionary
+/// or an array of [Value](Value).
+///
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Literal(Literal),
+    Dictionary(Map<Value>),
+    Array(Vec<Value>),
+}
+
+impl Value {
+    #[inline]
+    pub fn integer<V>(v: V) -> Value
+    where
+        V: Into<i64>,
+    {
+        Value::Literal(Literal::Number(Number::Integer(v.into())))
+    }
+
+    #[inline]
+    pub fn double<V>(v: V) -> Value
+    where
+        V: Into<f64>,
+    {
+        Value::Literal(Literal::Number(Number::Double(v.into())))
+    }
+
+    #[inline]
+    pub fn string<V>(v: V) -> Value
+    where
+        V: Into<String>,
+    {
+        Value::Literal(Literal::String(v.into()))
+    }
+
+    #[inline]
+    pub fn bool<V>(v: V) -> Value
+    where
+        V: Into<bool>,
+    {
+        Value::Literal(Literal::Bool(v.into()))
+    }
+
+    ///
+    /// Empty dictionary.
+    ///
+    #[inline]
+    pub fn dict() -> Value {
+        Value::Dictionary(Map::new())
+    }
+
+    ///
+    /// Integer value, `None` for anything else (doubles included).
+    ///
+    #[inline]
+    pub fn as_i64(&self) -> Option<i64> {
+        match self {
+            Value::Literal(Literal::Number(Number::Integer(v))) => Some(*v),
+            _ => None,
+        }
+    }
+
+    #[inline]
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Value::Literal(Literal::String(v)) => Some(v),
+            _ => None,
+        }
+    }
+
+    #[inline]
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            Value::Literal(Literal::Bool(v)) => Some(*v),
+            _ => None,
+        }
+    }
+}
+
+macro_rules! value_conv {
+    ($($conv:path => [$($src:ty),*]),*) => {
+        $($(impl From<$src> for Value {
+
+            #[inline]
+            fn from(v: $src) -> Self {
+                $conv(v)
+            }
+        })*)*
+    }
+}
+
+value_conv!(
+    Value::integer => [u8, u16, u32, i8, i16, i32, i64],
+    Value::double  => [f32, f64],
+    Value::string  => [String, &'static str],
+    Value::bool    => [bool]
+);
+
+///
+/// Build a [Value::Array](crate::value::Value::Array), every element is
+/// converted through `Value::from`.
+///
+/// ```rust
+/// use querable::{array, value::Value};
+///
+/// assert_eq!(array![1, "two"], Value::Array(vec![Value::integer(1), Value::string("two")]));
+/// ```
+///
+#[macro_export]
+macro_rules! array {
+    [] => ($crate::value::Value::Array(Vec::<$crate::value::Value>::new()));
+    [$($val:expr),* $(,)?] => ($crate::value::Value::Array(vec![$($crate::value::Value::from($val)),*]));
+}
+
+///
+/// Build a [Value::Dictionary](crate::value::Value::Dictionary), keys are
+/// converted through `String::from` and values through `Value::from`.
+///
+/// ```rust
+/// use querable::{dict, value::Value};
+///
+/// let data = dict! { "id" => 1, "name" => "alice" };
+///
+/// assert!(matches!(data, Value::Dictionary(ref d) if d.len() == 2));
+/// assert_eq!(dict! {}, Value::dict());
+/// ```
+///
+#[macro_export]
+macro_rules! dict {
+    ($($key:expr => $value:expr),* $(,)?) => {
+        {
+            #[allow(unused_mut)]
+            let mut _map = $crate::accessor::Map::new();
+            $(
+                let _ = _map.insert(String::from($key), $crate::value::Value::from($value));
+            )*
+            $crate::value::Value::Dictionary(_map)
+        }
+    };
+}
+
+impl Queryable for Value {
+    type Output = Value;
+
+    #[inline]
+    fn query_kind(&self) -> Option<QueryKind> {
+        match self {
+            Value::Literal(_) => None,
+            Value::Array(_) => Some(QueryKind::Array),
+            Value::Dictionary(_) => Some(QueryKind::Dictionary),
+        }
+    }
+
+    #[inline]
+    fn query_self(&self) -> Result<Self, Error> {
+        Ok(self.clone())
+    }
+
+    ///
+    /// Fallbacks are booleans (`true` or `false`), integers, doubles, or
+    /// strings otherwise.
+    ///
+    fn from_default_str(text: &str) -> Option<Self> {
+        let value = match text {
+            "true" | "false" => Value::bool(text == "true"),
+            _ => match (text.parse::<i64>(), text.parse::<f64>()) {
+                (Ok(v), _) => Value::integer(v),
+                (_, Ok(v)) => Value::double(v),
+                _ => Value::string(text),
+            },
+        };
+
+        Some(value)
+    }
+
+    ///
+    /// Strings are indexed by their characters.
+    ///
+    fn index_scalar(&self, idx: usize) -> Option<Result<Self, Error>> {
+        match self {
+            Value::Literal(Literal::String(s)) => Some(
+                s.chars()
+                    .nth(idx)
+                    .map(|c| Value::string(c.to_string()))
+                    .ok_or(Error::IndexNotExist {
+                        index: idx,
+                        len: s.chars().count(),
+                    }),
+            ),
+            _ => None,
+        }
+    }
+
+    fn query_dict(&self, path: &str) -> Result<Self, Error> {
+        match self {
+            Value::Dictionary(d) => d
+                .get(path)
+                .cloned()
+                .ok_or_else(|| Error::key_not_exist(String::from(path))),
+            Value::Array(_) => Err(Error::TypeError(
+                String::from(path),
+                QueryKind::Dictionary,
+                QueryKind::Array,
+            )),
+            _ => Err(Error::unknown_type(String::from(path))),
+        }
+    }
+
+    fn query_array(&self, idx: usize) -> Result<Self, Error> {
+        match self {
+            Value::Array(d) => d.get(idx).cloned().ok_or(Error::IndexNotExist {
+                index: idx,
+                len: d.len(),
+            }),
+            Value::Dictionary(_) => Err(Error::TypeError(
+                format!("[{}]", idx),
+                QueryKind::Array,
+                QueryKind::Dictionary,
+            )),
+            _ => Err(Error::unknown_type(format!("[{}]", idx))),
+        }
+    }
+
+    fn into_query_dict(self, path: &str) -> Result<Self, Error> {
+        match self {
+            Value::Dictionary(mut d) => d
+                .remove(path)
+                .ok_or_else(|| Error::key_not_exist(String::from(path))),
+            other => other.query_dict(path),
+        }
+    }
+
+    fn into_query_array(self, idx: usize) -> Result<Self, Error> {
+        match self {
+            Value::Array(mut d) if idx < d.len() => Ok(d.swap_remove(idx)),
+            other => other.query_array(idx),
+        }
+    }
+
+    fn query_values(&self) -> Result<Self, Error> {
+        match self {
+            Value::Dictionary(d) => {
+                let mut entries = d.iter().collect::<Vec<_>>();
+                entries.sort_by_key(|&(k, _)| k);
+
+                Ok(Value::Array(
+                    entries.into_iter().map(|(_, v)| v.clone()).collect(),
+                ))
+            }
+            Value::Array(_) => Err(Error::TypeError(
+                String::from(VALUES_SEGMENT),
+                QueryKind::Dictionary,
+                QueryKind::Array,
+            )),
+            _ => Err(Error::unknown_type(VALUES_SEGMENT)),
+        }
+    }
+
+    fn query_keys(&self) -> Option<Result<Self, Error>> {
+        match self {
+            Value::Dictionary(d) => {
+                let mut keys = d.keys().cloned().collect::<Vec<_>>();
+                keys.sort();
+
+                Some(Ok(Value::Array(
+                    keys.into_iter().map(Value::string).collect(),
+                )))
+            }
+            _ => None,
+        }
+    }
+
+    fn children(&self) -> Vec<(Step<'_>, &Self)> {
+        match self {
+            Value::Dictionary(d) => d
+                .iter()
+                .map(|(k, v)| (Step::Key(k.as_str().into()), v))
+                .collect(),
+            Value::Array(d) => d
+                .iter()
+                .enumerate()
+                .map(|(idx, v)| (Step::Index(idx), v))
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+}
+
+impl QueryableMut for Value {
+    #[inline]
+    fn make_empty_dict() -> Self {
+        Value::dict()
+    }
+
+    #[inline]
+    fn rebuild_dict(entries: Vec<(String, Self)>) -> Self {
+        Value::Dictionary(entries.into_iter().collect())
+    }
+
+    #[inline]
+    fn rebuild_array(items: Vec<Self>) -> Self {
+        Value::Array(items)
+    }
+
+    fn dict_entry(&mut self, key: &str) -> Result<&mut Self, Error> {
+        match self {
+            Value::Dictionary(d) => Ok(d
+                .entry(String::from(key))
+                .or_insert_with(Value::make_empty_dict)),
+            Value::Array(_) => Err(Error::TypeError(
+                String::from(key),
+                QueryKind::Dictionary,
+                QueryKind::Array,
+            )),
+            _ => Err(Error::unknown_type(String::from(key))),
+        }
+    }
+
+    fn query_dict_mut(&mut self, key: &str) -> Result<&mut Self, Error> {
+        match self {
+            Value::Dictionary(d) => d
+                .get_mut(key)
+                .ok_or_else(|| Error::key_not_exist(String::from(key))),
+            Value::Array(_) => Err(Error::TypeError(
+                String::from(key),
+                QueryKind::Dictionary,
+                QueryKind::Array,
+            )),
+            _ => Err(Error::unknown_type(String::from(key))),
+        }
+    }
+
+    fn query_array_mut(&mut self, idx: usize) -> Result<&mut Self, Error> {
+        match self {
+            Value::Array(d) => {
+                let len = d.len();
+                d.get_mut(idx)
+                    .ok_or(Error::IndexNotExist { index: idx, len })
+            }
+            Value::Dictionary(_) => Err(Error::TypeError(
+                format!("[{}]", idx),
+                QueryKind::Array,
+                QueryKind::Dictionary,
+            )),
+            _ => Err(Error::unknown_type(format!("[{}]", idx))),
+        }
+    }
+
+    fn dict_remove(&mut self, key: &str) -> Result<Self, Error> {
+        match self {
+            Value::Dictionary(d) => d
+                .remove(key)
+                .ok_or_else(|| Error::key_not_exist(String::from(key))),
+            Value::Array(_) => Err(Error::TypeError(
+                String::from(key),
+                QueryKind::Dictionary,
+                QueryKind::Array,
+            )),
+            _ => Err(Error::unknown_type(String::from(key))),
+        }
+    }
+
+    fn array_remove(&mut self, idx: usize) -> Result<Self, Error> {
+        match self {
+            Value::Array(d) if idx < d.len() => Ok(d.remove(idx)),
+            Value::Array(d) => Err(Error::IndexNotExist {
+                index: idx,
+                len: d.len(),
+            }),
+            Value::Dictionary(_) => Err(Error::TypeError(
+                format!("[{}]", idx),
+                QueryKind::Array,
+                QueryKind::Dictionary,
+            )),
+            _ => Err(Error::unknown_type(format!("[{}]", idx))),
+        }
+    }
+
+    fn array_push(&mut self, value: Self) -> Result<(), Error> {
+        match self {
+            Value::Array(d) => {
+                d.push(value);
+                Ok(())
+            }
+            Value::Dictionary(_) => Err(Error::TypeError(
+                String::new(),
+                QueryKind::Array,
+                QueryKind::Dictionary,
+            )),
+            _ => Err(Error::unknown_type("")),
+        }
+    }
+}
@@ -0,0 +1,227 @@
+//!
+//! Document that tracks its mutations.
+//!
+//! [Versioned](Versioned) bumps a generation counter on every mutation,
+//! [Cursor](Cursor)s created from it remember the generation they were
+//! created at, so using one after the document changed is
+//! [Error::Stale](Error::Stale) instead of silently resolving into
+//! something else, e.g. for long-lived documents that are mutated between
+//! queries.
+//!
+use crate::{
+    cursor::{find_child, index_child, key_child},
+    error::Error,
+    step::Step,
+    types::{QueryableMut, Tokenizer},
+};
+
+///
+/// Owning wrapper of `V` with a generation counter.
+///
+/// Reads go through [Versioned::get](Versioned::get), mutations only
+/// through [Versioned::query_mut](Versioned::query_mut),
+/// [Versioned::set_at](Versioned::set_at) and
+/// [Versioned::remove_at](Versioned::remove_at), each of them bumps the
+/// generation even when it fails, since missing dictionaries might have
+/// been created along the way.
+///
+#[derive(Debug, Clone, Default)]
+pub struct Versioned<V> {
+    inner: V,
+    generation: u64,
+}
+
+impl<V> Versioned<V>
+where
+    V: QueryableMut,
+{
+    #[inline]
+    pub fn new(inner: V) -> Self {
+        Versioned {
+            inner,
+            generation: 0,
+        }
+    }
+
+    #[inline]
+    pub fn get(&self) -> &V {
+        &self.inner
+    }
+
+    #[inline]
+    pub fn into_inner(self) -> V {
+        self.inner
+    }
+
+    ///
+    /// Number of mutations so far.
+    ///
+    #[inline]
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    ///
+    /// Cursor pointing at the root, valid until the next mutation.
+    ///
+    #[inline]
+    pub fn cursor(&self) -> Cursor {
+        Cursor {
+            generation: self.generation,
+            steps: Vec::new(),
+        }
+    }
+
+    ///
+    /// See [QueryableMut::query_mut](QueryableMut::query_mut).
+    ///
+    pub fn query_mut<T>(&mut self, path: &str) -> Result<&mut V, Error>
+    where
+        T: Tokenizer,
+    {
+        self.generation += 1;
+        self.inner.query_mut::<T>(path)
+    }
+
+    ///
+    /// See [QueryableMut::set_at](QueryableMut::set_at).
+    ///
+    pub fn set_at<T>(&mut self, path: &str, value: V) -> Result<(), Error>
+    where
+        T: Tokenizer,
+    {
+        self.generation += 1;
+        self.inner.set_at::<T>(path, value)
+    }
+
+    ///
+    /// See [QueryableMut::remove_at](QueryableMut::remove_at).
+    ///
+    pub fn remove_at<T>(&mut self, path: &str) -> Result<V, Error>
+    where
+        T: Tokenizer,
+    {
+        self.generation += 1;
+        self.inner.remove_at::<T>(path)
+    }
+}
+
+///
+/// Position inside a [Versioned](Versioned) document.
+///
+/// Unlike [cursor::Cursor](crate::cursor::Cursor) it doesn't borrow the
+/// document, only its path, so the document could still be mutated while
+/// the cursor is alive. Every use of the cursor after such mutation is
+/// [Error::Stale](Error::Stale).
+///
+/// The cursor only remembers a generation, not which document it was
+/// created from. Using it with another [Versioned](Versioned) (including a
+/// clone that has been mutated as many times) isn't detected, its path is
+/// just resolved against that document.
+///
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cursor {
+    generation: u64,
+    // steps from the root into the current node
+    steps: Vec<Step<'static>>,
+}
+
+impl Cursor {
+    ///
+    /// Generation of the document when the cursor was created.
+    ///
+    #[inline]
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    ///
+    /// Number of steps from the root, `0` when pointing at the root.
+    ///
+    #[inline]
+    pub fn depth(&self) -> usize {
+        self.steps.len()
+    }
+
+    ///
+    /// Path from the root into the current node rendered by
+    /// [Tokenizer](Tokenizer) `T`, empty when pointing at the root.
+    ///
+    #[inline]
+    pub fn path<T>(&self) -> String
+    where
+        T: Tokenizer,
+    {
+        T::render(&self.steps)
+    }
+
+    ///
+    /// Node the cursor points at in `doc`.
+    ///
+    pub fn current<'a, V>(&self, doc: &'a Versioned<V>) -> Result<&'a V, Error>
+    where
+        V: QueryableMut,
+    {
+        self.check(doc)?;
+
+        // the path was resolved when the cursor moved and `doc` hasn't
+        // been mutated since then
+        let mut node = doc.get();
+        for step in &self.steps {
+            node = find_child(node, |s| s == step)
+                .map(|(_, child)| child)
+                .ok_or(Error::Stale)?;
+        }
+
+        Ok(node)
+    }
+
+    ///
+    /// Move into the child `key` of the current dictionary.
+    ///
+    pub fn down_key<V>(&mut self, doc: &Versioned<V>, key: &str) -> Result<&mut Self, Error>
+    where
+        V: QueryableMut,
+    {
+        let (step, _) = key_child(self.current(doc)?, key)?;
+        self.steps.push(step.into_owned());
+
+        Ok(self)
+    }
+
+    ///
+    /// Move into the element `idx` of the current array.
+    ///
+    pub fn down_index<V>(&mut self, doc: &Versioned<V>, idx: usize) -> Result<&mut Self, Error>
+    where
+        V: QueryableMut,
+    {
+        let (step, _) = index_child(self.current(doc)?, idx)?;
+        self.steps.push(step.into_owned());
+
+        Ok(self)
+    }
+
+    ///
+    /// Move back into the parent node, [Error::NoParent](Error::NoParent)
+    /// when already pointing at the root.
+    ///
+    pub fn up<V>(&mut self, doc: &Versioned<V>) -> Result<&mut Self, Error>
+    where
+        V: QueryableMut,
+    {
+        self.check(doc)?;
+        self.steps.pop().ok_or(Error::NoParent)?;
+
+        Ok(self)
+    }
+
+    #[inline]
+    fn check<V>(&self, doc: &Versioned<V>) -> Result<(), Error> {
+        if self.generation == doc.generation {
+            Ok(())
+        } else {
+            Err(Error::Stale)
+        }
+    }
+}
@@ -0,0 +1,73 @@
+//!
+//! Visitor over [Queryable](Queryable) data structure.
+//!
+//! [Queryable::accept](Queryable::accept) walks every node depth-first
+//! through [Queryable::children](Queryable::children), calling back into
+//! a [QueryVisitor](QueryVisitor).
+//!
+use crate::{kind::QueryKind, step::Step, types::Queryable};
+
+///
+/// Callbacks for walking a [Queryable](Queryable) data structure, `N` is the
+/// node type being visited ([Queryable::Output](Queryable::Output)).
+///
+/// For each child of an array (or a dictionary),
+/// [QueryVisitor::visit_index](QueryVisitor::visit_index) (or
+/// [QueryVisitor::visit_key](QueryVisitor::visit_key)) is called before
+/// the child itself is visited. Dictionary entries are visited in the
+/// order of [Queryable::children](Queryable::children).
+///
+/// All callbacks do nothing by default.
+///
+pub trait QueryVisitor<N> {
+    #[inline]
+    fn visit_scalar(&mut self, _node: &N) {}
+
+    #[inline]
+    fn enter_array(&mut self) {}
+
+    #[inline]
+    fn leave_array(&mut self) {}
+
+    #[inline]
+    fn enter_dict(&mut self) {}
+
+    #[inline]
+    fn leave_dict(&mut self) {}
+
+    #[inline]
+    fn visit_key(&mut self, _key: &str) {}
+
+    #[inline]
+    fn visit_index(&mut self, _idx: usize) {}
+}
+
+// visit a container node, scalars are being handled by the caller
+pub(crate) fn walk<Q, V>(node: &Q, kind: QueryKind, visitor: &mut V)
+where
+    Q: Queryable,
+    V: QueryVisitor<Q::Output>,
+{
+    match kind {
+        QueryKind::Array => visitor.enter_array(),
+        QueryKind::Dictionary => visitor.enter_dict(),
+    }
+
+    for (step, child) in node.children() {
+        match step {
+            Step::Index(idx) => visitor.visit_index(idx),
+            Step::Key(key) => visitor.visit_key(&key),
+            Step::FromEnd(_) | Step::Wildcard => continue,
+        }
+
+        match child.query_kind() {
+            Some(kind) => walk(child, kind, visitor),
+            None => visitor.visit_scalar(child),
+        }
+    }
+
+    match kind {
+        QueryKind::Array => visitor.leave_array(),
+        QueryKind::Dictionary => visitor.leave_dict(),
+    }
+}
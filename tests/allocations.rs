@@ -0,0 +1,114 @@
+//!
+//! Guard against heap allocations in tokenizers.
+//!
+//! Allocations are counted per thread, so other tests running
+//! in parallel won't be accounted.
+//!
+extern crate querable;
+
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    borrow::Cow,
+    cell::Cell,
+};
+
+use querable::{
+    default::{DefaultTokenizer, SlashTokenizer},
+    error::Error,
+    kind::QueryKind,
+    lookup_cow,
+    step::Step,
+    types::{Queryable, Tokenizer},
+};
+
+struct CountingAllocator;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+fn allocations<F: FnOnce()>(f: F) -> usize {
+    let before = ALLOCATIONS.with(Cell::get);
+    f();
+    ALLOCATIONS.with(Cell::get) - before
+}
+
+#[test]
+fn test_slash_tokenizer_single_hop_no_allocation() {
+    let count = allocations(|| {
+        let state = SlashTokenizer::dict_parse("/0/child/id");
+        assert_eq!(state, Ok((Some("0"), Some("/child/id"))));
+    });
+
+    assert_eq!(count, 0);
+}
+
+// scalar that could be cloned without allocating
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Leaf(u8);
+
+impl Queryable for Leaf {
+    type Output = Leaf;
+
+    fn query_kind(&self) -> Option<QueryKind> {
+        None
+    }
+
+    fn query_dict(&self, path: &str) -> Result<Leaf, Error> {
+        Err(Error::unknown_type(String::from(path)))
+    }
+
+    fn query_array(&self, idx: usize) -> Result<Leaf, Error> {
+        Err(Error::unknown_type(format!("[{}]", idx)))
+    }
+
+    fn query_values(&self) -> Result<Leaf, Error> {
+        Err(Error::unknown_type("@values"))
+    }
+
+    fn children(&self) -> Vec<(Step<'_>, &Leaf)> {
+        Vec::new()
+    }
+}
+
+#[test]
+fn test_lookup_cow_borrowed_no_allocation() {
+    let data = [Leaf(0), Leaf(1), Leaf(2)];
+
+    let count = allocations(|| {
+        let found = lookup_cow::<_, DefaultTokenizer>(&data, Cow::Borrowed("[1]"));
+        assert_eq!(found, Ok(Leaf(1)));
+    });
+
+    assert_eq!(count, 0);
+}
+
+#[test]
+fn test_static_miss_no_allocation() {
+    let count = allocations(|| {
+        let found = Leaf(0).query_values();
+        assert_eq!(found, Err(Error::unknown_type("@values")));
+    });
+
+    assert_eq!(count, 0);
+
+    // borrowed and owned names are still equal
+    assert_eq!(
+        Leaf(0).query_values(),
+        Err(Error::UnknownType(String::from("@values").into()))
+    );
+}